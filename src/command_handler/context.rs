@@ -4,17 +4,18 @@ use twilight_http::Client as HttpClient;
 use twilight_model::{
     application::interaction::{
         Interaction,
-        application_command::{CommandData, CommandOptionValue},
+        application_command::{CommandData, CommandDataOption, CommandOptionValue},
     },
-    channel::Message,
+    channel::{Attachment, Message},
+    guild::Permissions,
     id::{
         Id,
-        marker::{ChannelMarker, GuildMarker, MessageMarker},
+        marker::{AttachmentMarker, ChannelMarker, GuildMarker, MessageMarker, RoleMarker},
     },
 };
 
 use crate::command_handler::response::CommandResponse;
-use crate::prefix_parser::Arguments;
+use crate::prefix_parser::{Arguments, parse_mention};
 
 pub trait FromCommandOptionValue: Sized {
     fn from_option_value(value: &CommandOptionValue) -> Option<Self>;
@@ -84,11 +85,37 @@ impl FromCommandOptionValue for f64 {
     }
 }
 
+/// Only the id is available here; [`CommandContext::get_attachments`]
+/// resolves it to the attachment itself.
+impl FromCommandOptionValue for Id<AttachmentMarker> {
+    fn from_option_value(value: &CommandOptionValue) -> Option<Self> {
+        if let CommandOptionValue::Attachment(id) = value {
+            Some(*id)
+        } else {
+            None
+        }
+    }
+}
+
+/// The option the user is currently typing into, as `(name, partial value)`.
+pub fn focused_option(data: &CommandData) -> Option<(&str, &str)> {
+    fn find(options: &[CommandDataOption]) -> Option<(&str, &str)> {
+        options.iter().find_map(|option| match &option.value {
+            CommandOptionValue::Focused(value, _) => Some((option.name.as_str(), value.as_str())),
+            CommandOptionValue::SubCommand(options)
+            | CommandOptionValue::SubCommandGroup(options) => find(options),
+            _ => None,
+        })
+    }
+    find(&data.options)
+}
+
 pub struct PrefixContext<'a> {
     pub message_id: Id<MessageMarker>,
     pub channel_id: Id<ChannelMarker>,
     pub message: &'a Message,
     pub parsed: Arguments<'a>,
+    pub subcommand: Option<&'a str>,
     pub prefix: String,
     pub http_client: Arc<HttpClient>,
 }
@@ -112,6 +139,9 @@ impl<'a> PrefixContext<'a> {
         if !response.components.is_empty() {
             create_message = create_message.components(&response.components);
         }
+        if !response.attachments.is_empty() {
+            create_message = create_message.attachments(&response.attachments);
+        }
 
         let response = create_message.await?;
         let message = response.model().await?;
@@ -126,6 +156,28 @@ pub struct SlashContext {
 }
 
 impl SlashContext {
+    /// The name of the invoked subcommand, if the command has any.
+    pub fn subcommand(&self) -> Option<&str> {
+        match self.data.options.first() {
+            Some(CommandDataOption {
+                name,
+                value: CommandOptionValue::SubCommand(_),
+            }) => Some(name.as_str()),
+            _ => None,
+        }
+    }
+
+    /// The options passed to the invoked command, unwrapped from their subcommand.
+    pub fn options(&self) -> &[CommandDataOption] {
+        match self.data.options.first() {
+            Some(CommandDataOption {
+                value: CommandOptionValue::SubCommand(options),
+                ..
+            }) => options,
+            _ => &self.data.options,
+        }
+    }
+
     pub async fn reply(
         &self,
         response: CommandResponse,
@@ -150,6 +202,11 @@ impl SlashContext {
             } else {
                 Some(response.components)
             },
+            attachments: if response.attachments.is_empty() {
+                None
+            } else {
+                Some(response.attachments)
+            },
             ..Default::default()
         };
 
@@ -186,6 +243,33 @@ impl<'ctx> CommandContext<'ctx> {
         }
     }
 
+    /// Replaces the content of a message sent with [`Self::reply`], e.g. to
+    /// report progress.
+    pub async fn edit_reply(
+        &self,
+        message: &twilight_model::channel::Message,
+        content: &str,
+    ) -> anyhow::Result<()> {
+        match self {
+            CommandContext::Prefix(prefix_ctx) => {
+                prefix_ctx
+                    .http_client
+                    .update_message(message.channel_id, message.id)
+                    .content(Some(content))
+                    .await?;
+            }
+            CommandContext::Slash(slash_ctx) => {
+                slash_ctx
+                    .http_client
+                    .interaction(slash_ctx.interaction.application_id)
+                    .update_response(&slash_ctx.interaction.token)
+                    .content(Some(content))
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+
     pub async fn reply_error(
         &self,
         error: &anyhow::Error,
@@ -214,8 +298,7 @@ impl<'ctx> CommandContext<'ctx> {
             }
             CommandContext::Slash(slash_ctx) => slash_ctx
                 .as_ref()
-                .data
-                .options
+                .options()
                 .iter()
                 .find(|opt| opt.name == name)
                 .and_then(|opt_data| {
@@ -264,6 +347,64 @@ impl<'ctx> CommandContext<'ctx> {
         }
     }
 
+    /// A string option. Prefix commands take it in double quotes to allow
+    /// spaces, or as one word.
+    pub fn get_quoted_arg(&mut self, name: &str) -> Option<String> {
+        match self {
+            CommandContext::Prefix(prefix_ctx) => prefix_ctx.parsed.next_quoted().map(str::to_owned),
+            CommandContext::Slash(_) => self.get_arg(name),
+        }
+    }
+
+    /// A user, role or channel option. Prefix commands take mentions as well
+    /// as raw ids.
+    pub fn get_id<T>(&mut self, name: &str) -> Option<Id<T>> {
+        let id = match self {
+            CommandContext::Prefix(_) => self
+                .get_arg::<String>(name)
+                .and_then(|arg| parse_mention(&arg)),
+            CommandContext::Slash(_) => self.get_arg::<u64>(name),
+        };
+        id.and_then(Id::new_checked)
+    }
+
+    /// The file attached for option `name`. Prefix commands use the first
+    /// attachment of the invoking message.
+    pub fn get_attachment(&self, name: &str) -> Option<Attachment> {
+        self.get_attachments(name).into_iter().next()
+    }
+
+    /// The files attached for option `name`. Prefix commands use the
+    /// attachments of the invoking message, or of the message it replies to.
+    pub fn get_attachments(&self, name: &str) -> Vec<Attachment> {
+        match self {
+            CommandContext::Prefix(prefix_ctx) => {
+                let message = prefix_ctx.message;
+                match &message.referenced_message {
+                    Some(replied_to) if message.attachments.is_empty() => {
+                        replied_to.attachments.clone()
+                    }
+                    _ => message.attachments.clone(),
+                }
+            }
+            CommandContext::Slash(slash_ctx) => slash_ctx
+                .options()
+                .iter()
+                .filter(|opt| opt.name == name)
+                .find_map(|opt| Id::<AttachmentMarker>::from_option_value(&opt.value))
+                .and_then(|id| slash_ctx.data.resolved.as_ref()?.attachments.get(&id).cloned())
+                .into_iter()
+                .collect(),
+        }
+    }
+
+    pub fn subcommand(&self) -> Option<&str> {
+        match self {
+            CommandContext::Prefix(prefix_ctx) => prefix_ctx.subcommand,
+            CommandContext::Slash(slash_ctx) => slash_ctx.subcommand(),
+        }
+    }
+
     pub fn author(&self) -> Option<&twilight_model::user::User> {
         match self {
             CommandContext::Prefix(prefix_ctx) => Some(&prefix_ctx.message.author),
@@ -271,6 +412,26 @@ impl<'ctx> CommandContext<'ctx> {
         }
     }
 
+    /// The roles of the invoking member; empty outside guilds.
+    pub fn member_roles(&self) -> Vec<Id<RoleMarker>> {
+        let roles = match self {
+            CommandContext::Prefix(prefix_ctx) => prefix_ctx.message.member.as_ref().map(|m| &m.roles),
+            CommandContext::Slash(slash_ctx) => slash_ctx.interaction.member.as_ref().map(|m| &m.roles),
+        };
+        roles.cloned().unwrap_or_default()
+    }
+
+    /// The invoking member's permissions in the channel. Only interactions
+    /// carry them, so this is `None` for prefix commands.
+    pub fn member_permissions(&self) -> Option<Permissions> {
+        match self {
+            CommandContext::Prefix(_) => None,
+            CommandContext::Slash(slash_ctx) => {
+                slash_ctx.interaction.member.as_ref().and_then(|m| m.permissions)
+            }
+        }
+    }
+
     pub fn guild_id(&self) -> Option<Id<GuildMarker>> {
         match self {
             CommandContext::Prefix(prefix_ctx) => prefix_ctx.message.guild_id,
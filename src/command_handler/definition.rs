@@ -5,7 +5,10 @@ use songbird::Songbird;
 use twilight_http::Client as HttpClient;
 use twilight_interactions::command::CreateCommand;
 use twilight_model::{
-    application::interaction::{Interaction, application_command::CommandData},
+    application::{
+        command::CommandOptionType,
+        interaction::{Interaction, application_command::CommandData},
+    },
     channel::Message,
 };
 
@@ -22,16 +25,28 @@ where
 {
     async fn execute<'ctx>(state: S, cmd_ctx: CommandContext<'ctx>) -> anyhow::Result<()>;
 
+    /// Answers autocomplete requests for the command's options. Commands
+    /// without autocompleted options never receive any.
+    async fn autocomplete(
+        _state: S,
+        _interaction: Interaction,
+        _data: CommandData,
+    ) -> anyhow::Result<()> {
+        Ok(())
+    }
+
     async fn execute_prefix_command<'msg_lifetime>(
         state: S,
         message_ref: &'msg_lifetime Message,
         arguments: Arguments<'msg_lifetime>,
+        subcommand: Option<&'msg_lifetime str>,
         prefix_str: String,
     ) -> anyhow::Result<()> {
         let prefix_ctx = super::context::PrefixContext {
             message_id: message_ref.id,
             channel_id: message_ref.channel_id,
             parsed: arguments.clone(),
+            subcommand,
             prefix: prefix_str.clone(),
             http_client: state.http_client(),
             message: message_ref,
@@ -43,6 +58,7 @@ where
                 message_id: message_ref.id,
                 channel_id: message_ref.channel_id,
                 parsed: arguments.clone(),
+                subcommand,
                 prefix: prefix_str,
                 http_client: state.http_client(),
                 message: message_ref,
@@ -98,18 +114,33 @@ where
     pub name: &'static str,
     pub aliases: &'static [&'static str],
     pub create_slash_data_fn: fn() -> twilight_model::application::command::Command,
+    /// Names of the subcommands declared on the slash command, if any.
+    pub subcommands: Vec<String>,
     pub slash_executor:
         fn(S, Interaction, CommandData) -> Pin<Box<dyn Future<Output = anyhow::Result<()>> + Send>>,
+    pub autocomplete_executor:
+        fn(S, Interaction, CommandData) -> Pin<Box<dyn Future<Output = anyhow::Result<()>> + Send>>,
     pub prefix_executor: for<'msg_lifetime> fn(
         S,
         &'msg_lifetime Message,
         Arguments<'msg_lifetime>,
+        Option<&'msg_lifetime str>,
         String,
     ) -> Pin<
         Box<dyn Future<Output = anyhow::Result<()>> + Send + 'msg_lifetime>,
     >,
 }
 
+/// Names of the subcommands declared on a slash command.
+pub fn subcommand_names(command: twilight_model::application::command::Command) -> Vec<String> {
+    command
+        .options
+        .into_iter()
+        .filter(|option| option.kind == CommandOptionType::SubCommand)
+        .map(|option| option.name)
+        .collect()
+}
+
 #[macro_export]
 macro_rules! command_def {
     ($state_type:ty, $command_type:ty) => {
@@ -117,11 +148,15 @@ macro_rules! command_def {
             name: <$command_type as twilight_interactions::command::CreateCommand>::NAME,
             aliases: &[],
             create_slash_data_fn: || <$command_type as twilight_interactions::command::CreateCommand>::create_command().into(),
+            subcommands: $crate::command_handler::subcommand_names(<$command_type as twilight_interactions::command::CreateCommand>::create_command().into()),
             slash_executor: |state, interaction, data| {
                 Box::pin(<$command_type as $crate::command_handler::Command<$state_type>>::execute_slash_command(state, interaction, data))
             },
-            prefix_executor: |state, message, args, prefix_str| {
-                Box::pin(<$command_type as $crate::command_handler::Command<$state_type>>::execute_prefix_command(state, message, args, prefix_str))
+            autocomplete_executor: |state, interaction, data| {
+                Box::pin(<$command_type as $crate::command_handler::Command<$state_type>>::autocomplete(state, interaction, data))
+            },
+            prefix_executor: |state, message, args, subcommand, prefix_str| {
+                Box::pin(<$command_type as $crate::command_handler::Command<$state_type>>::execute_prefix_command(state, message, args, subcommand, prefix_str))
             },
         }
     };
@@ -130,11 +165,15 @@ macro_rules! command_def {
             name: <$command_type as twilight_interactions::command::CreateCommand>::NAME,
             aliases: &[$($alias),*],
             create_slash_data_fn: || <$command_type as twilight_interactions::command::CreateCommand>::create_command().into(),
+            subcommands: $crate::command_handler::subcommand_names(<$command_type as twilight_interactions::command::CreateCommand>::create_command().into()),
             slash_executor: |state, interaction, data| {
                 Box::pin(<$command_type as $crate::command_handler::Command<$state_type>>::execute_slash_command(state, interaction, data))
             },
-            prefix_executor: |state, message, args, prefix_str| {
-                Box::pin(<$command_type as $crate::command_handler::Command<$state_type>>::execute_prefix_command(state, message, args, prefix_str))
+            autocomplete_executor: |state, interaction, data| {
+                Box::pin(<$command_type as $crate::command_handler::Command<$state_type>>::autocomplete(state, interaction, data))
+            },
+            prefix_executor: |state, message, args, subcommand, prefix_str| {
+                Box::pin(<$command_type as $crate::command_handler::Command<$state_type>>::execute_prefix_command(state, message, args, subcommand, prefix_str))
             },
         }
     };
@@ -1,10 +1,14 @@
 use twilight_model::{
-    application::interaction::{Interaction, application_command::CommandData},
+    application::interaction::{
+        Interaction,
+        application_command::{CommandData, CommandOptionValue},
+    },
     channel::Message,
 };
 
 use crate::command_handler::definition::GlobalState;
 use crate::commands::COMMANDS;
+use crate::components::reply_ephemeral;
 
 pub async fn slash_handler(
     interaction: Interaction,
@@ -13,6 +17,23 @@ pub async fn slash_handler(
 ) -> anyhow::Result<()> {
     for cmd_def in COMMANDS.iter() {
         if cmd_def.name == data.name.as_str() {
+            let subcommands = &cmd_def.subcommands;
+            if !subcommands.is_empty() {
+                let subcommand = data.options.first().and_then(|option| {
+                    matches!(option.value, CommandOptionValue::SubCommand(_))
+                        .then_some(option.name.as_str())
+                });
+                if !subcommand.is_some_and(|name| subcommands.iter().any(|s| s == name)) {
+                    tracing::warn!("Unknown subcommand for slash command {}: {:?}", data.name, subcommand);
+                    // Most likely a stale registration; tell the user instead of timing out.
+                    return reply_ephemeral(
+                        &state,
+                        &interaction,
+                        format!("`/{}` doesn't have that subcommand anymore.", data.name),
+                    )
+                    .await;
+                }
+            }
             return (cmd_def.slash_executor)(state, interaction, data).await;
         }
     }
@@ -20,6 +41,20 @@ pub async fn slash_handler(
     Ok(())
 }
 
+pub async fn autocomplete_handler(
+    interaction: Interaction,
+    data: CommandData,
+    state: GlobalState,
+) -> anyhow::Result<()> {
+    for cmd_def in COMMANDS.iter() {
+        if cmd_def.name == data.name.as_str() {
+            return (cmd_def.autocomplete_executor)(state, interaction, data).await;
+        }
+    }
+    tracing::warn!("Autocomplete for unknown slash command: {}", data.name);
+    Ok(())
+}
+
 pub async fn prefix_handler(
     message: Message,
     configured_prefix: &str,
@@ -31,12 +66,26 @@ pub async fn prefix_handler(
 
     if let Some(parsed_command) = crate::prefix_parser::parse(&message.content, configured_prefix) {
         let command_name = parsed_command.command;
-        let arguments = parsed_command.arguments();
+        let mut arguments = parsed_command.arguments();
         let prefix_string = configured_prefix.to_string();
 
         for cmd_def in COMMANDS.iter() {
             if cmd_def.name == command_name || cmd_def.aliases.contains(&command_name) {
-                return (cmd_def.prefix_executor)(state, &message, arguments, prefix_string).await;
+                let subcommand = arguments
+                    .clone()
+                    .next()
+                    .filter(|arg| cmd_def.subcommands.iter().any(|name| name == arg));
+                if subcommand.is_some() {
+                    arguments.next();
+                }
+                return (cmd_def.prefix_executor)(
+                    state,
+                    &message,
+                    arguments,
+                    subcommand,
+                    prefix_string,
+                )
+                .await;
             }
         }
         tracing::warn!("Unknown prefix command: {}", command_name);
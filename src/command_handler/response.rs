@@ -1,7 +1,11 @@
 use chrono::Utc;
 use twilight_model::{
+    application::command::{CommandOptionChoice, CommandOptionChoiceValue},
     channel::message::{Component, Embed},
-    http::interaction::{InteractionResponse, InteractionResponseData, InteractionResponseType},
+    http::{
+        attachment::Attachment,
+        interaction::{InteractionResponse, InteractionResponseData, InteractionResponseType},
+    },
     util::Timestamp,
 };
 
@@ -10,6 +14,7 @@ pub struct CommandResponse {
     pub embeds: Vec<Embed>,
     pub content: String,
     pub components: Vec<Component>,
+    pub attachments: Vec<Attachment>,
 }
 
 impl From<CommandResponse> for InteractionResponse {
@@ -32,6 +37,11 @@ impl From<CommandResponse> for InteractionResponse {
                 } else {
                     Some(val.components)
                 },
+                attachments: if val.attachments.is_empty() {
+                    None
+                } else {
+                    Some(val.attachments)
+                },
                 ..Default::default()
             }),
         }
@@ -43,6 +53,7 @@ pub struct CommandResponseBuilder {
     embeds: Vec<Embed>,
     content: String,
     components: Vec<Component>,
+    attachments: Vec<Attachment>,
 }
 
 impl CommandResponseBuilder {
@@ -65,15 +76,46 @@ impl CommandResponseBuilder {
         self
     }
 
+    /// Uploads a file with the response.
+    pub fn attachment<S: Into<String>>(mut self, filename: S, file: Vec<u8>) -> Self {
+        let id = self.attachments.len() as u64;
+        self.attachments
+            .push(Attachment::from_bytes(filename.into(), file, id));
+        self
+    }
+
     pub fn build(self) -> CommandResponse {
         CommandResponse {
             embeds: self.embeds,
             content: self.content,
             components: self.components,
+            attachments: self.attachments,
         }
     }
 }
 
+/// Builds the reply to an autocomplete request from `(name, value)` pairs.
+/// Discord shows at most 25 choices.
+pub fn autocomplete_response(choices: Vec<(String, String)>) -> InteractionResponse {
+    InteractionResponse {
+        kind: InteractionResponseType::ApplicationCommandAutocompleteResult,
+        data: Some(InteractionResponseData {
+            choices: Some(
+                choices
+                    .into_iter()
+                    .take(25)
+                    .map(|(name, value)| CommandOptionChoice {
+                        name,
+                        name_localizations: None,
+                        value: CommandOptionChoiceValue::String(value),
+                    })
+                    .collect(),
+            ),
+            ..Default::default()
+        }),
+    }
+}
+
 pub fn create_error_response(error: &anyhow::Error) -> CommandResponse {
     let now_utc = Utc::now();
     let timestamp_str = now_utc.to_rfc3339();
@@ -108,5 +150,6 @@ pub fn create_error_response(error: &anyhow::Error) -> CommandResponse {
         embeds: vec![embed],
         content: String::new(),
         components: Vec::new(),
+        attachments: Vec::new(),
     }
 }
@@ -24,6 +24,23 @@ pub static COMMANDS: Lazy<Vec<CommandDefinition<GlobalState>>> = Lazy::new(|| {
         command_def!(GlobalState, SkipCommand, aliases = ["s"]),
         command_def!(GlobalState, VolumeCommand, aliases = ["vol", "v"]),
         command_def!(GlobalState, LyricsCommand, aliases = ["ly"]),
+        command_def!(GlobalState, UndoCommand, aliases = ["u"]),
+        command_def!(GlobalState, WhenCommand, aliases = ["eta"]),
+        command_def!(GlobalState, TrackInfoCommand, aliases = ["ti", "info"]),
+        command_def!(GlobalState, ClipCommand, aliases = ["sfx"]),
+        command_def!(GlobalState, RadioCommand, aliases = ["fm"]),
+        command_def!(GlobalState, PodcastCommand, aliases = ["pod"]),
+        command_def!(GlobalState, BookmarkCommand, aliases = ["bm"]),
+        command_def!(GlobalState, LibraryCommand, aliases = ["lib"]),
+        command_def!(GlobalState, ImportCommand),
+        command_def!(GlobalState, PlaylistCommand, aliases = ["pl"]),
+        command_def!(GlobalState, GuildPlaylistCommand, aliases = ["gpl"]),
+        command_def!(GlobalState, LikedCommand, aliases = ["likes", "fav"]),
+        command_def!(GlobalState, StatsCommand),
+        command_def!(GlobalState, WrappedCommand),
+        command_def!(GlobalState, QuizCommand),
+        command_def!(GlobalState, VoteNextCommand, aliases = ["vn"]),
+        command_def!(GlobalState, ApprovalCommand),
         command_def!(
             GlobalState,
             HelpCommand,
@@ -0,0 +1,645 @@
+use super::play::queue_tracks;
+use super::{connect, new_playlist_track, to_query};
+use crate::command_handler::{
+    Command, CommandContext, CommandResponseBuilder, GlobalState, StateExt,
+};
+use crate::storage::{ApprovalSettings, PendingRequest};
+use crate::utils::attachment::is_upload;
+use crate::utils::permissions::is_guild_manager;
+use crate::utils::track::format_length_ms;
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+use lavalink_rs::model::track::TrackData;
+use lavalink_rs::prelude::{TrackInQueue, TrackLoadData};
+use std::time::Duration;
+use twilight_interactions::command::{CommandModel, CreateCommand};
+use twilight_model::channel::message::{
+    Component, Embed,
+    component::{ActionRow, Button, ButtonStyle},
+};
+use twilight_model::guild::Permissions;
+use twilight_model::id::{
+    Id,
+    marker::{ChannelMarker, GuildMarker, MessageMarker, RoleMarker, UserMarker},
+};
+use twilight_util::builder::embed::{EmbedBuilder, EmbedFieldBuilder, EmbedFooterBuilder};
+
+const DEFAULT_EXPIRE_MINUTES: i64 = 60;
+const MAX_EXPIRE_MINUTES: i64 = 7 * 24 * 60;
+/// Uploads play from links that stop working after about a day, so their
+/// requests expire well before that.
+const MAX_UPLOAD_EXPIRE_MINUTES: i64 = 12 * 60;
+const MINUTE_MS: i64 = 60_000;
+/// Each track is its own request, so bigger batches are left to the DJs.
+const MAX_REQUEST_TRACKS: usize = 10;
+/// Discord's limit on embed descriptions.
+const MAX_DESCRIPTION_LEN: usize = 4096;
+/// How often expired requests are cleared up.
+pub const EXPIRY_SWEEP_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(CommandModel, CreateCommand)]
+#[command(
+    name = "approval",
+    desc = "Have DJs approve song requests before they are queued."
+)]
+pub enum ApprovalCommand {
+    #[command(name = "on")]
+    On(ApprovalOnCommand),
+    #[command(name = "off")]
+    Off(ApprovalOffCommand),
+    #[command(name = "pending")]
+    Pending(ApprovalPendingCommand),
+}
+
+#[allow(unused)]
+#[derive(CommandModel, CreateCommand)]
+#[command(name = "on", desc = "Send requests to a DJ channel for approval.")]
+pub struct ApprovalOnCommand {
+    #[command(desc = "The channel the approval cards go to")]
+    channel: Id<ChannelMarker>,
+    #[command(desc = "DJs skip approval and decide on requests alongside server managers")]
+    role: Option<Id<RoleMarker>>,
+    #[command(
+        desc = "Minutes until an undecided request expires (default 60)",
+        min_value = 1,
+        max_value = 10080
+    )]
+    expire_minutes: Option<i64>,
+}
+
+#[derive(CommandModel, CreateCommand)]
+#[command(name = "off", desc = "Queue requests right away again.")]
+pub struct ApprovalOffCommand;
+
+#[derive(CommandModel, CreateCommand)]
+#[command(name = "pending", desc = "List the requests waiting for approval.")]
+pub struct ApprovalPendingCommand;
+
+/// What a DJ decided on a request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Decision {
+    Approve,
+    PlayNext,
+    Reject,
+}
+
+impl Decision {
+    pub fn from_action(action: &str) -> Option<Self> {
+        match action {
+            "approve" => Some(Decision::Approve),
+            "next" => Some(Decision::PlayNext),
+            "reject" => Some(Decision::Reject),
+            _ => None,
+        }
+    }
+}
+
+#[async_trait]
+impl Command<GlobalState> for ApprovalCommand {
+    async fn execute<'ctx>(state: GlobalState, mut cmd_ctx: CommandContext<'ctx>) -> Result<()> {
+        let guild_id = cmd_ctx
+            .guild_id()
+            .ok_or_else(|| anyhow!("This command must be used in a guild."))?;
+        let author = cmd_ctx
+            .author()
+            .ok_or_else(|| anyhow!("Interaction is missing author information."))?
+            .clone();
+        let settings = state.storage.approval_settings(guild_id)?;
+
+        let subcommand = cmd_ctx.subcommand().map(str::to_owned);
+        let response = match subcommand.as_deref() {
+            Some("on") => {
+                check_dj(
+                    &state,
+                    guild_id,
+                    author.id,
+                    (&cmd_ctx.member_roles(), cmd_ctx.member_permissions()),
+                    settings.as_ref(),
+                )?;
+                let dj_channel_id = cmd_ctx
+                    .get_id::<ChannelMarker>("channel")
+                    .ok_or_else(|| anyhow!("You must provide the channel for approval cards."))?;
+                let dj_role_id = cmd_ctx.get_id::<RoleMarker>("role");
+                let expire_minutes = cmd_ctx
+                    .get_arg::<i64>("expire_minutes")
+                    .unwrap_or(DEFAULT_EXPIRE_MINUTES)
+                    .clamp(1, MAX_EXPIRE_MINUTES);
+                state.storage.set_approval_settings(
+                    guild_id,
+                    &ApprovalSettings {
+                        dj_channel_id,
+                        dj_role_id,
+                        expire_after_ms: expire_minutes * MINUTE_MS,
+                    },
+                )?;
+                let deciders = match dj_role_id {
+                    Some(role_id) => format!("<@&{}> and server managers", role_id),
+                    None => "server managers".to_string(),
+                };
+                CommandResponseBuilder::new()
+                    .content(format!(
+                        "🛂 Requests now go to <#{}> for approval by {}. Undecided ones expire \
+                         after {} minutes.",
+                        dj_channel_id, deciders, expire_minutes
+                    ))
+                    .build()
+            }
+            Some("off") => {
+                check_dj(
+                    &state,
+                    guild_id,
+                    author.id,
+                    (&cmd_ctx.member_roles(), cmd_ctx.member_permissions()),
+                    settings.as_ref(),
+                )?;
+                if !state.storage.disable_approval(guild_id)? {
+                    return Err(anyhow!("Requests don't need approval here."));
+                }
+                CommandResponseBuilder::new()
+                    .content(
+                        "🛂 Requests are queued right away again. Pending ones can still be \
+                         decided on.",
+                    )
+                    .build()
+            }
+            Some("pending") => {
+                let requests = state.storage.pending_requests(guild_id)?;
+                CommandResponseBuilder::new()
+                    .embed(build_pending_embed(&requests))
+                    .build()
+            }
+            Some(other) => return Err(anyhow!("Unknown approval subcommand: {}", other)),
+            None => return Err(anyhow!("Use `approval on|off|pending`.")),
+        };
+
+        cmd_ctx.reply(response).await?;
+
+        Ok(())
+    }
+}
+
+fn has_dj_role(settings: Option<&ApprovalSettings>, roles: &[Id<RoleMarker>]) -> bool {
+    settings
+        .and_then(|settings| settings.dj_role_id)
+        .is_some_and(|role_id| roles.contains(&role_id))
+}
+
+fn check_dj(
+    state: &GlobalState,
+    guild_id: Id<GuildMarker>,
+    user_id: Id<UserMarker>,
+    (roles, permissions): (&[Id<RoleMarker>], Option<Permissions>),
+    settings: Option<&ApprovalSettings>,
+) -> Result<()> {
+    if is_guild_manager(&state.cache, guild_id, user_id, permissions)
+        || has_dj_role(settings, roles)
+    {
+        Ok(())
+    } else {
+        Err(anyhow!(
+            "Only the server owner, server managers or a DJ can change the approval mode."
+        ))
+    }
+}
+
+/// Whether the member may run the guild's music: the owner, members with
+/// Manage Guild, and DJs. They queue without approval and decide on the
+/// requests of everyone else.
+pub fn is_dj(
+    state: &GlobalState,
+    guild_id: Id<GuildMarker>,
+    user_id: Id<UserMarker>,
+    roles: &[Id<RoleMarker>],
+    permissions: Option<Permissions>,
+) -> Result<bool> {
+    let settings = state.storage.approval_settings(guild_id)?;
+    Ok(
+        is_guild_manager(&state.cache, guild_id, user_id, permissions)
+            || has_dj_role(settings.as_ref(), roles),
+    )
+}
+
+/// The approval settings if tracks queued by this member have to be vetted.
+/// Those who pass [`is_dj`] queue directly.
+pub(super) fn approval_needed(
+    state: &GlobalState,
+    guild_id: Id<GuildMarker>,
+    user_id: Id<UserMarker>,
+    (roles, permissions): (&[Id<RoleMarker>], Option<Permissions>),
+) -> Result<Option<ApprovalSettings>> {
+    let settings = state.storage.approval_settings(guild_id)?;
+    if is_guild_manager(&state.cache, guild_id, user_id, permissions)
+        || has_dj_role(settings.as_ref(), roles)
+    {
+        return Ok(None);
+    }
+    Ok(settings)
+}
+
+/// The member's roles as last seen in an interaction or message. A member
+/// that isn't cached has none, so they can't skip approval by accident.
+pub(super) fn cached_member_roles(
+    state: &GlobalState,
+    guild_id: Id<GuildMarker>,
+    user_id: Id<UserMarker>,
+) -> Vec<Id<RoleMarker>> {
+    state
+        .cache
+        .member(guild_id, user_id)
+        .map(|member| member.roles().to_vec())
+        .unwrap_or_default()
+}
+
+/// Where a request plays once approved: the requester's voice channel, or
+/// the bot's if they have left it.
+pub(super) fn request_voice_channel(
+    state: &GlobalState,
+    guild_id: Id<GuildMarker>,
+    user_id: Id<UserMarker>,
+) -> Option<Id<ChannelMarker>> {
+    let bot_id = state.cache.current_user().map(|user| user.id);
+    [Some(user_id), bot_id]
+        .into_iter()
+        .flatten()
+        .find_map(|id| state.cache.voice_state(id, guild_id))
+        .map(|voice_state| voice_state.channel_id())
+}
+
+/// Loads what `/play` asked for as a single track. Playlists are left to
+/// the DJs to queue themselves.
+pub(super) async fn load_request(
+    state: &GlobalState,
+    guild_id: Id<GuildMarker>,
+    song_query: String,
+) -> Result<TrackData> {
+    let loaded = state
+        .lavalink()
+        .load_tracks(guild_id, &to_query(song_query)?)
+        .await?;
+    match loaded.data {
+        Some(TrackLoadData::Track(track)) => Ok(track),
+        Some(TrackLoadData::Search(results)) => results
+            .into_iter()
+            .next()
+            .ok_or_else(|| anyhow!("No tracks found from search.")),
+        Some(TrackLoadData::Playlist(_)) => Err(anyhow!(
+            "Playlists can't be requested while requests need approval. Ask a DJ to queue it."
+        )),
+        Some(TrackLoadData::Error(e)) => Err(anyhow!("Error loading tracks: {}", e.message)),
+        None => Err(anyhow!(
+            "Failed to load tracks: received no data from Lavalink."
+        )),
+    }
+}
+
+/// Puts the tracks on the pending list and posts a card for each to the DJ
+/// channel. Returns the reply for the requester. If any card can't be
+/// posted, none of the tracks stay pending.
+pub(super) async fn request_approval(
+    state: &GlobalState,
+    guild_id: Id<GuildMarker>,
+    settings: &ApprovalSettings,
+    requester_id: Id<UserMarker>,
+    (voice_channel_id, text_channel_id): (Id<ChannelMarker>, Option<Id<ChannelMarker>>),
+    tracks: &[TrackInQueue],
+) -> Result<String> {
+    if tracks.is_empty() {
+        return Err(anyhow!("There is nothing to request."));
+    }
+    if tracks.len() > MAX_REQUEST_TRACKS {
+        return Err(anyhow!(
+            "Up to {} tracks can be requested at once while requests need approval. \
+             Ask a DJ to queue more.",
+            MAX_REQUEST_TRACKS
+        ));
+    }
+
+    let requested_at = chrono::Utc::now().timestamp_millis();
+    let mut posted = Vec::with_capacity(tracks.len());
+    let mut first_expiry = i64::MAX;
+    for track in tracks {
+        let expire_after_ms = if is_upload(&track.track) {
+            settings
+                .expire_after_ms
+                .min(MAX_UPLOAD_EXPIRE_MINUTES * MINUTE_MS)
+        } else {
+            settings.expire_after_ms
+        };
+        let expires_at = requested_at + expire_after_ms;
+        first_expiry = first_expiry.min(expires_at);
+        let id = state.storage.add_pending_request(
+            guild_id,
+            requester_id,
+            voice_channel_id,
+            text_channel_id,
+            &new_playlist_track(&track.track),
+            expires_at,
+        )?;
+        let info = &track.track.info;
+        let request = PendingRequest {
+            id,
+            guild_id,
+            requester_id,
+            voice_channel_id,
+            text_channel_id,
+            encoded: track.track.encoded.clone(),
+            title: info.title.clone(),
+            author: info.author.clone(),
+            uri: info.uri.clone(),
+            length_ms: info.length,
+            is_stream: info.is_stream,
+            card: None,
+            requested_at,
+            expires_at,
+        };
+
+        let card = async {
+            let card = state
+                .http
+                .create_message(settings.dj_channel_id)
+                .embeds(&[approval_card(&request, None)])
+                .components(&approval_buttons(request.id))
+                .await?
+                .model()
+                .await?;
+            state
+                .storage
+                .set_pending_request_card(id, card.channel_id, card.id)?;
+            anyhow::Ok(card.id)
+        }
+        .await;
+        match card {
+            Ok(card_id) => posted.push((id, Some(card_id))),
+            Err(e) => {
+                posted.push((id, None));
+                withdraw_requests(state, guild_id, settings, &posted).await;
+                return Err(anyhow!(
+                    "Could not post the request to the DJ channel: {}",
+                    e
+                ));
+            }
+        }
+    }
+
+    let what = match tracks {
+        [track] => format!("**{}**", track.track.info.title),
+        tracks => format!("{} tracks", tracks.len()),
+    };
+    Ok(format!(
+        "📨 Sent {} to the DJs for approval. I'll let you know what they decide, \
+         unless it expires <t:{}:R>.",
+        what,
+        first_expiry / 1000
+    ))
+}
+
+/// Takes back requests from a `request_approval` that failed partway, along
+/// with the cards already posted for them.
+async fn withdraw_requests(
+    state: &GlobalState,
+    guild_id: Id<GuildMarker>,
+    settings: &ApprovalSettings,
+    posted: &[(i64, Option<Id<MessageMarker>>)],
+) {
+    for &(id, card_id) in posted {
+        if let Err(e) = state.storage.take_pending_request(guild_id, id) {
+            tracing::warn!(error = ?e, "Failed to withdraw a song request");
+        }
+        if let Some(card_id) = card_id
+            && let Err(e) = state
+                .http
+                .delete_message(settings.dj_channel_id, card_id)
+                .await
+        {
+            tracing::debug!(error = ?e, "Could not delete a withdrawn approval card");
+        }
+    }
+}
+
+/// Carries out a DJ's decision and tells the requester. Returns the card as
+/// it should look now.
+pub async fn decide_request(
+    state: &GlobalState,
+    guild_id: Id<GuildMarker>,
+    dj_id: Id<UserMarker>,
+    id: i64,
+    decision: Decision,
+) -> Result<Embed> {
+    let is_own_request = state
+        .storage
+        .pending_requests(guild_id)?
+        .iter()
+        .any(|request| request.id == id && request.requester_id == dj_id);
+    if is_own_request {
+        return Err(anyhow!("You can't decide on your own request."));
+    }
+    let request = state
+        .storage
+        .take_pending_request(guild_id, id)?
+        .ok_or_else(|| anyhow!("This request was already decided on or has expired."))?;
+
+    if decision != Decision::Reject
+        && let Err(e) = queue_request(state, &request, decision == Decision::PlayNext).await
+    {
+        // Leave it pending so another DJ can try again.
+        state.storage.restore_pending_request(&request)?;
+        return Err(e);
+    }
+
+    let (status, notice) = match decision {
+        Decision::Approve => ("✅ Approved", "was approved and queued"),
+        Decision::PlayNext => ("⏭️ Approved to play next", "was approved and plays next"),
+        Decision::Reject => ("❌ Rejected", "was turned down"),
+    };
+    notify_requester(
+        state,
+        &request,
+        &format!(
+            "🎶 Your request **{}** {} by <@{}>.",
+            request.title, notice, dj_id
+        ),
+    )
+    .await;
+
+    Ok(approval_card(
+        &request,
+        Some(&format!("{} by <@{}>", status, dj_id)),
+    ))
+}
+
+/// Clears up requests nobody decided on in time, updating their cards and
+/// telling the requesters. Runs from startup on, so requests that expired
+/// while the bot was down are handled too.
+pub async fn expire_requests(state: &GlobalState) -> Result<()> {
+    let expired = state
+        .storage
+        .take_expired_requests(chrono::Utc::now().timestamp_millis())?;
+    for request in expired {
+        if let Some((channel_id, message_id)) = request.card {
+            let updated = state
+                .http
+                .update_message(channel_id, message_id)
+                .embeds(Some(&[approval_card(&request, Some("⌛ Expired"))]))
+                .components(Some(&[]))
+                .await;
+            if let Err(e) = updated {
+                tracing::debug!(error = ?e, "Could not update an expired approval card");
+            }
+        }
+        notify_requester(
+            state,
+            &request,
+            &format!(
+                "⌛ Your request **{}** expired before a DJ got to it.",
+                request.title
+            ),
+        )
+        .await;
+    }
+    Ok(())
+}
+
+async fn queue_request(
+    state: &GlobalState,
+    request: &PendingRequest,
+    play_next: bool,
+) -> Result<()> {
+    connect(
+        state.clone(),
+        request.voice_channel_id,
+        request.text_channel_id,
+        request.guild_id,
+    )
+    .await?;
+    let mut track = state
+        .lavalink()
+        .decode_track(request.guild_id, &request.encoded)
+        .await
+        .map_err(|e| anyhow!("Could not load the requested track: {}", e))?;
+    // Podcasts and uploads come with names Lavalink doesn't know.
+    track.info.title = request.title.clone();
+    track.info.author = request.author.clone();
+    queue_tracks(
+        state,
+        request.guild_id,
+        vec![TrackInQueue::from(track)],
+        request.requester_id,
+        play_next,
+    )
+    .await
+}
+
+/// DMs the requester, or mentions them where they asked if their DMs are
+/// closed.
+async fn notify_requester(state: &GlobalState, request: &PendingRequest, content: &str) {
+    let sent = async {
+        let channel = state
+            .http
+            .create_private_channel(request.requester_id)
+            .await?
+            .model()
+            .await?;
+        state
+            .http
+            .create_message(channel.id)
+            .content(content)
+            .await?;
+        anyhow::Ok(())
+    }
+    .await;
+    let Err(e) = sent else {
+        return;
+    };
+    tracing::debug!(error = ?e, "Could not DM a request decision");
+    if let Some(channel_id) = request.text_channel_id {
+        let fallback = state
+            .http
+            .create_message(channel_id)
+            .content(&format!("<@{}> {}", request.requester_id, content))
+            .await;
+        if let Err(e) = fallback {
+            tracing::warn!(error = ?e, "Failed to tell the requester about their request");
+        }
+    }
+}
+
+/// The card a DJ decides on. `status` is set once it is decided.
+fn approval_card(request: &PendingRequest, status: Option<&str>) -> Embed {
+    let title = match &request.uri {
+        Some(uri) => format!("[{}](<{}>)", request.title, uri),
+        None => request.title.clone(),
+    };
+    let length = format_length_ms(request.length_ms, request.is_stream);
+    let mut embed = EmbedBuilder::new()
+        .title("🛂 Song request")
+        .description(format!("{} by **{}**", title, request.author))
+        .color(0x1DB954)
+        .field(
+            EmbedFieldBuilder::new("Requested by", format!("<@{}>", request.requester_id)).inline(),
+        )
+        .field(EmbedFieldBuilder::new("Length", length).inline());
+    embed = match status {
+        Some(status) => embed.field(EmbedFieldBuilder::new("Decision", status)),
+        None => embed.field(
+            EmbedFieldBuilder::new("Expires", format!("<t:{}:R>", request.expires_at / 1000))
+                .inline(),
+        ),
+    };
+    embed
+        .footer(EmbedFooterBuilder::new(format!("Request #{}", request.id)))
+        .build()
+}
+
+fn approval_buttons(id: i64) -> Vec<Component> {
+    let button = |label: &str, action: &str, style| {
+        Component::Button(Button {
+            label: Some(label.to_string()),
+            custom_id: Some(format!("approval:{}:{}", action, id)),
+            style,
+            emoji: None,
+            disabled: false,
+            url: None,
+            sku_id: None,
+        })
+    };
+    vec![Component::ActionRow(ActionRow {
+        components: vec![
+            button("✅ Approve", "approve", ButtonStyle::Success),
+            button("⏭️ Play next", "next", ButtonStyle::Primary),
+            button("❌ Reject", "reject", ButtonStyle::Danger),
+        ],
+    })]
+}
+
+fn build_pending_embed(requests: &[PendingRequest]) -> Embed {
+    let mut description = String::new();
+    // Leave room for the "…and N more" line however many get listed.
+    let budget = MAX_DESCRIPTION_LEN.saturating_sub(32);
+    let mut listed = 0;
+    for request in requests {
+        let line = format!(
+            "\n`#{}` **{}** - {} · <@{}> · expires <t:{}:R>",
+            request.id,
+            request.title,
+            request.author,
+            request.requester_id,
+            request.expires_at / 1000
+        );
+        if description.chars().count() + line.chars().count() > budget {
+            break;
+        }
+        description.push_str(&line);
+        listed += 1;
+    }
+    if requests.is_empty() {
+        description.push_str("No requests are waiting for approval.");
+    } else if requests.len() > listed {
+        description.push_str(&format!("\n…and {} more", requests.len() - listed));
+    }
+    let description = description.trim_start();
+    EmbedBuilder::new()
+        .title("🛂 Pending requests")
+        .description(description)
+        .color(0x1DB954)
+        .build()
+}
@@ -0,0 +1,265 @@
+use std::time::Duration;
+
+use super::{Enqueued, enqueue};
+use crate::command_handler::{
+    Command, CommandContext, CommandResponseBuilder, GlobalState, StateExt,
+};
+use crate::storage::{Bookmark, NewBookmark};
+use crate::utils::attachment::{UPLOAD_NOT_SAVED, is_upload};
+use crate::utils::track::{RESUMED_FLAG, flag_track};
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+use lavalink_rs::prelude::TrackInQueue;
+use twilight_interactions::command::{CommandModel, CreateCommand};
+use twilight_model::channel::message::Embed;
+use twilight_util::builder::embed::{EmbedBuilder, EmbedFooterBuilder};
+
+const BOOKMARKS_PAGE_SIZE: usize = 10;
+const MAX_NOTE_LEN: usize = 200;
+
+#[derive(CommandModel, CreateCommand)]
+#[command(name = "bookmark", desc = "Save and return to spots in long tracks.")]
+pub enum BookmarkCommand {
+    #[command(name = "add")]
+    Add(BookmarkAddCommand),
+    #[command(name = "list")]
+    List(BookmarkListCommand),
+    #[command(name = "play")]
+    Play(BookmarkPlayCommand),
+    #[command(name = "remove")]
+    Remove(BookmarkRemoveCommand),
+}
+
+#[allow(unused)]
+#[derive(CommandModel, CreateCommand)]
+#[command(
+    name = "add",
+    desc = "Bookmark the current position of the playing track."
+)]
+pub struct BookmarkAddCommand {
+    #[command(desc = "A note to remember this spot by", max_length = 200)]
+    note: Option<String>,
+}
+
+#[allow(unused)]
+#[derive(CommandModel, CreateCommand)]
+#[command(name = "list", desc = "List your bookmarks.")]
+pub struct BookmarkListCommand {
+    #[command(desc = "The page to show", min_value = 1)]
+    page: Option<i64>,
+}
+
+#[allow(unused)]
+#[derive(CommandModel, CreateCommand)]
+#[command(
+    name = "play",
+    desc = "Queue a bookmarked track from its saved position."
+)]
+pub struct BookmarkPlayCommand {
+    #[command(desc = "The bookmark id from /bookmark list")]
+    id: i64,
+}
+
+#[allow(unused)]
+#[derive(CommandModel, CreateCommand)]
+#[command(name = "remove", desc = "Delete a bookmark.")]
+pub struct BookmarkRemoveCommand {
+    #[command(desc = "The bookmark id from /bookmark list")]
+    id: i64,
+}
+
+#[async_trait]
+impl Command<GlobalState> for BookmarkCommand {
+    async fn execute<'ctx>(state: GlobalState, mut cmd_ctx: CommandContext<'ctx>) -> Result<()> {
+        let author = cmd_ctx
+            .author()
+            .ok_or_else(|| anyhow!("Interaction is missing author information."))?
+            .clone();
+
+        let subcommand = cmd_ctx.subcommand().map(str::to_owned);
+        let response = match subcommand.as_deref() {
+            Some("add") => {
+                let guild_id = cmd_ctx
+                    .guild_id()
+                    .ok_or_else(|| anyhow!("This command must be used in a guild."))?;
+                let note = cmd_ctx
+                    .get_remainder_arg("note")
+                    .map(|note| note.chars().take(MAX_NOTE_LEN).collect::<String>());
+
+                let player = state
+                    .lavalink()
+                    .get_player_context(guild_id)
+                    .ok_or_else(|| anyhow!("Nothing is playing right now."))?;
+                let player_data = player.get_player().await?;
+                let track = player_data
+                    .track
+                    .ok_or_else(|| anyhow!("Nothing is playing right now."))?;
+                if track.info.is_stream {
+                    return Err(anyhow!("Livestreams can't be bookmarked."));
+                }
+                if is_upload(&track) {
+                    return Err(anyhow!(UPLOAD_NOT_SAVED));
+                }
+
+                let position_ms = player_data.state.position;
+                let id = state.storage.add_bookmark(
+                    author.id,
+                    &NewBookmark {
+                        encoded: &track.encoded,
+                        title: &track.info.title,
+                        author: &track.info.author,
+                        uri: track.info.uri.as_deref(),
+                        length_ms: track.info.length,
+                        position_ms,
+                        note: note.as_deref(),
+                    },
+                )?;
+                CommandResponseBuilder::new()
+                    .content(format!(
+                        "🔖 Saved bookmark `#{}`: **{}** at {}",
+                        id,
+                        track.info.title,
+                        format_position(position_ms)
+                    ))
+                    .build()
+            }
+            Some("list") => {
+                let page = cmd_ctx
+                    .get_arg::<i64>("page")
+                    .map_or(0, |page| usize::try_from(page - 1).unwrap_or(0));
+                let count = state.storage.bookmark_count(author.id)?;
+                let page_count = count.div_ceil(BOOKMARKS_PAGE_SIZE).max(1);
+                let page = page.min(page_count - 1);
+                let offset = page * BOOKMARKS_PAGE_SIZE;
+                let bookmarks = state
+                    .storage
+                    .bookmarks(author.id, offset, BOOKMARKS_PAGE_SIZE)?;
+                CommandResponseBuilder::new()
+                    .embed(build_bookmarks_embed(&bookmarks, page, page_count, count))
+                    .build()
+            }
+            Some("play") => {
+                let guild_id = cmd_ctx
+                    .guild_id()
+                    .ok_or_else(|| anyhow!("This command must be used in a guild."))?;
+                let id = bookmark_id(&mut cmd_ctx)?;
+                let bookmark = state
+                    .storage
+                    .bookmark(author.id, id)?
+                    .ok_or_else(|| anyhow!("You have no bookmark `#{}`.", id))?;
+                if state.cache.voice_state(author.id, guild_id).is_none() {
+                    return Err(anyhow!(
+                        "You must be in a voice channel to use this command."
+                    ));
+                }
+                let mut track = state
+                    .lavalink()
+                    .decode_track(guild_id, &bookmark.encoded)
+                    .await
+                    .map_err(|e| anyhow!("Could not restore the bookmarked track: {}", e))?;
+                flag_track(&mut track, RESUMED_FLAG);
+
+                let track = TrackInQueue {
+                    start_time: Some(Duration::from_millis(bookmark.position_ms)),
+                    ..TrackInQueue::from(track)
+                };
+                let content = match enqueue(
+                    &state,
+                    guild_id,
+                    vec![track],
+                    author.id,
+                    cmd_ctx.member_permissions(),
+                    cmd_ctx.channel_id(),
+                    false,
+                )
+                .await?
+                {
+                    Enqueued::Queued(_) => format!(
+                        "`＋` Queued **{}** from {}",
+                        bookmark.title,
+                        format_position(bookmark.position_ms)
+                    ),
+                    Enqueued::PendingApproval(reply) => reply,
+                };
+                CommandResponseBuilder::new().content(content).build()
+            }
+            Some("remove") => {
+                let id = bookmark_id(&mut cmd_ctx)?;
+                if !state.storage.delete_bookmark(author.id, id)? {
+                    return Err(anyhow!("You have no bookmark `#{}`.", id));
+                }
+                CommandResponseBuilder::new()
+                    .content(format!("🗑️ Deleted bookmark `#{}`.", id))
+                    .build()
+            }
+            Some(other) => return Err(anyhow!("Unknown bookmark subcommand: {}", other)),
+            None => return Err(anyhow!("Use `bookmark add|list|play|remove`.")),
+        };
+
+        cmd_ctx.reply(response).await?;
+
+        Ok(())
+    }
+}
+
+/// Accepts `12` as well as `#12`, as shown by `/bookmark list`.
+fn bookmark_id(cmd_ctx: &mut CommandContext<'_>) -> Result<i64> {
+    let id = if matches!(cmd_ctx, CommandContext::Prefix(_)) {
+        cmd_ctx
+            .get_arg::<String>("id")
+            .and_then(|id| id.trim_start_matches('#').parse().ok())
+    } else {
+        cmd_ctx.get_arg("id")
+    };
+    id.ok_or_else(|| anyhow!("You must provide a bookmark id from `/bookmark list`."))
+}
+
+fn format_position(position_ms: u64) -> String {
+    humantime::format_duration(Duration::from_secs(position_ms / 1000)).to_string()
+}
+
+fn build_bookmarks_embed(
+    bookmarks: &[Bookmark],
+    page: usize,
+    page_count: usize,
+    count: usize,
+) -> Embed {
+    let description = if bookmarks.is_empty() {
+        "You have no bookmarks yet. Save one with `/bookmark add`.".to_string()
+    } else {
+        bookmarks
+            .iter()
+            .map(|bookmark| {
+                let title = match &bookmark.uri {
+                    Some(uri) => format!("[{}](<{}>)", bookmark.title, uri),
+                    None => bookmark.title.clone(),
+                };
+                let mut line = format!(
+                    "`#{}` {} - {} at **{}** / {}",
+                    bookmark.id,
+                    title,
+                    bookmark.author,
+                    format_position(bookmark.position_ms),
+                    format_position(bookmark.length_ms)
+                );
+                if let Some(note) = &bookmark.note {
+                    line.push_str(&format!("\n> {}", note));
+                }
+                line
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    EmbedBuilder::new()
+        .title("🔖 Your bookmarks")
+        .description(description)
+        .color(0x1DB954)
+        .footer(EmbedFooterBuilder::new(format!(
+            "Page {}/{} • {} bookmarks",
+            page + 1,
+            page_count,
+            count
+        )))
+        .build()
+}
@@ -0,0 +1,225 @@
+use super::approval::is_dj;
+use super::connect;
+use crate::command_handler::{
+    Command, CommandContext, CommandResponseBuilder, GlobalState, StateExt,
+};
+use crate::utils::clips::{
+    MAX_CLIP_SIZE, clip_extension, find_clip, list_clips, save_clip, validate_clip_name,
+};
+use crate::utils::interrupt::interrupt_with;
+use crate::utils::track::{CLIP_FLAG, flag_track};
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+use lavalink_rs::prelude::TrackLoadData;
+use twilight_interactions::command::{CommandModel, CreateCommand};
+use twilight_model::channel::Attachment;
+use twilight_model::id::{Id, marker::GuildMarker};
+
+#[derive(CommandModel, CreateCommand)]
+#[command(name = "clip", desc = "Play or manage this server's soundboard clips.")]
+pub enum ClipCommand {
+    #[command(name = "play")]
+    Play(ClipPlayCommand),
+    #[command(name = "add")]
+    Add(ClipAddCommand),
+    #[command(name = "remove")]
+    Remove(ClipRemoveCommand),
+    #[command(name = "list")]
+    List(ClipListCommand),
+}
+
+#[allow(unused)]
+#[derive(CommandModel, CreateCommand)]
+#[command(name = "play", desc = "Play a clip over the current track.")]
+pub struct ClipPlayCommand {
+    #[command(desc = "The clip to play")]
+    name: String,
+}
+
+#[allow(unused)]
+#[derive(CommandModel, CreateCommand)]
+#[command(
+    name = "add",
+    desc = "Upload a new clip. Only for DJs and server managers."
+)]
+pub struct ClipAddCommand {
+    #[command(desc = "The name to save the clip under")]
+    name: String,
+    #[command(desc = "The audio file to use (mp3, ogg, wav, flac or m4a)")]
+    file: Attachment,
+}
+
+#[allow(unused)]
+#[derive(CommandModel, CreateCommand)]
+#[command(
+    name = "remove",
+    desc = "Delete a clip. Only for DJs and server managers."
+)]
+pub struct ClipRemoveCommand {
+    #[command(desc = "The clip to delete")]
+    name: String,
+}
+
+#[derive(CommandModel, CreateCommand)]
+#[command(name = "list", desc = "List this server's clips.")]
+pub struct ClipListCommand;
+
+#[async_trait]
+impl Command<GlobalState> for ClipCommand {
+    async fn execute<'ctx>(state: GlobalState, mut cmd_ctx: CommandContext<'ctx>) -> Result<()> {
+        let guild_id = cmd_ctx
+            .guild_id()
+            .ok_or_else(|| anyhow!("This command must be used in a guild."))?;
+        let data_dir = state.config.data_dir.clone();
+
+        let subcommand = cmd_ctx.subcommand().map(str::to_owned);
+        let content = match subcommand.as_deref() {
+            Some("list") => {
+                let names = list_clips(&data_dir, guild_id).await?;
+                if names.is_empty() {
+                    "There are no clips yet. Upload one with `/clip add`.".to_string()
+                } else {
+                    format!("🔊 Clips: {}", names.join(", "))
+                }
+            }
+            Some("add") => {
+                check_clip_manager(&state, &cmd_ctx, guild_id)?;
+                let name: String = cmd_ctx
+                    .get_arg("name")
+                    .ok_or_else(|| anyhow!("You must provide a name for the clip."))?;
+                let name = validate_clip_name(&name)?;
+                let attachment = cmd_ctx
+                    .get_attachment("file")
+                    .ok_or_else(|| anyhow!("You must attach an audio file."))?;
+
+                if attachment.size > MAX_CLIP_SIZE {
+                    return Err(anyhow!(
+                        "Clips can be at most {} KiB.",
+                        MAX_CLIP_SIZE / 1024
+                    ));
+                }
+                let extension =
+                    clip_extension(&attachment.filename, attachment.content_type.as_deref())
+                        .ok_or_else(|| {
+                            anyhow!("Clips must be mp3, ogg, wav, flac or m4a files.")
+                        })?;
+
+                let bytes = state
+                    .reqwest
+                    .get(&attachment.url)
+                    .send()
+                    .await?
+                    .error_for_status()?
+                    .bytes()
+                    .await?;
+                save_clip(&data_dir, guild_id, &name, extension, &bytes).await?;
+                format!("💾 Saved clip `{}`.", name)
+            }
+            Some("remove") => {
+                check_clip_manager(&state, &cmd_ctx, guild_id)?;
+                let name: String = cmd_ctx
+                    .get_arg("name")
+                    .ok_or_else(|| anyhow!("You must provide the clip to delete."))?;
+                let name = validate_clip_name(&name)?;
+                let path = find_clip(&data_dir, guild_id, &name)
+                    .await
+                    .ok_or_else(|| anyhow!("There is no clip called `{}`.", name))?;
+                tokio::fs::remove_file(path).await?;
+                format!("🗑️ Deleted clip `{}`.", name)
+            }
+            // `!clip airhorn` plays a clip without naming the subcommand.
+            Some("play") | None => {
+                let name = cmd_ctx
+                    .get_arg::<String>("name")
+                    .ok_or_else(|| anyhow!("You must provide the clip to play."))?;
+                let name = validate_clip_name(&name)?;
+                play_clip(&state, &cmd_ctx, &name).await?
+            }
+            Some(other) => return Err(anyhow!("Unknown clip subcommand: {}", other)),
+        };
+
+        cmd_ctx
+            .reply(CommandResponseBuilder::new().content(content).build())
+            .await?;
+
+        Ok(())
+    }
+}
+
+/// Only the owner, server managers and DJs change the soundboard.
+fn check_clip_manager(
+    state: &GlobalState,
+    cmd_ctx: &CommandContext<'_>,
+    guild_id: Id<GuildMarker>,
+) -> Result<()> {
+    let author = cmd_ctx
+        .author()
+        .ok_or_else(|| anyhow!("Interaction is missing author information."))?;
+    let roles = cmd_ctx.member_roles();
+    if is_dj(
+        state,
+        guild_id,
+        author.id,
+        &roles,
+        cmd_ctx.member_permissions(),
+    )? {
+        Ok(())
+    } else {
+        Err(anyhow!(
+            "Only the server owner, server managers or a DJ can add or remove clips."
+        ))
+    }
+}
+
+async fn play_clip(
+    state: &GlobalState,
+    cmd_ctx: &CommandContext<'_>,
+    name: &str,
+) -> Result<String> {
+    let guild_id = cmd_ctx
+        .guild_id()
+        .ok_or_else(|| anyhow!("This command must be used in a guild."))?;
+    let author = cmd_ctx
+        .author()
+        .ok_or_else(|| anyhow!("Interaction is missing author information."))?;
+    let voice_state = state
+        .cache
+        .voice_state(author.id, guild_id)
+        .ok_or_else(|| anyhow!("You must be in a voice channel to use this command."))?;
+
+    let path = find_clip(&state.config.data_dir, guild_id, name)
+        .await
+        .ok_or_else(|| anyhow!("There is no clip called `{}`.", name))?;
+    // Lavalink reads the file itself, so it needs the full path.
+    let path = std::path::absolute(path)?;
+
+    connect(
+        state.clone(),
+        voice_state.channel_id(),
+        cmd_ctx.channel_id(),
+        guild_id,
+    )
+    .await?;
+
+    let player = state
+        .lavalink()
+        .get_player_context(guild_id)
+        .ok_or_else(|| anyhow!("Player context not found. Is the bot in a voice channel?"))?;
+
+    let loaded = state
+        .lavalink()
+        .load_tracks(guild_id, &path.to_string_lossy())
+        .await?;
+    let mut track = match loaded.data {
+        Some(TrackLoadData::Track(track)) => track,
+        Some(TrackLoadData::Error(e)) => {
+            return Err(anyhow!("Error loading clip: {}", e.message));
+        }
+        _ => return Err(anyhow!("Lavalink could not load the clip file.")),
+    };
+
+    flag_track(&mut track, CLIP_FLAG);
+    interrupt_with(&player, &track).await?;
+
+    Ok(format!("🔊 Playing clip `{}`.", name))
+}
@@ -0,0 +1,650 @@
+use super::Enqueued;
+use super::playlist::{
+    MAX_PLAYLIST_NAME_LEN, MAX_PLAYLIST_TRACKS, added_message, build_tracks_embed, fit_tracks,
+    new_playlist_track, playlist_name_arg, queue_saved_tracks, tracks_to_add,
+};
+use crate::command_handler::{
+    Command, CommandContext, CommandResponseBuilder, GlobalState, autocomplete_response,
+    focused_option,
+};
+use crate::storage::{GuildPlaylist, GuildPlaylistBackup, PlaylistChange, PlaylistChangeKind};
+use crate::utils::fuzzy;
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+use twilight_interactions::command::{CommandModel, CreateCommand};
+use twilight_model::application::interaction::{Interaction, application_command::CommandData};
+use twilight_model::channel::Attachment;
+use twilight_model::channel::message::Embed;
+use twilight_model::id::{
+    Id,
+    marker::{GuildMarker, RoleMarker, UserMarker},
+};
+use twilight_util::builder::embed::{EmbedBuilder, EmbedFooterBuilder};
+
+const CHANGES_PAGE_SIZE: usize = 15;
+/// Also the most choices autocomplete can offer.
+const MAX_GUILD_PLAYLISTS: usize = 25;
+const MAX_CHOICE_LEN: usize = 100;
+const MAX_BACKUP_FILE_SIZE: u64 = 8 * 1024 * 1024;
+
+#[derive(CommandModel, CreateCommand)]
+#[command(name = "gplaylist", desc = "Playlists shared by the whole server.")]
+pub enum GuildPlaylistCommand {
+    #[command(name = "create")]
+    Create(GuildPlaylistCreateCommand),
+    #[command(name = "delete")]
+    Delete(GuildPlaylistDeleteCommand),
+    #[command(name = "add")]
+    Add(GuildPlaylistAddCommand),
+    #[command(name = "remove")]
+    Remove(GuildPlaylistRemoveCommand),
+    #[command(name = "list")]
+    List(GuildPlaylistListCommand),
+    #[command(name = "show")]
+    Show(GuildPlaylistShowCommand),
+    #[command(name = "play")]
+    Play(GuildPlaylistPlayCommand),
+    #[command(name = "allow")]
+    Allow(GuildPlaylistAllowCommand),
+    #[command(name = "disallow")]
+    Disallow(GuildPlaylistDisallowCommand),
+    #[command(name = "role")]
+    Role(GuildPlaylistRoleCommand),
+    #[command(name = "log")]
+    Log(GuildPlaylistLogCommand),
+    #[command(name = "backup")]
+    Backup(GuildPlaylistBackupCommand),
+    #[command(name = "restore")]
+    Restore(GuildPlaylistRestoreCommand),
+}
+
+#[allow(unused)]
+#[derive(CommandModel, CreateCommand)]
+#[command(name = "create", desc = "Create a shared playlist that you own.")]
+pub struct GuildPlaylistCreateCommand {
+    #[command(desc = "The playlist name", max_length = 50)]
+    name: String,
+}
+
+#[allow(unused)]
+#[derive(CommandModel, CreateCommand)]
+#[command(name = "delete", desc = "Delete a shared playlist you own.")]
+pub struct GuildPlaylistDeleteCommand {
+    #[command(desc = "The playlist", autocomplete = true)]
+    playlist: String,
+}
+
+#[allow(unused)]
+#[derive(CommandModel, CreateCommand)]
+#[command(
+    name = "add",
+    desc = "Add the current track, a search result or the whole queue to a shared playlist."
+)]
+pub struct GuildPlaylistAddCommand {
+    #[command(desc = "The playlist", autocomplete = true)]
+    playlist: String,
+    #[command(desc = "A song or URL to add instead of the current track")]
+    query: Option<String>,
+    #[command(desc = "Add the current track and everything queued")]
+    queue: Option<bool>,
+}
+
+#[allow(unused)]
+#[derive(CommandModel, CreateCommand)]
+#[command(name = "remove", desc = "Remove a track from a shared playlist.")]
+pub struct GuildPlaylistRemoveCommand {
+    #[command(desc = "The playlist", autocomplete = true)]
+    playlist: String,
+    #[command(desc = "The track number from /gplaylist show", min_value = 1)]
+    position: i64,
+}
+
+#[derive(CommandModel, CreateCommand)]
+#[command(name = "list", desc = "List the server's shared playlists.")]
+pub struct GuildPlaylistListCommand;
+
+#[allow(unused)]
+#[derive(CommandModel, CreateCommand)]
+#[command(name = "show", desc = "Show the tracks of a shared playlist.")]
+pub struct GuildPlaylistShowCommand {
+    #[command(desc = "The playlist", autocomplete = true)]
+    playlist: String,
+    #[command(desc = "The page to show", min_value = 1)]
+    page: Option<i64>,
+}
+
+#[allow(unused)]
+#[derive(CommandModel, CreateCommand)]
+#[command(name = "play", desc = "Queue a shared playlist.")]
+pub struct GuildPlaylistPlayCommand {
+    #[command(desc = "The playlist", autocomplete = true)]
+    playlist: String,
+}
+
+#[allow(unused)]
+#[derive(CommandModel, CreateCommand)]
+#[command(name = "allow", desc = "Let someone edit a shared playlist you own.")]
+pub struct GuildPlaylistAllowCommand {
+    #[command(desc = "The playlist", autocomplete = true)]
+    playlist: String,
+    #[command(desc = "The new editor")]
+    user: Id<UserMarker>,
+}
+
+#[allow(unused)]
+#[derive(CommandModel, CreateCommand)]
+#[command(
+    name = "disallow",
+    desc = "Stop someone from editing a shared playlist you own."
+)]
+pub struct GuildPlaylistDisallowCommand {
+    #[command(desc = "The playlist", autocomplete = true)]
+    playlist: String,
+    #[command(desc = "The editor to remove")]
+    user: Id<UserMarker>,
+}
+
+#[allow(unused)]
+#[derive(CommandModel, CreateCommand)]
+#[command(
+    name = "role",
+    desc = "Let everyone with a role edit a shared playlist you own."
+)]
+pub struct GuildPlaylistRoleCommand {
+    #[command(desc = "The playlist", autocomplete = true)]
+    playlist: String,
+    #[command(desc = "The role; leave out to clear it")]
+    role: Option<Id<RoleMarker>>,
+}
+
+#[allow(unused)]
+#[derive(CommandModel, CreateCommand)]
+#[command(name = "log", desc = "Show who changed a shared playlist.")]
+pub struct GuildPlaylistLogCommand {
+    #[command(desc = "The playlist", autocomplete = true)]
+    playlist: String,
+    #[command(desc = "The page to show", min_value = 1)]
+    page: Option<i64>,
+}
+
+#[derive(CommandModel, CreateCommand)]
+#[command(name = "backup", desc = "Download all shared playlists of the server.")]
+pub struct GuildPlaylistBackupCommand;
+
+#[allow(unused)]
+#[derive(CommandModel, CreateCommand)]
+#[command(
+    name = "restore",
+    desc = "Recreate shared playlists from a backup file."
+)]
+pub struct GuildPlaylistRestoreCommand {
+    #[command(desc = "The file from /gplaylist backup")]
+    file: Attachment,
+}
+
+#[async_trait]
+impl Command<GlobalState> for GuildPlaylistCommand {
+    async fn execute<'ctx>(state: GlobalState, mut cmd_ctx: CommandContext<'ctx>) -> Result<()> {
+        let guild_id = cmd_ctx
+            .guild_id()
+            .ok_or_else(|| anyhow!("This command must be used in a guild."))?;
+        let author = cmd_ctx
+            .author()
+            .ok_or_else(|| anyhow!("Interaction is missing author information."))?
+            .clone();
+        let is_guild_owner = guild_owner(&state, guild_id) == Some(author.id);
+
+        let subcommand = cmd_ctx.subcommand().map(str::to_owned);
+        let response = match subcommand.as_deref() {
+            Some("create") => {
+                let name = playlist_name_arg(&mut cmd_ctx, "name", true)?;
+                if state.storage.guild_playlists(guild_id)?.len() >= MAX_GUILD_PLAYLISTS {
+                    return Err(anyhow!(
+                        "This server can have at most {} shared playlists.",
+                        MAX_GUILD_PLAYLISTS
+                    ));
+                }
+                if state
+                    .storage
+                    .create_guild_playlist(guild_id, &name, author.id)?
+                    .is_none()
+                {
+                    return Err(anyhow!(
+                        "This server already has a playlist called `{}`.",
+                        name
+                    ));
+                }
+                CommandResponseBuilder::new()
+                    .content(format!(
+                        "📂 Created shared playlist **{}**. Let others edit it with `/gplaylist allow` or `/gplaylist role`.",
+                        name
+                    ))
+                    .build()
+            }
+            Some("delete") => {
+                let playlist = find_guild_playlist(&state, guild_id, &mut cmd_ctx, true)?;
+                check_owner(&playlist, author.id, is_guild_owner)?;
+                state.storage.delete_guild_playlist(guild_id, playlist.id)?;
+                CommandResponseBuilder::new()
+                    .content(format!(
+                        "🗑️ Deleted **{}** and its {} tracks.",
+                        playlist.name, playlist.track_count
+                    ))
+                    .build()
+            }
+            Some("add") => {
+                let playlist = find_guild_playlist(&state, guild_id, &mut cmd_ctx, false)?;
+                check_editor(
+                    &playlist,
+                    author.id,
+                    &cmd_ctx.member_roles(),
+                    is_guild_owner,
+                )?;
+                let mut tracks = tracks_to_add(&state, &mut cmd_ctx, guild_id).await?;
+                let left_out = fit_tracks(&playlist.name, playlist.track_count, &mut tracks)?;
+
+                let new_tracks: Vec<_> = tracks.iter().map(new_playlist_track).collect();
+                state
+                    .storage
+                    .add_guild_playlist_tracks(playlist.id, author.id, &new_tracks)?;
+
+                let content = added_message(&tracks, &playlist.name, left_out);
+                CommandResponseBuilder::new().content(content).build()
+            }
+            Some("remove") => {
+                let playlist = find_guild_playlist(&state, guild_id, &mut cmd_ctx, false)?;
+                check_editor(
+                    &playlist,
+                    author.id,
+                    &cmd_ctx.member_roles(),
+                    is_guild_owner,
+                )?;
+                let position = cmd_ctx
+                    .get_arg::<i64>("position")
+                    .and_then(|position| usize::try_from(position - 1).ok())
+                    .ok_or_else(|| {
+                        anyhow!("You must provide a track number from `/gplaylist show`.")
+                    })?;
+                let removed = state
+                    .storage
+                    .remove_guild_playlist_track(playlist.id, author.id, position)?
+                    .ok_or_else(|| {
+                        anyhow!("**{}** has no track {}.", playlist.name, position + 1)
+                    })?;
+                CommandResponseBuilder::new()
+                    .content(format!(
+                        "🗑️ Removed **{}** from **{}**.",
+                        removed.title, playlist.name
+                    ))
+                    .build()
+            }
+            Some("list") => {
+                let playlists = state.storage.guild_playlists(guild_id)?;
+                CommandResponseBuilder::new()
+                    .embed(build_guild_playlists_embed(&playlists))
+                    .build()
+            }
+            Some("show") => {
+                let playlist = find_guild_playlist(&state, guild_id, &mut cmd_ctx, true)?;
+                let page = cmd_ctx
+                    .get_arg::<i64>("page")
+                    .map_or(0, |page| usize::try_from(page - 1).unwrap_or(0));
+                let tracks = state.storage.guild_playlist_tracks(playlist.id)?;
+                let mut embed = build_tracks_embed(&format!("📂 {}", playlist.name), &tracks, page);
+                embed.description = Some(format!(
+                    "{}\n\n{}",
+                    describe_editors(&playlist),
+                    embed.description.unwrap_or_default()
+                ));
+                CommandResponseBuilder::new().embed(embed).build()
+            }
+            Some("play") => {
+                let playlist = find_guild_playlist(&state, guild_id, &mut cmd_ctx, true)?;
+                let tracks = state.storage.guild_playlist_tracks(playlist.id)?;
+                if tracks.is_empty() {
+                    return Err(anyhow!("**{}** has no tracks yet.", playlist.name));
+                }
+                if state.cache.voice_state(author.id, guild_id).is_none() {
+                    return Err(anyhow!(
+                        "You must be in a voice channel to use this command."
+                    ));
+                }
+                let content = match queue_saved_tracks(
+                    &state,
+                    guild_id,
+                    author.id,
+                    cmd_ctx.member_permissions(),
+                    cmd_ctx.channel_id(),
+                    &tracks,
+                )
+                .await?
+                {
+                    Enqueued::Queued(queued) => format!(
+                        "`＋` Queued shared playlist **{}** ({} tracks)",
+                        playlist.name, queued
+                    ),
+                    Enqueued::PendingApproval(reply) => reply,
+                };
+                CommandResponseBuilder::new().content(content).build()
+            }
+            Some(kind @ ("allow" | "disallow")) => {
+                let playlist = find_guild_playlist(&state, guild_id, &mut cmd_ctx, false)?;
+                check_owner(&playlist, author.id, is_guild_owner)?;
+                let user = cmd_ctx
+                    .get_id::<UserMarker>("user")
+                    .ok_or_else(|| anyhow!("You must mention a user."))?;
+                let allowed = kind == "allow";
+                let changed = state.storage.set_guild_playlist_editor(
+                    playlist.id,
+                    author.id,
+                    user,
+                    allowed,
+                )?;
+                let content = match (allowed, changed) {
+                    (true, true) => format!("✏️ <@{}> can now edit **{}**.", user, playlist.name),
+                    (true, false) => format!("<@{}> can already edit **{}**.", user, playlist.name),
+                    (false, true) => {
+                        format!("<@{}> can no longer edit **{}**.", user, playlist.name)
+                    }
+                    (false, false) => {
+                        format!("<@{}> is not an editor of **{}**.", user, playlist.name)
+                    }
+                };
+                CommandResponseBuilder::new().content(content).build()
+            }
+            Some("role") => {
+                let playlist = find_guild_playlist(&state, guild_id, &mut cmd_ctx, false)?;
+                check_owner(&playlist, author.id, is_guild_owner)?;
+                let role = cmd_ctx.get_id::<RoleMarker>("role");
+                state
+                    .storage
+                    .set_guild_playlist_role(playlist.id, author.id, role)?;
+                let content = match role {
+                    Some(role) => format!(
+                        "✏️ Everyone with <@&{}> can now edit **{}**.",
+                        role, playlist.name
+                    ),
+                    None => format!("✏️ **{}** no longer has an editor role.", playlist.name),
+                };
+                CommandResponseBuilder::new().content(content).build()
+            }
+            Some("log") => {
+                let playlist = find_guild_playlist(&state, guild_id, &mut cmd_ctx, false)?;
+                let page = cmd_ctx
+                    .get_arg::<i64>("page")
+                    .map_or(0, |page| usize::try_from(page - 1).unwrap_or(0));
+                let count = state.storage.guild_playlist_change_count(playlist.id)?;
+                let page_count = count.div_ceil(CHANGES_PAGE_SIZE).max(1);
+                let page = page.min(page_count - 1);
+                let changes = state.storage.guild_playlist_changes(
+                    playlist.id,
+                    page * CHANGES_PAGE_SIZE,
+                    CHANGES_PAGE_SIZE,
+                )?;
+                CommandResponseBuilder::new()
+                    .embed(build_changes_embed(
+                        &playlist.name,
+                        &changes,
+                        page,
+                        page_count,
+                    ))
+                    .build()
+            }
+            Some("backup") => {
+                if !is_guild_owner {
+                    return Err(anyhow!(
+                        "Only the server owner can back up shared playlists."
+                    ));
+                }
+                let backup = state.storage.backup_guild_playlists(guild_id)?;
+                if backup.playlists.is_empty() {
+                    return Err(anyhow!("This server has no shared playlists."));
+                }
+                let filename = format!(
+                    "playlists-{}-{}.json",
+                    guild_id,
+                    chrono::Utc::now().format("%Y%m%d")
+                );
+                CommandResponseBuilder::new()
+                    .content(format!(
+                        "💾 Backed up {} shared playlists. Restore them with `/gplaylist restore`.",
+                        backup.playlists.len()
+                    ))
+                    .attachment(filename, backup.to_json()?)
+                    .build()
+            }
+            Some("restore") => {
+                if !is_guild_owner {
+                    return Err(anyhow!(
+                        "Only the server owner can restore shared playlists."
+                    ));
+                }
+                let attachment = cmd_ctx.get_attachment("file").ok_or_else(|| {
+                    anyhow!("You must attach a file made by `/gplaylist backup`.")
+                })?;
+                if attachment.size > MAX_BACKUP_FILE_SIZE {
+                    return Err(anyhow!("That file is too big to be a playlist backup."));
+                }
+                let contents = state
+                    .reqwest
+                    .get(&attachment.url)
+                    .send()
+                    .await?
+                    .error_for_status()?
+                    .text()
+                    .await?;
+                let backup = GuildPlaylistBackup::from_json(&contents)?;
+                let existing = state.storage.guild_playlists(guild_id)?.len();
+                if existing + backup.playlists.len() > MAX_GUILD_PLAYLISTS {
+                    return Err(anyhow!(
+                        "Restoring would take this server over {} shared playlists.",
+                        MAX_GUILD_PLAYLISTS
+                    ));
+                }
+                let limits = (MAX_PLAYLIST_TRACKS, MAX_PLAYLIST_NAME_LEN);
+                let (restored, skipped) = state
+                    .storage
+                    .restore_guild_playlists(guild_id, &backup, limits)?;
+                let mut content = format!("💾 Restored {} shared playlists.", restored.len());
+                if !skipped.is_empty() {
+                    content.push_str(&format!(
+                        "\nSkipped because the name is taken: {}",
+                        skipped.join(", ")
+                    ));
+                }
+                CommandResponseBuilder::new().content(content).build()
+            }
+            Some(other) => return Err(anyhow!("Unknown gplaylist subcommand: {}", other)),
+            None => {
+                return Err(anyhow!(
+                    "Use `gplaylist create|delete|add|remove|list|show|play|allow|disallow|role|log|backup|restore`."
+                ));
+            }
+        };
+
+        cmd_ctx.reply(response).await?;
+
+        Ok(())
+    }
+
+    async fn autocomplete(
+        state: GlobalState,
+        interaction: Interaction,
+        data: CommandData,
+    ) -> Result<()> {
+        let Some(guild_id) = interaction.guild_id else {
+            return Ok(());
+        };
+        let query = focused_option(&data).map_or("", |(_, value)| value);
+        let choices = state
+            .storage
+            .guild_playlists(guild_id)?
+            .into_iter()
+            .filter(|playlist| query.trim().is_empty() || fuzzy::matches(query, &playlist.name))
+            .map(|playlist| {
+                let name: String = playlist.name.chars().take(MAX_CHOICE_LEN).collect();
+                (name.clone(), name)
+            })
+            .collect();
+
+        state
+            .http
+            .interaction(interaction.application_id)
+            .create_response(
+                interaction.id,
+                &interaction.token,
+                &autocomplete_response(choices),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+fn guild_owner(state: &GlobalState, guild_id: Id<GuildMarker>) -> Option<Id<UserMarker>> {
+    state.cache.guild(guild_id).map(|guild| guild.owner_id())
+}
+
+fn find_guild_playlist(
+    state: &GlobalState,
+    guild_id: Id<GuildMarker>,
+    cmd_ctx: &mut CommandContext<'_>,
+    last: bool,
+) -> Result<GuildPlaylist> {
+    let name = playlist_name_arg(cmd_ctx, "playlist", last)?;
+    state
+        .storage
+        .guild_playlist(guild_id, &name)?
+        .ok_or_else(|| anyhow!("This server has no playlist called `{}`.", name))
+}
+
+/// The playlist owner and the server owner manage editors and can delete.
+fn check_owner(
+    playlist: &GuildPlaylist,
+    user_id: Id<UserMarker>,
+    is_guild_owner: bool,
+) -> Result<()> {
+    if user_id == playlist.owner_id || is_guild_owner {
+        Ok(())
+    } else {
+        Err(anyhow!(
+            "Only <@{}> can manage **{}**.",
+            playlist.owner_id,
+            playlist.name
+        ))
+    }
+}
+
+fn check_editor(
+    playlist: &GuildPlaylist,
+    user_id: Id<UserMarker>,
+    roles: &[Id<RoleMarker>],
+    is_guild_owner: bool,
+) -> Result<()> {
+    if playlist.can_edit(user_id, roles) || is_guild_owner {
+        Ok(())
+    } else {
+        Err(anyhow!(
+            "You are not allowed to edit **{}**.",
+            playlist.name
+        ))
+    }
+}
+
+fn describe_editors(playlist: &GuildPlaylist) -> String {
+    let mut editors: Vec<String> = playlist
+        .editors
+        .iter()
+        .map(|id| format!("<@{}>", id))
+        .collect();
+    if let Some(role) = playlist.edit_role_id {
+        editors.push(format!("<@&{}>", role));
+    }
+    if editors.is_empty() {
+        format!("Owned by <@{}>", playlist.owner_id)
+    } else {
+        format!(
+            "Owned by <@{}> • Editors: {}",
+            playlist.owner_id,
+            editors.join(", ")
+        )
+    }
+}
+
+fn build_guild_playlists_embed(playlists: &[GuildPlaylist]) -> Embed {
+    let description = if playlists.is_empty() {
+        "This server has no shared playlists yet. Make one with `/gplaylist create`.".to_string()
+    } else {
+        playlists
+            .iter()
+            .map(|playlist| {
+                format!(
+                    "**{}** · {} tracks · by <@{}>",
+                    playlist.name, playlist.track_count, playlist.owner_id
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    EmbedBuilder::new()
+        .title("📂 Shared playlists")
+        .description(description)
+        .color(0x1DB954)
+        .footer(EmbedFooterBuilder::new(format!(
+            "{}/{} playlists",
+            playlists.len(),
+            MAX_GUILD_PLAYLISTS
+        )))
+        .build()
+}
+
+fn build_changes_embed(
+    name: &str,
+    changes: &[PlaylistChange],
+    page: usize,
+    page_count: usize,
+) -> Embed {
+    let description = if changes.is_empty() {
+        "No changes yet.".to_string()
+    } else {
+        changes
+            .iter()
+            .map(|change| {
+                let what = match change.kind {
+                    PlaylistChangeKind::Created => format!("created **{}**", change.detail),
+                    PlaylistChangeKind::Added => format!("added **{}**", change.detail),
+                    PlaylistChangeKind::Removed => format!("removed **{}**", change.detail),
+                    PlaylistChangeKind::EditorAdded => format!("let <@{}> edit", change.detail),
+                    PlaylistChangeKind::EditorRemoved => {
+                        format!("stopped <@{}> from editing", change.detail)
+                    }
+                    PlaylistChangeKind::RoleChanged if change.detail.is_empty() => {
+                        "cleared the editor role".to_string()
+                    }
+                    PlaylistChangeKind::RoleChanged => {
+                        format!("set the editor role to <@&{}>", change.detail)
+                    }
+                };
+                format!(
+                    "<t:{}:R> <@{}> {}",
+                    change.created_at / 1000,
+                    change.user_id,
+                    what
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    EmbedBuilder::new()
+        .title(format!("📜 Changes to {}", name))
+        .description(description)
+        .color(0x1DB954)
+        .footer(EmbedFooterBuilder::new(format!(
+            "Page {}/{}",
+            page + 1,
+            page_count
+        )))
+        .build()
+}
@@ -3,6 +3,7 @@ use crate::commands::COMMANDS;
 use anyhow::Result;
 use async_trait::async_trait;
 use twilight_interactions::command::{CommandModel, CreateCommand};
+use twilight_model::application::command::CommandOptionType;
 use twilight_util::builder::embed::{EmbedBuilder, EmbedFieldBuilder};
 
 #[derive(CommandModel, CreateCommand)]
@@ -21,9 +22,24 @@ impl Command<GlobalState> for HelpCommand {
             let slash_command = (command_def.create_slash_data_fn)();
             let mut description = format!("```{}", slash_command.description);
 
-            if !slash_command.options.is_empty() {
+            let (subcommands, arguments): (Vec<_>, Vec<_>) = slash_command
+                .options
+                .into_iter()
+                .partition(|option| option.kind == CommandOptionType::SubCommand);
+
+            if !subcommands.is_empty() {
+                description.push_str("\n\nSubcommands:");
+                for subcommand in subcommands {
+                    description.push_str(&format!(
+                        "\n{}: {}",
+                        subcommand.name, subcommand.description
+                    ));
+                }
+            }
+
+            if !arguments.is_empty() {
                 description.push_str("\n\nArguments:");
-                for option in slash_command.options {
+                for option in arguments {
                     description.push_str(&format!(
                         "\n{} ({}): {}",
                         option.name,
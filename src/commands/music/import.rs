@@ -0,0 +1,216 @@
+use std::time::{Duration, Instant};
+
+use super::approval::approval_needed;
+use super::{Enqueued, enqueue};
+use crate::command_handler::{
+    Command, CommandContext, CommandResponseBuilder, GlobalState, StateExt,
+};
+use crate::utils::playlist_file::{EntryQuery, PlaylistFormat, parse_playlist};
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+use lavalink_rs::model::track::TrackData;
+use lavalink_rs::prelude::{SearchEngines, TrackInQueue, TrackLoadData};
+use twilight_interactions::command::{CommandModel, CreateCommand};
+use twilight_model::channel::Attachment;
+use twilight_model::id::{Id, marker::GuildMarker};
+
+/// Playlist files are text, so anything bigger is almost certainly not one.
+const MAX_PLAYLIST_FILE_SIZE: u64 = 1024 * 1024;
+const MAX_IMPORT_ENTRIES: usize = 250;
+/// How many unresolved entries the summary names.
+const MAX_LISTED_UNRESOLVED: usize = 15;
+/// Discord's limit on message content.
+const MAX_MESSAGE_LEN: usize = 2000;
+/// Minimum time between edits of the progress message.
+const PROGRESS_INTERVAL: Duration = Duration::from_secs(2);
+
+#[allow(unused)]
+#[derive(CommandModel, CreateCommand)]
+#[command(
+    name = "import",
+    desc = "Queue the tracks of an M3U, PLS or XSPF playlist file."
+)]
+pub struct ImportCommand {
+    #[command(desc = "The playlist file")]
+    file: Attachment,
+}
+
+#[async_trait]
+impl Command<GlobalState> for ImportCommand {
+    async fn execute<'ctx>(state: GlobalState, cmd_ctx: CommandContext<'ctx>) -> Result<()> {
+        let guild_id = cmd_ctx
+            .guild_id()
+            .ok_or_else(|| anyhow!("This command must be used in a guild."))?;
+        let author = cmd_ctx
+            .author()
+            .ok_or_else(|| anyhow!("Interaction is missing author information."))?;
+        let attachment = cmd_ctx
+            .get_attachment("file")
+            .ok_or_else(|| anyhow!("You must attach an M3U, PLS or XSPF playlist file."))?;
+        if attachment.size > MAX_PLAYLIST_FILE_SIZE {
+            return Err(anyhow!("That file is too big to be a playlist."));
+        }
+        if state.cache.voice_state(author.id, guild_id).is_none() {
+            return Err(anyhow!(
+                "You must be in a voice channel to use this command."
+            ));
+        }
+        if approval_needed(
+            &state,
+            guild_id,
+            author.id,
+            (&cmd_ctx.member_roles(), cmd_ctx.member_permissions()),
+        )?
+        .is_some()
+        {
+            return Err(anyhow!(
+                "Requests need approval here, so ask a DJ to import playlists."
+            ));
+        }
+
+        let contents = state
+            .reqwest
+            .get(&attachment.url)
+            .send()
+            .await?
+            .error_for_status()?
+            .text()
+            .await?;
+        let format = PlaylistFormat::detect(&attachment.filename, &contents).ok_or_else(|| {
+            anyhow!(
+                "`{}` is not an M3U, PLS or XSPF playlist.",
+                attachment.filename
+            )
+        })?;
+        let mut entries = parse_playlist(format, &contents)?;
+        if entries.is_empty() {
+            return Err(anyhow!("`{}` has no entries.", attachment.filename));
+        }
+        let skipped = entries.len().saturating_sub(MAX_IMPORT_ENTRIES);
+        entries.truncate(MAX_IMPORT_ENTRIES);
+
+        let total = entries.len();
+        let progress = cmd_ctx
+            .reply(
+                CommandResponseBuilder::new()
+                    .content(format!(
+                        "📥 Importing **{}**: 0/{} entries…",
+                        attachment.filename, total
+                    ))
+                    .build(),
+            )
+            .await?;
+
+        let mut queued = 0;
+        let mut unresolved = Vec::new();
+        let mut last_update = Instant::now();
+        for (idx, entry) in entries.iter().enumerate() {
+            let resolved = match entry.query() {
+                Some(query) => resolve_entry(&state, guild_id, &query)
+                    .await
+                    .unwrap_or_else(|e| {
+                        tracing::debug!("Failed to resolve playlist entry {:?}: {:?}", entry, e);
+                        Vec::new()
+                    }),
+                None => Vec::new(),
+            };
+            if resolved.is_empty() {
+                unresolved.push(entry.label());
+            } else {
+                // Queue as we go so playback can start before the import is done.
+                let tracks = resolved.into_iter().map(TrackInQueue::from).collect();
+                if let Enqueued::Queued(count) = enqueue(
+                    &state,
+                    guild_id,
+                    tracks,
+                    author.id,
+                    cmd_ctx.member_permissions(),
+                    cmd_ctx.channel_id(),
+                    false,
+                )
+                .await?
+                {
+                    queued += count;
+                }
+            }
+
+            if last_update.elapsed() >= PROGRESS_INTERVAL {
+                last_update = Instant::now();
+                let content = format!(
+                    "📥 Importing **{}**: {}/{} entries, {} tracks found…",
+                    attachment.filename,
+                    idx + 1,
+                    total,
+                    queued
+                );
+                if let Err(e) = cmd_ctx.edit_reply(&progress, &content).await {
+                    tracing::warn!(error = ?e, "Failed to update import progress");
+                }
+            }
+        }
+
+        let summary = build_summary(&attachment.filename, queued, total, skipped, &unresolved);
+        cmd_ctx.edit_reply(&progress, &summary).await?;
+
+        Ok(())
+    }
+}
+
+/// Loads an entry: URLs directly, anything else as a search taking the top hit.
+async fn resolve_entry(
+    state: &GlobalState,
+    guild_id: Id<GuildMarker>,
+    query: &EntryQuery,
+) -> Result<Vec<TrackData>> {
+    let identifier = match query {
+        EntryQuery::Url(url) => url.clone(),
+        EntryQuery::Search(terms) => SearchEngines::Spotify.to_query(terms)?,
+    };
+    let loaded = state.lavalink().load_tracks(guild_id, &identifier).await?;
+    Ok(match loaded.data {
+        Some(TrackLoadData::Track(track)) => vec![track],
+        Some(TrackLoadData::Search(results)) => results.into_iter().take(1).collect(),
+        Some(TrackLoadData::Playlist(playlist)) => playlist.tracks,
+        Some(TrackLoadData::Error(_)) | None => Vec::new(),
+    })
+}
+
+fn build_summary(
+    filename: &str,
+    queued: usize,
+    total: usize,
+    skipped: usize,
+    unresolved: &[String],
+) -> String {
+    let mut summary = format!(
+        "📥 Imported **{}**: queued {} tracks from {}/{} entries.",
+        filename,
+        queued,
+        total - unresolved.len(),
+        total
+    );
+    if skipped > 0 {
+        summary.push_str(&format!(
+            "\nOnly the first {} entries were imported; {} were left out.",
+            MAX_IMPORT_ENTRIES, skipped
+        ));
+    }
+    if !unresolved.is_empty() {
+        summary.push_str("\n**Not found:**");
+        // Leave room for the "…and N more" line however many get listed.
+        let budget = MAX_MESSAGE_LEN.saturating_sub(32);
+        let mut listed = 0;
+        for label in unresolved.iter().take(MAX_LISTED_UNRESOLVED) {
+            let line = format!("\n• {}", label);
+            if summary.chars().count() + line.chars().count() > budget {
+                break;
+            }
+            summary.push_str(&line);
+            listed += 1;
+        }
+        if unresolved.len() > listed {
+            summary.push_str(&format!("\n…and {} more", unresolved.len() - listed));
+        }
+    }
+    summary
+}
@@ -2,6 +2,7 @@ use super::check_voice_state;
 use crate::command_handler::{
     Command, CommandContext, CommandResponseBuilder, GlobalState, StateExt,
 };
+use crate::utils::queue_history::snapshot_queue;
 use anyhow::Result;
 use async_trait::async_trait;
 use twilight_interactions::command::{CommandModel, CreateCommand};
@@ -61,6 +62,8 @@ impl Command<GlobalState> for JumpCommand {
             ));
         }
 
+        snapshot_queue(&state, guild_id).await?;
+
         for i in 0..target_idx {
             match queue.remove(0) {
                 Ok(_) => {
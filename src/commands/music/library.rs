@@ -0,0 +1,270 @@
+use std::time::Duration;
+
+use super::{Enqueued, enqueue};
+use crate::command_handler::{
+    Command, CommandContext, CommandResponseBuilder, GlobalState, StateExt,
+};
+use crate::utils::library::{LibraryTrack, rescan_library};
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+use lavalink_rs::prelude::{TrackInQueue, TrackLoadData};
+use twilight_interactions::command::{CommandModel, CreateCommand};
+use twilight_model::channel::message::Embed;
+use twilight_model::guild::Permissions;
+use twilight_model::id::{
+    Id,
+    marker::{ChannelMarker, GuildMarker, UserMarker},
+};
+use twilight_util::builder::embed::{EmbedBuilder, EmbedFooterBuilder};
+
+const MAX_SEARCH_RESULTS: usize = 10;
+/// Upper bound on how many files one `/library artist` queues.
+const MAX_QUEUED_FILES: usize = 100;
+
+#[derive(CommandModel, CreateCommand)]
+#[command(name = "library", desc = "Browse and play the local music library.")]
+pub enum LibraryCommand {
+    #[command(name = "search")]
+    Search(LibrarySearchCommand),
+    #[command(name = "play")]
+    Play(LibraryPlayCommand),
+    #[command(name = "album")]
+    Album(LibraryAlbumCommand),
+    #[command(name = "artist")]
+    Artist(LibraryArtistCommand),
+    #[command(name = "rescan")]
+    Rescan(LibraryRescanCommand),
+}
+
+#[allow(unused)]
+#[derive(CommandModel, CreateCommand)]
+#[command(
+    name = "search",
+    desc = "Search the library by title, artist or album."
+)]
+pub struct LibrarySearchCommand {
+    #[command(desc = "What to look for")]
+    query: String,
+}
+
+#[allow(unused)]
+#[derive(CommandModel, CreateCommand)]
+#[command(name = "play", desc = "Queue the best matching library track.")]
+pub struct LibraryPlayCommand {
+    #[command(desc = "Title, artist or album")]
+    query: String,
+}
+
+#[allow(unused)]
+#[derive(CommandModel, CreateCommand)]
+#[command(name = "album", desc = "Queue a whole album from the library.")]
+pub struct LibraryAlbumCommand {
+    #[command(desc = "The album name")]
+    name: String,
+}
+
+#[allow(unused)]
+#[derive(CommandModel, CreateCommand)]
+#[command(
+    name = "artist",
+    desc = "Queue everything by an artist in the library."
+)]
+pub struct LibraryArtistCommand {
+    #[command(desc = "The artist name")]
+    name: String,
+}
+
+#[derive(CommandModel, CreateCommand)]
+#[command(name = "rescan", desc = "Re-read the library folder.")]
+pub struct LibraryRescanCommand;
+
+#[async_trait]
+impl Command<GlobalState> for LibraryCommand {
+    async fn execute<'ctx>(state: GlobalState, mut cmd_ctx: CommandContext<'ctx>) -> Result<()> {
+        let guild_id = cmd_ctx
+            .guild_id()
+            .ok_or_else(|| anyhow!("This command must be used in a guild."))?;
+        let author = cmd_ctx
+            .author()
+            .ok_or_else(|| anyhow!("Interaction is missing author information."))?
+            .clone();
+        if state.config.library_dir.is_none() {
+            return Err(anyhow!("No music library is configured."));
+        }
+
+        let subcommand = cmd_ctx.subcommand().map(str::to_owned);
+        let response = match subcommand.as_deref() {
+            Some("search") => {
+                let query = cmd_ctx
+                    .get_remainder_arg("query")
+                    .ok_or_else(|| anyhow!("You must provide something to search for."))?;
+                let library = state.library.lock().await;
+                let results = library.search(&query);
+                CommandResponseBuilder::new()
+                    .embed(build_results_embed(&query, &results))
+                    .build()
+            }
+            Some("rescan") => {
+                let count = rescan_library(&state).await?;
+                CommandResponseBuilder::new()
+                    .content(format!("📚 Library rescanned: {} tracks.", count))
+                    .build()
+            }
+            Some(kind @ ("play" | "album" | "artist")) => {
+                let arg = if kind == "play" { "query" } else { "name" };
+                let query = cmd_ctx
+                    .get_remainder_arg(arg)
+                    .ok_or_else(|| anyhow!("You must say what to play."))?;
+                let tracks: Vec<LibraryTrack> = {
+                    let library = state.library.lock().await;
+                    let matches = match kind {
+                        "play" => library.search(&query).into_iter().take(1).collect(),
+                        "album" => library.album(&query),
+                        _ => library.artist(&query),
+                    };
+                    matches
+                        .into_iter()
+                        .take(MAX_QUEUED_FILES)
+                        .cloned()
+                        .collect()
+                };
+                if tracks.is_empty() {
+                    return Err(anyhow!("Nothing in the library matches `{}`.", query));
+                }
+
+                if state.cache.voice_state(author.id, guild_id).is_none() {
+                    return Err(anyhow!(
+                        "You must be in a voice channel to use this command."
+                    ));
+                }
+
+                let description = match kind {
+                    "play" => format!(
+                        "**{}** by {}",
+                        tracks[0].title,
+                        tracks[0].artist_or_unknown()
+                    ),
+                    "album" => format!(
+                        "the album **{}**",
+                        tracks[0].album.as_deref().unwrap_or_default()
+                    ),
+                    _ => format!("tracks by **{}**", tracks[0].artist_or_unknown()),
+                };
+                let content = match queue_library_tracks(
+                    &state,
+                    guild_id,
+                    author.id,
+                    cmd_ctx.member_permissions(),
+                    cmd_ctx.channel_id(),
+                    &tracks,
+                )
+                .await?
+                {
+                    Enqueued::Queued(queued) => {
+                        let mut content =
+                            format!("`＋` Queued {} ({} tracks)", description, queued);
+                        if queued < tracks.len() {
+                            content.push_str(&format!(
+                                ", {} could not be loaded",
+                                tracks.len() - queued
+                            ));
+                        }
+                        content
+                    }
+                    Enqueued::PendingApproval(reply) => reply,
+                };
+                CommandResponseBuilder::new().content(content).build()
+            }
+            Some(other) => return Err(anyhow!("Unknown library subcommand: {}", other)),
+            None => return Err(anyhow!("Use `library search|play|album|artist|rescan`.")),
+        };
+
+        cmd_ctx.reply(response).await?;
+
+        Ok(())
+    }
+}
+
+/// Loads each file through Lavalink's local source and queues the ones that
+/// loaded.
+async fn queue_library_tracks(
+    state: &GlobalState,
+    guild_id: Id<GuildMarker>,
+    requester: Id<UserMarker>,
+    permissions: Option<Permissions>,
+    text_channel_id: Option<Id<ChannelMarker>>,
+    tracks: &[LibraryTrack],
+) -> Result<Enqueued> {
+    let mut queue = Vec::with_capacity(tracks.len());
+    for library_track in tracks {
+        // Lavalink reads the file itself, so it needs the full path.
+        let path = std::path::absolute(&library_track.path)?;
+        let loaded = state
+            .lavalink()
+            .load_tracks(guild_id, &path.to_string_lossy())
+            .await?;
+        let Some(TrackLoadData::Track(mut track)) = loaded.data else {
+            tracing::warn!("Lavalink could not load library file {}", path.display());
+            continue;
+        };
+        track.info.title = library_track.title.clone();
+        track.info.author = library_track.artist_or_unknown().to_string();
+        queue.push(TrackInQueue::from(track));
+    }
+
+    if queue.is_empty() {
+        return Ok(Enqueued::Queued(0));
+    }
+    enqueue(
+        state,
+        guild_id,
+        queue,
+        requester,
+        permissions,
+        text_channel_id,
+        false,
+    )
+    .await
+}
+
+fn build_results_embed(query: &str, results: &[&LibraryTrack]) -> Embed {
+    let description = if results.is_empty() {
+        "Nothing in the library matches that.".to_string()
+    } else {
+        results
+            .iter()
+            .take(MAX_SEARCH_RESULTS)
+            .enumerate()
+            .map(|(idx, track)| {
+                let length = track.duration_ms.map_or_else(
+                    || "?".to_string(),
+                    |ms| humantime::format_duration(Duration::from_secs(ms / 1000)).to_string(),
+                );
+                let album = track
+                    .album
+                    .as_deref()
+                    .map(|album| format!(" · _{}_", album))
+                    .unwrap_or_default();
+                format!(
+                    "`{}.` **{}** - {}{} `[{}]`",
+                    idx + 1,
+                    track.title,
+                    track.artist_or_unknown(),
+                    album,
+                    length
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    EmbedBuilder::new()
+        .title(format!("📚 Library: {}", query))
+        .description(description)
+        .color(0x1DB954)
+        .footer(EmbedFooterBuilder::new(format!(
+            "{} matches",
+            results.len()
+        )))
+        .build()
+}
@@ -0,0 +1,196 @@
+use std::hash::{BuildHasher, RandomState};
+
+use super::Enqueued;
+use super::playlist::{build_tracks_embed, queue_saved_tracks};
+use crate::command_handler::{Command, CommandContext, CommandResponseBuilder, GlobalState};
+use crate::storage::{MostLikedTrack, PlaylistTrack};
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+use twilight_interactions::command::{CommandModel, CreateCommand};
+use twilight_model::channel::message::Embed;
+use twilight_util::builder::embed::EmbedBuilder;
+
+/// How many liked tracks `play` and `shuffle` queue at once.
+const MAX_QUEUED_LIKES: usize = 500;
+const MOST_LIKED_COUNT: usize = 10;
+
+#[derive(CommandModel, CreateCommand)]
+#[command(name = "liked", desc = "Your tracks liked with the ❤️ button.")]
+pub enum LikedCommand {
+    #[command(name = "list")]
+    List(LikedListCommand),
+    #[command(name = "play")]
+    Play(LikedPlayCommand),
+    #[command(name = "shuffle")]
+    Shuffle(LikedShuffleCommand),
+    #[command(name = "remove")]
+    Remove(LikedRemoveCommand),
+    #[command(name = "top")]
+    Top(LikedTopCommand),
+}
+
+#[allow(unused)]
+#[derive(CommandModel, CreateCommand)]
+#[command(name = "list", desc = "Show your liked tracks, newest first.")]
+pub struct LikedListCommand {
+    #[command(desc = "The page to show", min_value = 1)]
+    page: Option<i64>,
+}
+
+#[derive(CommandModel, CreateCommand)]
+#[command(name = "play", desc = "Queue your liked tracks, newest first.")]
+pub struct LikedPlayCommand;
+
+#[derive(CommandModel, CreateCommand)]
+#[command(name = "shuffle", desc = "Queue your liked tracks in random order.")]
+pub struct LikedShuffleCommand;
+
+#[allow(unused)]
+#[derive(CommandModel, CreateCommand)]
+#[command(name = "remove", desc = "Unlike a track.")]
+pub struct LikedRemoveCommand {
+    #[command(desc = "The track number from /liked list", min_value = 1)]
+    position: i64,
+}
+
+#[derive(CommandModel, CreateCommand)]
+#[command(name = "top", desc = "Show the most liked tracks in this server.")]
+pub struct LikedTopCommand;
+
+#[async_trait]
+impl Command<GlobalState> for LikedCommand {
+    async fn execute<'ctx>(state: GlobalState, mut cmd_ctx: CommandContext<'ctx>) -> Result<()> {
+        let author = cmd_ctx
+            .author()
+            .ok_or_else(|| anyhow!("Interaction is missing author information."))?
+            .clone();
+
+        let subcommand = cmd_ctx.subcommand().map(str::to_owned);
+        let response = match subcommand.as_deref() {
+            Some("list") => {
+                let page = cmd_ctx
+                    .get_arg::<i64>("page")
+                    .map_or(0, |page| usize::try_from(page - 1).unwrap_or(0));
+                let tracks = state.storage.liked_tracks(author.id)?;
+                if tracks.is_empty() {
+                    return Err(anyhow!(
+                        "You have no liked tracks yet. Press ❤️ Like under `/now_playing`."
+                    ));
+                }
+                CommandResponseBuilder::new()
+                    .embed(build_tracks_embed("❤️ Liked tracks", &tracks, page))
+                    .build()
+            }
+            Some(action @ ("play" | "shuffle")) => {
+                let guild_id = cmd_ctx
+                    .guild_id()
+                    .ok_or_else(|| anyhow!("This command must be used in a guild."))?;
+                let mut tracks = state.storage.liked_tracks(author.id)?;
+                if tracks.is_empty() {
+                    return Err(anyhow!(
+                        "You have no liked tracks yet. Press ❤️ Like under `/now_playing`."
+                    ));
+                }
+                if state.cache.voice_state(author.id, guild_id).is_none() {
+                    return Err(anyhow!(
+                        "You must be in a voice channel to use this command."
+                    ));
+                }
+
+                if action == "shuffle" {
+                    shuffle(&mut tracks);
+                }
+                tracks.truncate(MAX_QUEUED_LIKES);
+                let emoji = if action == "shuffle" { "🔀" } else { "`＋`" };
+                let content = match queue_saved_tracks(
+                    &state,
+                    guild_id,
+                    author.id,
+                    cmd_ctx.member_permissions(),
+                    cmd_ctx.channel_id(),
+                    &tracks,
+                )
+                .await?
+                {
+                    Enqueued::Queued(queued) => {
+                        format!("{} Queued {} liked tracks", emoji, queued)
+                    }
+                    Enqueued::PendingApproval(reply) => reply,
+                };
+                CommandResponseBuilder::new().content(content).build()
+            }
+            Some("remove") => {
+                let position = cmd_ctx
+                    .get_arg::<i64>("position")
+                    .and_then(|position| usize::try_from(position - 1).ok())
+                    .ok_or_else(|| {
+                        anyhow!("You must provide a track number from `/liked list`.")
+                    })?;
+                let removed = state
+                    .storage
+                    .unlike_track(author.id, position)?
+                    .ok_or_else(|| anyhow!("You have no liked track {}.", position + 1))?;
+                CommandResponseBuilder::new()
+                    .content(format!(
+                        "💔 Removed **{}** from your liked tracks.",
+                        removed.title
+                    ))
+                    .build()
+            }
+            Some("top") => {
+                let guild_id = cmd_ctx
+                    .guild_id()
+                    .ok_or_else(|| anyhow!("This command must be used in a guild."))?;
+                let tracks = state
+                    .storage
+                    .most_liked_tracks(guild_id, MOST_LIKED_COUNT)?;
+                CommandResponseBuilder::new()
+                    .embed(build_most_liked_embed(&tracks))
+                    .build()
+            }
+            Some(other) => return Err(anyhow!("Unknown liked subcommand: {}", other)),
+            None => return Err(anyhow!("Use `liked list|play|shuffle|remove|top`.")),
+        };
+
+        cmd_ctx.reply(response).await?;
+
+        Ok(())
+    }
+}
+
+/// Orders the tracks by a random key per track.
+fn shuffle(tracks: &mut [PlaylistTrack]) {
+    let random = RandomState::new();
+    tracks.sort_by_cached_key(|track| random.hash_one(track.id));
+}
+
+fn build_most_liked_embed(tracks: &[MostLikedTrack]) -> Embed {
+    let description = if tracks.is_empty() {
+        "Nobody has liked a track here yet. Press ❤️ Like under `/now_playing`.".to_string()
+    } else {
+        tracks
+            .iter()
+            .enumerate()
+            .map(|(idx, track)| {
+                let title = match &track.uri {
+                    Some(uri) => format!("[{}](<{}>)", track.title, uri),
+                    None => track.title.clone(),
+                };
+                format!(
+                    "`{}.` {} - {} · ❤️ {}",
+                    idx + 1,
+                    title,
+                    track.author,
+                    track.likes
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    EmbedBuilder::new()
+        .title("💖 Most liked in this server")
+        .description(description)
+        .color(0x1DB954)
+        .build()
+}
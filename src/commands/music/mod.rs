@@ -1,22 +1,60 @@
+mod approval;
+mod bookmark;
+mod clip;
+mod gplaylist;
 mod help;
+mod import;
 mod jump;
+mod library;
+mod liked;
 mod lyrics;
 mod now_playing;
 mod play;
+mod playlist;
+mod podcast;
 mod queue;
+mod quiz;
+mod radio;
+mod session;
 mod skip;
+mod stats;
 mod stop;
+mod track_info;
+mod undo;
 mod volume;
-
+mod vote_next;
+mod when;
+mod wrapped;
+
+pub use approval::{
+    ApprovalCommand, Decision, EXPIRY_SWEEP_INTERVAL, decide_request, expire_requests, is_dj,
+};
+pub use bookmark::BookmarkCommand;
+pub use clip::ClipCommand;
+pub use gplaylist::GuildPlaylistCommand;
 pub use help::HelpCommand;
+pub use import::ImportCommand;
 pub use jump::JumpCommand;
+pub use library::LibraryCommand;
+pub use liked::LikedCommand;
 pub use lyrics::LyricsCommand;
 pub use now_playing::NowPlayingCommand;
-pub use play::PlayCommand;
+pub use play::{Enqueued, PlayCommand, enqueue, to_query};
+pub use playlist::{PlaylistCommand, new_playlist_track};
+pub use podcast::PodcastCommand;
 pub use queue::QueueCommand;
+pub use quiz::{QuizCommand, quiz_guess};
+pub use radio::{RadioCommand, play_station};
+pub use session::save_session_playlist;
 pub use skip::SkipCommand;
+pub use stats::StatsCommand;
 pub use stop::StopCommand;
+pub use track_info::TrackInfoCommand;
+pub use undo::UndoCommand;
 pub use volume::VolumeCommand;
+pub use vote_next::{VoteNextCommand, close_vote_poll_on_start, vote_poll_message};
+pub use when::WhenCommand;
+pub use wrapped::{WrappedCommand, wrapped_message};
 
 
 use std::sync::Arc;
@@ -37,15 +75,19 @@ fn convert_connection_info(connection_info: SongbirdConnectionInfo) -> Connectio
     }
 }
 
-pub async fn join(
+/// Joins the voice channel and creates the player context without replying
+/// anywhere. Returns `false` if the bot was already connected in the guild.
+///
+/// Joining starts a listening session, recapped in `text_channel_id` (or the
+/// voice channel's chat) when the bot leaves, at the latest once it idles.
+pub async fn connect(
     state: Arc<crate::state::State>,
-    ctx: &crate::command_handler::CommandContext<'_>,
     channel_id: twilight_model::id::Id<twilight_model::id::marker::ChannelMarker>,
+    text_channel_id: Option<twilight_model::id::Id<twilight_model::id::marker::ChannelMarker>>,
     guild_id: twilight_model::id::Id<twilight_model::id::marker::GuildMarker>,
-    http_client: Arc<twilight_http::Client>,
-) -> anyhow::Result<Option<Message>> {
+) -> anyhow::Result<bool> {
     if state.songbird().get(guild_id).is_some() {
-        return Ok(None);
+        return Ok(false);
     }
 
     let (connection_info, _) = state
@@ -54,17 +96,35 @@ pub async fn join(
         .await
         .map_err(|e| anyhow::anyhow!("Failed to join voice channel, {}", e))?;
 
+    let started_at = chrono::Utc::now().timestamp_millis();
     state
         .lavalink()
-        .create_player_context_with_data::<(
-            twilight_model::id::Id<twilight_model::id::marker::ChannelMarker>,
-            std::sync::Arc<twilight_http::Client>,
-        )>(
+        .create_player_context_with_data::<crate::state::PlayerData>(
             guild_id,
             convert_connection_info(connection_info),
-            Arc::new((channel_id, http_client)),
+            Arc::new(crate::state::PlayerData {
+                channel_id,
+                text_channel_id: text_channel_id.unwrap_or(channel_id),
+                started_at,
+                now_playing: Default::default(),
+                podcast_progress: Default::default(),
+            }),
         )
         .await?;
+    session::leave_when_idle(state, guild_id, started_at);
+
+    Ok(true)
+}
+
+pub async fn join(
+    state: Arc<crate::state::State>,
+    ctx: &crate::command_handler::CommandContext<'_>,
+    channel_id: twilight_model::id::Id<twilight_model::id::marker::ChannelMarker>,
+    guild_id: twilight_model::id::Id<twilight_model::id::marker::GuildMarker>,
+) -> anyhow::Result<Option<Message>> {
+    if !connect(state, channel_id, ctx.channel_id(), guild_id).await? {
+        return Ok(None);
+    }
 
     let response = CommandResponseBuilder::new()
         .content(format!("🎙️ Joined {}", channel_id.mention()))
@@ -78,8 +138,27 @@ pub async fn leave(
     state: Arc<crate::state::State>,
     guild_id: twilight_model::id::Id<twilight_model::id::marker::GuildMarker>,
 ) -> anyhow::Result<()> {
+    let session = state
+        .lavalink()
+        .get_player_context(guild_id)
+        .and_then(|player| player.data::<crate::state::PlayerData>().ok());
+
+    if let Some(game) = state.quiz_games.lock().await.remove(&guild_id) {
+        game.wake.notify_one();
+    }
+    state.vote_polls.lock().await.remove(&guild_id);
+    // Stops the station's title from being polled.
+    state.radio_sessions.lock().await.remove(&guild_id);
     state.songbird().remove(guild_id).await?;
     state.lavalink().delete_player(guild_id).await?;
+    // Lavalink may not report the end of a track on a destroyed player.
+    state.storage.log_play_end(guild_id)?;
+
+    if let Some(session) = session
+        && let Err(e) = session::post_recap(&state, guild_id, &session).await
+    {
+        tracing::warn!(error = ?e, "Failed to post the session recap");
+    }
     Ok(())
 }
 
@@ -2,6 +2,8 @@ use super::check_voice_state;
 use crate::command_handler::{
     Command, CommandContext, CommandResponseBuilder, GlobalState, StateExt,
 };
+use crate::utils::eta::{playback_speed, remaining_ms};
+use crate::utils::track::{LIVE_BADGE, format_length, station_name};
 use anyhow::Result;
 use async_trait::async_trait;
 use lavalink_rs::model::track::TrackData;
@@ -38,7 +40,17 @@ impl Command<GlobalState> for NowPlayingCommand {
             .ok_or_else(|| anyhow::anyhow!("No track is currently playing."))?;
         let volume = player_data.volume;
         let position = player_data.state.position / 1000;
-        let embed = build_now_playing_embed(track, volume, position).await?;
+        let speed = playback_speed(player_data.filters.as_ref());
+        let mut embed = build_now_playing_embed(track, volume, position, speed).await?;
+        if let Some(title) = state
+            .radio_sessions
+            .lock()
+            .await
+            .get(&guild_id)
+            .and_then(|session| session.last_title.clone())
+        {
+            embed.fields.push(EmbedFieldBuilder::new("On air", title).build());
+        }
         let component = action_menu().await;
         let response = CommandResponseBuilder::new()
             .embed(embed.clone())
@@ -55,34 +67,51 @@ pub async fn build_now_playing_embed(
     track: &TrackData,
     volume: u16,
     position: u64,
+    speed: f64,
 ) -> Result<Embed> {
-    let finishing_time =
-        chrono::Utc::now().timestamp() as u64 + (track.info.length / 1000 - position);
     let parsed_duration = humantime::format_duration(std::time::Duration::from_secs(position));
-    let embed = EmbedBuilder::new()
+    let mut embed = EmbedBuilder::new()
         .title("🎶 Now Playing")
-        .description(format!(
-            "**{}** by **{}**",
-            track.info.title, track.info.author
-        ))
         .color(0x1DB954)
         .thumbnail(ImageSource::url(track.info.artwork_url.clone().unwrap_or_default()).unwrap())
-        .url(track.info.uri.clone().unwrap_or_default())
-        .field(
-            EmbedFieldBuilder::new(
-                "Duration",
-                format!(
-                    "{} / {}",
-                    parsed_duration,
-                    humantime::format_duration(std::time::Duration::from_secs(
-                        track.info.length / 1000
-                    ))
-                ),
+        .url(track.info.uri.clone().unwrap_or_default());
+
+    if let Some(station) = station_name(track) {
+        embed = embed
+            .description(format!("{} 📻 **{}**", LIVE_BADGE, station))
+            .field(EmbedFieldBuilder::new("Listening for", parsed_duration.to_string()).inline());
+    } else if track.info.is_stream {
+        embed = embed
+            .description(format!(
+                "{} **{}** by **{}**",
+                LIVE_BADGE, track.info.title, track.info.author
+            ))
+            .field(EmbedFieldBuilder::new("Listening for", parsed_duration.to_string()).inline());
+    } else {
+        let remaining = remaining_ms(track, position * 1000).unwrap_or_default() / 1000;
+        #[allow(clippy::cast_precision_loss, clippy::cast_possible_truncation)]
+        let finishing_time =
+            chrono::Utc::now().timestamp() + (remaining as f64 / speed) as i64;
+        embed = embed
+            .description(format!(
+                "**{}** by **{}**",
+                track.info.title, track.info.author
+            ))
+            .field(
+                EmbedFieldBuilder::new(
+                    "Duration",
+                    format!("{} / {}", parsed_duration, format_length(track)),
+                )
+                .inline()
+                .build(),
             )
-            .inline()
-            .build(),
-        )
-        .field(EmbedFieldBuilder::new("Finished in", format!("<t:{}:R>", finishing_time)).inline())
+            .field(
+                EmbedFieldBuilder::new("Finished in", format!("<t:{}:R>", finishing_time))
+                    .inline(),
+            );
+    }
+
+    let embed = embed
         .field(EmbedFieldBuilder::new("Volume", format!("{}%", volume)).inline())
         .build();
 
@@ -110,6 +139,24 @@ pub async fn action_menu() -> Component {
                 url: None,
                 sku_id: None,
             }),
+            Component::Button(Button {
+                label: Some("❤️ Like".to_string()),
+                custom_id: Some("like".to_string()),
+                style: twilight_model::channel::message::component::ButtonStyle::Secondary,
+                emoji: None,
+                disabled: false,
+                url: None,
+                sku_id: None,
+            }),
+            Component::Button(Button {
+                label: Some("📩 Grab".to_string()),
+                custom_id: Some("grab".to_string()),
+                style: twilight_model::channel::message::component::ButtonStyle::Secondary,
+                emoji: None,
+                disabled: false,
+                url: None,
+                sku_id: None,
+            }),
             Component::Button(Button {
                 label: Some("⏩ Skip".to_string()),
                 custom_id: Some("skip".to_string()),
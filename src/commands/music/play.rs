@@ -1,30 +1,61 @@
 use std::collections::VecDeque;
 
-use super::join;
+use super::approval::{
+    approval_needed, cached_member_roles, load_request, request_approval, request_voice_channel,
+};
+use super::{connect, join};
 use crate::command_handler::{
     Command, CommandContext, CommandResponseBuilder, GlobalState, HasHttpClient,
 };
+use crate::utils::attachment::{
+    MAX_ATTACHMENTS, apply_filename_title, validate_audio_attachment,
+};
+use crate::utils::queue_history::snapshot_queue;
+use crate::utils::track::LIVE_BADGE;
 use anyhow::{anyhow, Result};
 use async_trait::async_trait;
 use lavalink_rs::prelude::{SearchEngines, TrackInQueue, TrackLoadData};
 use twilight_interactions::command::{CommandModel, CreateCommand};
 use twilight_model::{
-    channel::Message,
+    channel::{Attachment, Message},
+    guild::Permissions,
+    id::{
+        Id,
+        marker::{ChannelMarker, GuildMarker, UserMarker},
+    },
 };
 
 #[derive(CommandModel, CreateCommand)]
 #[command(name = "play", desc = "Play a song from YouTube or other sources.")]
 pub struct PlayCommand {
     #[command(desc = "The song to play")]
-    pub song: String,
+    pub song: Option<String>,
+    #[command(desc = "An audio file to play instead")]
+    pub attachment: Option<Attachment>,
 }
 
 #[async_trait]
 impl Command<GlobalState> for PlayCommand {
     async fn execute<'ctx>(state: GlobalState, mut cmd_ctx: CommandContext<'ctx>) -> Result<()> {
-        let song_query: String = cmd_ctx
-            .get_remainder_arg("song")
-            .ok_or_else(|| anyhow!("You must provide a song name or URL to play."))?;
+        let song_query = cmd_ctx.get_remainder_arg("song");
+        // Files are only played when no song was named, so `!play <url>` in
+        // reply to a message with attachments still plays the URL.
+        let attachments = if song_query.is_none() {
+            cmd_ctx.get_attachments("attachment")
+        } else {
+            Vec::new()
+        };
+        if song_query.is_none() && attachments.is_empty() {
+            return Err(anyhow!(
+                "You must provide a song name or URL to play, or attach an audio file."
+            ));
+        }
+        if attachments.len() > MAX_ATTACHMENTS {
+            return Err(anyhow!("You can play at most {} files at once.", MAX_ATTACHMENTS));
+        }
+        for attachment in &attachments {
+            validate_audio_attachment(attachment)?;
+        }
 
         let author = cmd_ctx
             .author()
@@ -39,34 +70,59 @@ impl Command<GlobalState> for PlayCommand {
             .voice_state(author.id, guild_id)
             .ok_or_else(|| anyhow!("You must be in a voice channel to use this command."))?;
 
+        if let Some(settings) = approval_needed(
+            &state,
+            guild_id,
+            author.id,
+            (&cmd_ctx.member_roles(), cmd_ctx.member_permissions()),
+        )? {
+            let tracks = match song_query {
+                Some(song_query) => vec![load_request(&state, guild_id, song_query).await?.into()],
+                None => load_attachments(&state, guild_id, &attachments).await?,
+            };
+            let reply_message = request_approval(
+                &state,
+                guild_id,
+                &settings,
+                author.id,
+                (voice_state.channel_id(), cmd_ctx.channel_id()),
+                &tracks,
+            )
+            .await?;
+            cmd_ctx
+                .reply(CommandResponseBuilder::new().content(reply_message).build())
+                .await?;
+            return Ok(());
+        }
+
         let msg = join(
             state.clone(),
             &cmd_ctx,
             voice_state.channel_id(),
             guild_id,
-            state.http_client(),
         )
         .await?;
 
         let lava_client = state.lavalink.clone();
 
-        let player = lava_client
-            .get_player_context(guild_id)
-            .ok_or_else(|| anyhow!("Player context not found. Is the bot in a voice channel?"))?;
+        let Some(song_query) = song_query else {
+            let tracks = load_attachments(&state, guild_id, &attachments).await?;
+            let reply_message = match tracks.as_slice() {
+                [track] => format!("`＋` Queued file `{}`", track.track.info.title),
+                tracks => format!("`＋` Queued {} files", tracks.len()),
+            };
+            queue_tracks(&state, guild_id, tracks, author.id, false).await?;
 
-        let query_term = if song_query.starts_with("http") {
-            song_query
-        } else if (song_query.contains(':') && song_query.split(':').count() == 2)
-            || song_query.contains(" - ")
-        {
-            song_query
-        } else {
-            SearchEngines::Spotify.to_query(&song_query)?
+            let response_builder = CommandResponseBuilder::new().content(reply_message);
+            reply_to_join(&state, &cmd_ctx, &msg, response_builder).await?;
+            return Ok(());
         };
 
+        let query_term = to_query(song_query)?;
+
         let loaded_tracks_response = lava_client.load_tracks(guild_id, &query_term).await?;
 
-        let (mut tracks_to_queue, opt_playlist_info) = match loaded_tracks_response.data {
+        let (tracks_to_queue, opt_playlist_info) = match loaded_tracks_response.data {
             Some(TrackLoadData::Track(track)) => (vec![track.into()], None),
             Some(TrackLoadData::Search(search_results)) => {
                 let track = search_results
@@ -101,16 +157,17 @@ impl Command<GlobalState> for PlayCommand {
             return Ok(());
         }
 
-        tracks_to_queue.iter_mut().for_each(|track_in_queue| {
-            track_in_queue.track.user_data = Some(serde_json::json!({ "requester_id": author.id }));
-        });
-
         let reply_message = if let Some(p_info) = &opt_playlist_info {
             format!(
                 "`＋`Queued playlist: [{}] ({} tracks)",
                 p_info.name,
                 tracks_to_queue.len()
             )
+        } else if tracks_to_queue[0].track.info.is_stream {
+            format!(
+                "`＋` Queued {}: `{}`",
+                LIVE_BADGE, tracks_to_queue[0].track.info.title
+            )
         } else if let Some(uri) = tracks_to_queue[0].track.info.uri.as_ref() {
             format!(
                 "`＋` Queued [`{}`](<{}>)",
@@ -121,20 +178,145 @@ impl Command<GlobalState> for PlayCommand {
             format!("`＋` Queued: `{}`", tracks_to_queue[0].track.info.title)
         };
 
-        let queue = player.get_queue();
-        queue.append(VecDeque::from(tracks_to_queue))?;
+        queue_tracks(&state, guild_id, tracks_to_queue, author.id, false).await?;
 
         let response_builder = CommandResponseBuilder::new().content(reply_message);
         reply_to_join(&state, &cmd_ctx, &msg, response_builder).await?;
 
-        if let Ok(player_data) = player.get_player().await {
-            if player_data.track.is_none() && queue.get_track(0).await.is_ok_and(|x| x.is_some()) {
-                player.skip()?;
+        Ok(())
+    }
+}
+
+/// Turns user input into a Lavalink identifier: URLs and already prefixed
+/// queries are passed through, anything else becomes a Spotify search.
+pub fn to_query(song_query: String) -> Result<String> {
+    if song_query.starts_with("http") {
+        Ok(song_query)
+    } else if (song_query.contains(':') && song_query.split(':').count() == 2)
+        || song_query.contains(" - ")
+    {
+        Ok(song_query)
+    } else {
+        Ok(SearchEngines::Spotify.to_query(&song_query)?)
+    }
+}
+
+/// Loads uploaded files through Lavalink's HTTP source, naming untagged ones
+/// after the file.
+async fn load_attachments(
+    state: &GlobalState,
+    guild_id: Id<GuildMarker>,
+    attachments: &[Attachment],
+) -> Result<Vec<TrackInQueue>> {
+    let mut tracks = Vec::with_capacity(attachments.len());
+    for attachment in attachments {
+        let loaded = state.lavalink.load_tracks(guild_id, &attachment.url).await?;
+        let mut track = match loaded.data {
+            Some(TrackLoadData::Track(track)) => track,
+            Some(TrackLoadData::Error(e)) => {
+                return Err(anyhow!("Could not play `{}`: {}", attachment.filename, e.message));
             }
+            _ => return Err(anyhow!("Could not play `{}`.", attachment.filename)),
+        };
+        apply_filename_title(&mut track, &attachment.filename);
+        tracks.push(TrackInQueue::from(track));
+    }
+    Ok(tracks)
+}
+
+/// What [`enqueue`] did with the tracks.
+#[must_use]
+pub enum Enqueued {
+    /// This many tracks were added to the queue.
+    Queued(usize),
+    /// The guild vets requests, so they went to the DJs instead. Holds the
+    /// reply for the requester.
+    PendingApproval(String),
+}
+
+/// Adds `tracks` to the guild's queue for `requester`, joining their voice
+/// channel first, or, if the guild has them vetted by DJs, to its pending
+/// requests without joining. Every command and button that queues tracks for
+/// a member goes through here.
+pub async fn enqueue(
+    state: &GlobalState,
+    guild_id: Id<GuildMarker>,
+    tracks: Vec<TrackInQueue>,
+    requester: Id<UserMarker>,
+    permissions: Option<Permissions>,
+    text_channel_id: Option<Id<ChannelMarker>>,
+    play_next: bool,
+) -> Result<Enqueued> {
+    let voice_channel_id = request_voice_channel(state, guild_id, requester)
+        .ok_or_else(|| anyhow!("You must be in a voice channel to request tracks."))?;
+    let roles = cached_member_roles(state, guild_id, requester);
+    if let Some(settings) = approval_needed(state, guild_id, requester, (&roles, permissions))? {
+        let reply = request_approval(
+            state,
+            guild_id,
+            &settings,
+            requester,
+            (voice_channel_id, text_channel_id),
+            &tracks,
+        )
+        .await?;
+        return Ok(Enqueued::PendingApproval(reply));
+    }
+
+    connect(state.clone(), voice_channel_id, text_channel_id, guild_id).await?;
+    let count = tracks.len();
+    queue_tracks(state, guild_id, tracks, requester, play_next).await?;
+    Ok(Enqueued::Queued(count))
+}
+
+/// Tags `tracks` with their requester and adds them to the guild's queue,
+/// either at the end or up next, then starts playback if the player is idle.
+/// Skips the approval check, so only for tracks that passed it.
+pub(super) async fn queue_tracks(
+    state: &GlobalState,
+    guild_id: Id<GuildMarker>,
+    mut tracks: Vec<TrackInQueue>,
+    requester: Id<UserMarker>,
+    play_next: bool,
+) -> Result<()> {
+    let player = state
+        .lavalink
+        .get_player_context(guild_id)
+        .ok_or_else(|| anyhow!("Player context not found. Is the bot in a voice channel?"))?;
+
+    // Keep any metadata the caller attached, such as the radio station.
+    tracks.iter_mut().for_each(|track_in_queue| {
+        let mut user_data = track_in_queue
+            .track
+            .user_data
+            .take()
+            .filter(serde_json::Value::is_object)
+            .unwrap_or_else(|| serde_json::json!({}));
+        user_data["requester_id"] = serde_json::json!(requester);
+        user_data["added_at"] = serde_json::json!(chrono::Utc::now().timestamp_millis());
+        track_in_queue.track.user_data = Some(user_data);
+    });
+
+    snapshot_queue(state, guild_id).await?;
+
+    let queue = player.get_queue();
+    if play_next {
+        let mut pending = queue.get_queue().await?;
+        for track in tracks.into_iter().rev() {
+            pending.push_front(track);
         }
+        queue.replace(pending)?;
+    } else {
+        queue.append(VecDeque::from(tracks))?;
+    }
 
-        Ok(())
+    if let Ok(player_data) = player.get_player().await {
+        if player_data.track.is_none() && queue.get_track(0).await.is_ok_and(|x| x.is_some()) {
+            player.skip()?;
+        }
     }
+
+    Ok(())
 }
 
 pub async fn reply_to_join(
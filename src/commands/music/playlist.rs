@@ -0,0 +1,718 @@
+use super::{Enqueued, enqueue, to_query};
+use crate::command_handler::{
+    Command, CommandContext, CommandResponseBuilder, GlobalState, StateExt, autocomplete_response,
+    focused_option,
+};
+use crate::storage::{
+    NewPlaylistTrack, Playlist, PlaylistShare, PlaylistTrack, normalize_share_code,
+};
+use crate::utils::attachment::{UPLOAD_NOT_SAVED, is_upload};
+use crate::utils::fuzzy;
+use crate::utils::track::{LIVE_BADGE, format_length_ms};
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+use lavalink_rs::model::track::TrackData;
+use lavalink_rs::prelude::{TrackInQueue, TrackLoadData};
+use twilight_interactions::command::{CommandModel, CreateCommand};
+use twilight_model::application::interaction::{Interaction, application_command::CommandData};
+use twilight_model::channel::message::Embed;
+use twilight_model::guild::Permissions;
+use twilight_model::id::{
+    Id,
+    marker::{ChannelMarker, GuildMarker, UserMarker},
+};
+use twilight_util::builder::embed::{EmbedBuilder, EmbedFooterBuilder};
+
+const PLAYLIST_PAGE_SIZE: usize = 10;
+pub(super) const MAX_PLAYLIST_TRACKS: usize = 500;
+/// Also the most choices autocomplete can offer.
+pub(super) const MAX_PLAYLISTS: usize = 25;
+pub(super) const MAX_PLAYLIST_NAME_LEN: usize = 50;
+const MAX_CHOICE_LEN: usize = 100;
+const DAY_MS: i64 = 24 * 60 * 60 * 1000;
+
+#[derive(CommandModel, CreateCommand)]
+#[command(name = "playlist", desc = "Save tracks to your own playlists.")]
+pub enum PlaylistCommand {
+    #[command(name = "create")]
+    Create(PlaylistCreateCommand),
+    #[command(name = "add")]
+    Add(PlaylistAddCommand),
+    #[command(name = "remove")]
+    Remove(PlaylistRemoveCommand),
+    #[command(name = "rename")]
+    Rename(PlaylistRenameCommand),
+    #[command(name = "delete")]
+    Delete(PlaylistDeleteCommand),
+    #[command(name = "list")]
+    List(PlaylistListCommand),
+    #[command(name = "show")]
+    Show(PlaylistShowCommand),
+    #[command(name = "play")]
+    Play(PlaylistPlayCommand),
+    #[command(name = "share")]
+    Share(PlaylistShareCommand),
+    #[command(name = "unshare")]
+    Unshare(PlaylistUnshareCommand),
+    #[command(name = "shares")]
+    Shares(PlaylistSharesCommand),
+    #[command(name = "import")]
+    Import(PlaylistImportCommand),
+}
+
+#[allow(unused)]
+#[derive(CommandModel, CreateCommand)]
+#[command(name = "create", desc = "Create an empty playlist.")]
+pub struct PlaylistCreateCommand {
+    #[command(desc = "The playlist name", max_length = 50)]
+    name: String,
+}
+
+#[allow(unused)]
+#[derive(CommandModel, CreateCommand)]
+#[command(
+    name = "add",
+    desc = "Add the current track, a search result or the whole queue to a playlist."
+)]
+pub struct PlaylistAddCommand {
+    #[command(desc = "The playlist", autocomplete = true)]
+    playlist: String,
+    #[command(desc = "A song or URL to add instead of the current track")]
+    query: Option<String>,
+    #[command(desc = "Add the current track and everything queued")]
+    queue: Option<bool>,
+}
+
+#[allow(unused)]
+#[derive(CommandModel, CreateCommand)]
+#[command(name = "remove", desc = "Remove a track from a playlist.")]
+pub struct PlaylistRemoveCommand {
+    #[command(desc = "The playlist", autocomplete = true)]
+    playlist: String,
+    #[command(desc = "The track number from /playlist show", min_value = 1)]
+    position: i64,
+}
+
+#[allow(unused)]
+#[derive(CommandModel, CreateCommand)]
+#[command(name = "rename", desc = "Rename a playlist.")]
+pub struct PlaylistRenameCommand {
+    #[command(desc = "The playlist", autocomplete = true)]
+    playlist: String,
+    #[command(desc = "The new name", max_length = 50)]
+    name: String,
+}
+
+#[allow(unused)]
+#[derive(CommandModel, CreateCommand)]
+#[command(name = "delete", desc = "Delete a playlist and its tracks.")]
+pub struct PlaylistDeleteCommand {
+    #[command(desc = "The playlist", autocomplete = true)]
+    playlist: String,
+}
+
+#[derive(CommandModel, CreateCommand)]
+#[command(name = "list", desc = "List your playlists.")]
+pub struct PlaylistListCommand;
+
+#[allow(unused)]
+#[derive(CommandModel, CreateCommand)]
+#[command(name = "show", desc = "Show the tracks of a playlist.")]
+pub struct PlaylistShowCommand {
+    #[command(desc = "The playlist", autocomplete = true)]
+    playlist: String,
+    #[command(desc = "The page to show", min_value = 1)]
+    page: Option<i64>,
+}
+
+#[allow(unused)]
+#[derive(CommandModel, CreateCommand)]
+#[command(name = "play", desc = "Queue a playlist.")]
+pub struct PlaylistPlayCommand {
+    #[command(desc = "The playlist", autocomplete = true)]
+    playlist: String,
+}
+
+#[allow(unused)]
+#[derive(CommandModel, CreateCommand)]
+#[command(
+    name = "share",
+    desc = "Make a code others can import a copy of a playlist with."
+)]
+pub struct PlaylistShareCommand {
+    #[command(desc = "The playlist", autocomplete = true)]
+    playlist: String,
+    #[command(
+        desc = "Days until the code stops working; never if left out",
+        min_value = 1,
+        max_value = 365
+    )]
+    days: Option<i64>,
+}
+
+#[allow(unused)]
+#[derive(CommandModel, CreateCommand)]
+#[command(name = "unshare", desc = "Revoke a share code.")]
+pub struct PlaylistUnshareCommand {
+    #[command(desc = "The code from /playlist shares")]
+    code: String,
+}
+
+#[derive(CommandModel, CreateCommand)]
+#[command(name = "shares", desc = "List your share codes that still work.")]
+pub struct PlaylistSharesCommand;
+
+#[allow(unused)]
+#[derive(CommandModel, CreateCommand)]
+#[command(name = "import", desc = "Copy a shared playlist into your playlists.")]
+pub struct PlaylistImportCommand {
+    #[command(desc = "The share code")]
+    code: String,
+}
+
+#[async_trait]
+impl Command<GlobalState> for PlaylistCommand {
+    async fn execute<'ctx>(state: GlobalState, mut cmd_ctx: CommandContext<'ctx>) -> Result<()> {
+        let author = cmd_ctx
+            .author()
+            .ok_or_else(|| anyhow!("Interaction is missing author information."))?
+            .clone();
+
+        let subcommand = cmd_ctx.subcommand().map(str::to_owned);
+        let response = match subcommand.as_deref() {
+            Some("create") => {
+                let name = playlist_name_arg(&mut cmd_ctx, "name", true)?;
+                if state.storage.playlists(author.id)?.len() >= MAX_PLAYLISTS {
+                    return Err(anyhow!("You can have at most {} playlists.", MAX_PLAYLISTS));
+                }
+                if state.storage.create_playlist(author.id, &name)?.is_none() {
+                    return Err(anyhow!("You already have a playlist called `{}`.", name));
+                }
+                CommandResponseBuilder::new()
+                    .content(format!(
+                        "📁 Created playlist **{}**. Add tracks with `/playlist add`.",
+                        name
+                    ))
+                    .build()
+            }
+            Some("add") => {
+                let guild_id = cmd_ctx
+                    .guild_id()
+                    .ok_or_else(|| anyhow!("This command must be used in a guild."))?;
+                let playlist = find_playlist(&state, author.id, &mut cmd_ctx, false)?;
+                let mut tracks = tracks_to_add(&state, &mut cmd_ctx, guild_id).await?;
+                let left_out = fit_tracks(&playlist.name, playlist.track_count, &mut tracks)?;
+
+                let new_tracks: Vec<_> = tracks.iter().map(new_playlist_track).collect();
+                state
+                    .storage
+                    .add_playlist_tracks(playlist.id, &new_tracks)?;
+
+                let content = added_message(&tracks, &playlist.name, left_out);
+                CommandResponseBuilder::new().content(content).build()
+            }
+            Some("remove") => {
+                let playlist = find_playlist(&state, author.id, &mut cmd_ctx, false)?;
+                let position = cmd_ctx
+                    .get_arg::<i64>("position")
+                    .and_then(|position| usize::try_from(position - 1).ok())
+                    .ok_or_else(|| {
+                        anyhow!("You must provide a track number from `/playlist show`.")
+                    })?;
+                let removed = state
+                    .storage
+                    .remove_playlist_track(playlist.id, position)?
+                    .ok_or_else(|| {
+                        anyhow!("**{}** has no track {}.", playlist.name, position + 1)
+                    })?;
+                CommandResponseBuilder::new()
+                    .content(format!(
+                        "🗑️ Removed **{}** from **{}**.",
+                        removed.title, playlist.name
+                    ))
+                    .build()
+            }
+            Some("rename") => {
+                let playlist = find_playlist(&state, author.id, &mut cmd_ctx, false)?;
+                let name = playlist_name_arg(&mut cmd_ctx, "name", true)?;
+                if !state
+                    .storage
+                    .rename_playlist(author.id, playlist.id, &name)?
+                {
+                    return Err(anyhow!("You already have a playlist called `{}`.", name));
+                }
+                CommandResponseBuilder::new()
+                    .content(format!("📁 Renamed **{}** to **{}**.", playlist.name, name))
+                    .build()
+            }
+            Some("delete") => {
+                let playlist = find_playlist(&state, author.id, &mut cmd_ctx, true)?;
+                state.storage.delete_playlist(author.id, playlist.id)?;
+                CommandResponseBuilder::new()
+                    .content(format!(
+                        "🗑️ Deleted **{}** and its {} tracks.",
+                        playlist.name, playlist.track_count
+                    ))
+                    .build()
+            }
+            Some("list") => {
+                let playlists = state.storage.playlists(author.id)?;
+                CommandResponseBuilder::new()
+                    .embed(build_playlists_embed(&playlists))
+                    .build()
+            }
+            Some("show") => {
+                let playlist = find_playlist(&state, author.id, &mut cmd_ctx, true)?;
+                let page = cmd_ctx
+                    .get_arg::<i64>("page")
+                    .map_or(0, |page| usize::try_from(page - 1).unwrap_or(0));
+                let tracks = state.storage.playlist_tracks(playlist.id)?;
+                let mut embed = build_tracks_embed(&format!("📁 {}", playlist.name), &tracks, page);
+                if let Some(source) = &playlist.source {
+                    embed.description = Some(format!(
+                        "Imported from **{}** by <@{}> with code `{}` <t:{}:R>\n\n{}",
+                        source.name,
+                        source.owner_id,
+                        source.code,
+                        source.imported_at / 1000,
+                        embed.description.unwrap_or_default()
+                    ));
+                }
+                CommandResponseBuilder::new().embed(embed).build()
+            }
+            Some("play") => {
+                let guild_id = cmd_ctx
+                    .guild_id()
+                    .ok_or_else(|| anyhow!("This command must be used in a guild."))?;
+                let playlist = find_playlist(&state, author.id, &mut cmd_ctx, true)?;
+                let tracks = state.storage.playlist_tracks(playlist.id)?;
+                if tracks.is_empty() {
+                    return Err(anyhow!("**{}** has no tracks yet.", playlist.name));
+                }
+                if state.cache.voice_state(author.id, guild_id).is_none() {
+                    return Err(anyhow!(
+                        "You must be in a voice channel to use this command."
+                    ));
+                }
+                let content = match queue_saved_tracks(
+                    &state,
+                    guild_id,
+                    author.id,
+                    cmd_ctx.member_permissions(),
+                    cmd_ctx.channel_id(),
+                    &tracks,
+                )
+                .await?
+                {
+                    Enqueued::Queued(queued) => format!(
+                        "`＋` Queued playlist **{}** ({} tracks)",
+                        playlist.name, queued
+                    ),
+                    Enqueued::PendingApproval(reply) => reply,
+                };
+                CommandResponseBuilder::new().content(content).build()
+            }
+            Some("share") => {
+                let playlist = find_playlist(&state, author.id, &mut cmd_ctx, false)?;
+                let expires_at = cmd_ctx.get_arg::<i64>("days").map(|days| {
+                    chrono::Utc::now().timestamp_millis() + days.clamp(1, 365) * DAY_MS
+                });
+                let code =
+                    state
+                        .storage
+                        .create_playlist_share(author.id, playlist.id, expires_at)?;
+                let expiry = match expires_at {
+                    Some(expires_at) => format!("It stops working <t:{}:R>.", expires_at / 1000),
+                    None => "It works until you revoke it with `/playlist unshare`.".to_string(),
+                };
+                CommandResponseBuilder::new()
+                    .content(format!(
+                        "🔗 Share code for **{}**: `{}`\nAnyone can copy it with `/playlist import {}`. {}",
+                        playlist.name, code, code, expiry
+                    ))
+                    .build()
+            }
+            Some("unshare") => {
+                let code = share_code_arg(&mut cmd_ctx)?;
+                if !state.storage.revoke_playlist_share(author.id, &code)? {
+                    return Err(anyhow!("You have no active share code `{}`.", code));
+                }
+                CommandResponseBuilder::new()
+                    .content(format!("🔗 Revoked share code `{}`.", code))
+                    .build()
+            }
+            Some("shares") => {
+                let shares = state.storage.active_playlist_shares(author.id)?;
+                CommandResponseBuilder::new()
+                    .embed(build_shares_embed(&shares))
+                    .build()
+            }
+            Some("import") => {
+                let code = share_code_arg(&mut cmd_ctx)?;
+                let share = state
+                    .storage
+                    .playlist_share(&code)?
+                    .ok_or_else(|| anyhow!("No playlist is shared with the code `{}`.", code))?;
+                if share.revoked_at.is_some() {
+                    return Err(anyhow!("The share code `{}` has been revoked.", code));
+                }
+                if !share.is_active(chrono::Utc::now().timestamp_millis()) {
+                    return Err(anyhow!("The share code `{}` has expired.", code));
+                }
+                if share.created_by == author.id {
+                    return Err(anyhow!("**{}** is already yours.", share.playlist_name));
+                }
+                if state.storage.playlists(author.id)?.len() >= MAX_PLAYLISTS {
+                    return Err(anyhow!("You can have at most {} playlists.", MAX_PLAYLISTS));
+                }
+                let playlist = state.storage.clone_shared_playlist(&share, author.id)?;
+                CommandResponseBuilder::new()
+                    .content(format!(
+                        "📥 Imported **{}** ({} tracks) from <@{}>.",
+                        playlist.name, playlist.track_count, share.created_by
+                    ))
+                    .build()
+            }
+            Some(other) => return Err(anyhow!("Unknown playlist subcommand: {}", other)),
+            None => {
+                return Err(anyhow!(
+                    "Use `playlist create|add|remove|rename|delete|list|show|play|share|unshare|shares|import`."
+                ));
+            }
+        };
+
+        cmd_ctx.reply(response).await?;
+
+        Ok(())
+    }
+
+    async fn autocomplete(
+        state: GlobalState,
+        interaction: Interaction,
+        data: CommandData,
+    ) -> Result<()> {
+        let Some(user_id) = interaction.author_id() else {
+            return Ok(());
+        };
+        let query = focused_option(&data).map_or("", |(_, value)| value);
+        let choices = state
+            .storage
+            .playlists(user_id)?
+            .into_iter()
+            .filter(|playlist| query.trim().is_empty() || fuzzy::matches(query, &playlist.name))
+            .map(|playlist| {
+                let name: String = playlist.name.chars().take(MAX_CHOICE_LEN).collect();
+                (name.clone(), name)
+            })
+            .collect();
+
+        state
+            .http
+            .interaction(interaction.application_id)
+            .create_response(
+                interaction.id,
+                &interaction.token,
+                &autocomplete_response(choices),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+/// Reads a playlist name. Prefix commands take names with spaces where the
+/// name is the last argument, or elsewhere in double quotes.
+pub(super) fn playlist_name_arg(
+    cmd_ctx: &mut CommandContext<'_>,
+    name: &str,
+    last: bool,
+) -> Result<String> {
+    let value = if last {
+        cmd_ctx.get_remainder_arg(name)
+    } else {
+        cmd_ctx.get_quoted_arg(name)
+    };
+    let value = value
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+        .ok_or_else(|| anyhow!("You must provide a playlist name."))?;
+    if value.chars().count() > MAX_PLAYLIST_NAME_LEN {
+        return Err(anyhow!(
+            "Playlist names can be at most {} characters long.",
+            MAX_PLAYLIST_NAME_LEN
+        ));
+    }
+    Ok(value)
+}
+
+fn share_code_arg(cmd_ctx: &mut CommandContext<'_>) -> Result<String> {
+    let input = cmd_ctx
+        .get_remainder_arg("code")
+        .ok_or_else(|| anyhow!("You must provide a share code."))?;
+    normalize_share_code(&input).ok_or_else(|| anyhow!("`{}` is not a share code.", input.trim()))
+}
+
+fn find_playlist(
+    state: &GlobalState,
+    user_id: Id<UserMarker>,
+    cmd_ctx: &mut CommandContext<'_>,
+    last: bool,
+) -> Result<Playlist> {
+    let name = playlist_name_arg(cmd_ctx, "playlist", last)?;
+    state
+        .storage
+        .playlist(user_id, &name)?
+        .ok_or_else(|| anyhow!("You have no playlist called `{}`.", name))
+}
+
+/// What `add` saves: the whole queue, the result of the `query` option, or
+/// the current track. Prefix commands spell the option as `add <name> queue`.
+/// Uploaded files are left out, see [`UPLOAD_NOT_SAVED`].
+pub(super) async fn tracks_to_add(
+    state: &GlobalState,
+    cmd_ctx: &mut CommandContext<'_>,
+    guild_id: Id<GuildMarker>,
+) -> Result<Vec<TrackData>> {
+    let query = cmd_ctx.get_remainder_arg("query");
+    let whole_queue = if matches!(cmd_ctx, CommandContext::Prefix(_)) {
+        query
+            .as_deref()
+            .is_some_and(|q| q.eq_ignore_ascii_case("queue"))
+    } else {
+        cmd_ctx.get_arg::<bool>("queue").unwrap_or(false)
+    };
+
+    let mut tracks = if whole_queue {
+        current_and_queued(state, guild_id).await?
+    } else if let Some(query) = query {
+        search_tracks(state, guild_id, query).await?
+    } else {
+        current_and_queued(state, guild_id)
+            .await?
+            .into_iter()
+            .take(1)
+            .collect()
+    };
+    if tracks.is_empty() {
+        return Err(anyhow!("Nothing is playing right now."));
+    }
+    tracks.retain(|track| !is_upload(track));
+    if tracks.is_empty() {
+        return Err(anyhow!(UPLOAD_NOT_SAVED));
+    }
+    Ok(tracks)
+}
+
+/// Drops the tracks that don't fit into a playlist holding `track_count`.
+/// Returns how many were dropped.
+pub(super) fn fit_tracks(
+    name: &str,
+    track_count: usize,
+    tracks: &mut Vec<TrackData>,
+) -> Result<usize> {
+    let room = MAX_PLAYLIST_TRACKS.saturating_sub(track_count);
+    if room == 0 {
+        return Err(anyhow!(
+            "**{}** already has {} tracks, the most a playlist can hold.",
+            name,
+            MAX_PLAYLIST_TRACKS
+        ));
+    }
+    let left_out = tracks.len().saturating_sub(room);
+    tracks.truncate(room);
+    Ok(left_out)
+}
+
+pub(super) fn added_message(tracks: &[TrackData], name: &str, left_out: usize) -> String {
+    let mut content = match tracks {
+        [track] => format!("📁 Added **{}** to **{}**.", track.info.title, name),
+        tracks => format!("📁 Added {} tracks to **{}**.", tracks.len(), name),
+    };
+    if left_out > 0 {
+        content.push_str(&format!(
+            " {} did not fit; playlists hold at most {} tracks.",
+            left_out, MAX_PLAYLIST_TRACKS
+        ));
+    }
+    content
+}
+
+/// The playing track followed by everything queued after it.
+async fn current_and_queued(
+    state: &GlobalState,
+    guild_id: Id<GuildMarker>,
+) -> Result<Vec<TrackData>> {
+    let player = state
+        .lavalink()
+        .get_player_context(guild_id)
+        .ok_or_else(|| anyhow!("Nothing is playing right now."))?;
+    let current = player.get_player().await?.track;
+    let queued = player.get_queue().get_queue().await?;
+    Ok(current
+        .into_iter()
+        .chain(queued.into_iter().map(|t| t.track))
+        .collect())
+}
+
+/// Resolves a query the way `/play` does: a URL's tracks, or the top search hit.
+async fn search_tracks(
+    state: &GlobalState,
+    guild_id: Id<GuildMarker>,
+    query: String,
+) -> Result<Vec<TrackData>> {
+    let loaded = state
+        .lavalink()
+        .load_tracks(guild_id, &to_query(query)?)
+        .await?;
+    match loaded.data {
+        Some(TrackLoadData::Track(track)) => Ok(vec![track]),
+        Some(TrackLoadData::Search(results)) => Ok(results.into_iter().take(1).collect()),
+        Some(TrackLoadData::Playlist(playlist)) => Ok(playlist.tracks),
+        Some(TrackLoadData::Error(e)) => Err(anyhow!("Error loading tracks: {}", e.message)),
+        None => Err(anyhow!("No tracks found.")),
+    }
+}
+
+pub fn new_playlist_track(track: &TrackData) -> NewPlaylistTrack<'_> {
+    NewPlaylistTrack {
+        encoded: &track.encoded,
+        title: &track.info.title,
+        author: &track.info.author,
+        uri: track.info.uri.as_deref(),
+        length_ms: track.info.length,
+        is_stream: track.info.is_stream,
+    }
+}
+
+/// Decodes saved tracks in one request and queues them for `requester`.
+pub(super) async fn queue_saved_tracks(
+    state: &GlobalState,
+    guild_id: Id<GuildMarker>,
+    requester: Id<UserMarker>,
+    permissions: Option<Permissions>,
+    text_channel_id: Option<Id<ChannelMarker>>,
+    tracks: &[PlaylistTrack],
+) -> Result<Enqueued> {
+    let encoded: Vec<String> = tracks.iter().map(|t| t.encoded.clone()).collect();
+    let decoded = state
+        .lavalink()
+        .decode_tracks(guild_id, &encoded)
+        .await
+        .map_err(|e| anyhow!("Could not load the saved tracks: {}", e))?;
+    if decoded.is_empty() {
+        return Ok(Enqueued::Queued(0));
+    }
+    let tracks = decoded.into_iter().map(TrackInQueue::from).collect();
+    enqueue(
+        state,
+        guild_id,
+        tracks,
+        requester,
+        permissions,
+        text_channel_id,
+        false,
+    )
+    .await
+}
+
+fn build_playlists_embed(playlists: &[Playlist]) -> Embed {
+    let description = if playlists.is_empty() {
+        "You have no playlists yet. Make one with `/playlist create`.".to_string()
+    } else {
+        playlists
+            .iter()
+            .map(|playlist| format!("**{}** · {} tracks", playlist.name, playlist.track_count))
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    EmbedBuilder::new()
+        .title("📁 Your playlists")
+        .description(description)
+        .color(0x1DB954)
+        .footer(EmbedFooterBuilder::new(format!(
+            "{}/{} playlists",
+            playlists.len(),
+            MAX_PLAYLISTS
+        )))
+        .build()
+}
+
+fn build_shares_embed(shares: &[PlaylistShare]) -> Embed {
+    let description = if shares.is_empty() {
+        "You have no active share codes. Make one with `/playlist share`.".to_string()
+    } else {
+        shares
+            .iter()
+            .map(|share| {
+                let expiry = share.expires_at.map_or_else(
+                    || "never expires".to_string(),
+                    |at| format!("expires <t:{}:R>", at / 1000),
+                );
+                format!(
+                    "`{}` · **{}** · {} · imported {} times",
+                    share.code, share.playlist_name, expiry, share.uses
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    EmbedBuilder::new()
+        .title("🔗 Your share codes")
+        .description(description)
+        .color(0x1DB954)
+        .build()
+}
+
+/// One page of a saved track list.
+pub(super) fn build_tracks_embed(title: &str, tracks: &[PlaylistTrack], page: usize) -> Embed {
+    let page_count = tracks.len().div_ceil(PLAYLIST_PAGE_SIZE).max(1);
+    let page = page.min(page_count - 1);
+    let description = if tracks.is_empty() {
+        "This playlist is empty.".to_string()
+    } else {
+        tracks
+            .iter()
+            .enumerate()
+            .skip(page * PLAYLIST_PAGE_SIZE)
+            .take(PLAYLIST_PAGE_SIZE)
+            .map(|(idx, track)| {
+                let title = match &track.uri {
+                    Some(uri) => format!("[{}](<{}>)", track.title, uri),
+                    None => track.title.clone(),
+                };
+                format!(
+                    "`{}.` {} - {} `[{}]`",
+                    idx + 1,
+                    title,
+                    track.author,
+                    format_length_ms(track.length_ms, track.is_stream)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+    // Streams never end, so they count as live rather than towards the total.
+    let total_ms = tracks
+        .iter()
+        .filter(|t| !t.is_stream)
+        .fold(0u64, |total, t| total.saturating_add(t.length_ms));
+    let mut total = format_length_ms(total_ms, false);
+    if tracks.iter().any(|t| t.is_stream) {
+        total = format!("{} + {}", total, LIVE_BADGE);
+    }
+
+    EmbedBuilder::new()
+        .title(title)
+        .description(description)
+        .color(0x1DB954)
+        .footer(EmbedFooterBuilder::new(format!(
+            "Page {}/{} • {} tracks • {}",
+            page + 1,
+            page_count,
+            tracks.len(),
+            total
+        )))
+        .build()
+}
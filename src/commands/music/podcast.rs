@@ -0,0 +1,331 @@
+use std::time::Duration;
+
+use super::{Enqueued, enqueue};
+use crate::command_handler::{
+    Command, CommandContext, CommandResponseBuilder, GlobalState, StateExt, autocomplete_response,
+    focused_option,
+};
+use crate::storage::PodcastSubscription;
+use crate::utils::fuzzy;
+use crate::utils::podcast::{Feed, fetch_feed, resume_position, select_episode};
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+use lavalink_rs::prelude::{TrackInQueue, TrackLoadData};
+use twilight_interactions::command::{CommandModel, CreateCommand};
+use twilight_model::application::interaction::{Interaction, application_command::CommandData};
+use twilight_model::channel::message::Embed;
+use twilight_model::id::{Id, marker::GuildMarker};
+use twilight_util::builder::embed::{EmbedBuilder, EmbedFooterBuilder};
+
+const EPISODES_PAGE_SIZE: usize = 10;
+/// Autocomplete choice names and values are capped at 100 characters.
+const MAX_CHOICE_LEN: usize = 100;
+
+#[derive(CommandModel, CreateCommand)]
+#[command(name = "podcast", desc = "Subscribe to and listen to podcasts.")]
+pub enum PodcastCommand {
+    #[command(name = "subscribe")]
+    Subscribe(PodcastSubscribeCommand),
+    #[command(name = "unsubscribe")]
+    Unsubscribe(PodcastUnsubscribeCommand),
+    #[command(name = "list")]
+    List(PodcastListCommand),
+    #[command(name = "episodes")]
+    Episodes(PodcastEpisodesCommand),
+    #[command(name = "play")]
+    Play(PodcastPlayCommand),
+}
+
+#[allow(unused)]
+#[derive(CommandModel, CreateCommand)]
+#[command(name = "subscribe", desc = "Subscribe this server to a podcast feed.")]
+pub struct PodcastSubscribeCommand {
+    #[command(desc = "The RSS or Atom feed URL")]
+    url: String,
+}
+
+#[allow(unused)]
+#[derive(CommandModel, CreateCommand)]
+#[command(name = "unsubscribe", desc = "Remove a podcast from this server.")]
+pub struct PodcastUnsubscribeCommand {
+    #[command(desc = "The podcast to remove", autocomplete = true)]
+    show: String,
+}
+
+#[derive(CommandModel, CreateCommand)]
+#[command(name = "list", desc = "List this server's podcasts.")]
+pub struct PodcastListCommand;
+
+#[allow(unused)]
+#[derive(CommandModel, CreateCommand)]
+#[command(name = "episodes", desc = "List a podcast's episodes.")]
+pub struct PodcastEpisodesCommand {
+    #[command(desc = "The podcast", autocomplete = true)]
+    show: String,
+    #[command(desc = "The page to show", min_value = 1)]
+    page: Option<i64>,
+}
+
+#[allow(unused)]
+#[derive(CommandModel, CreateCommand)]
+#[command(
+    name = "play",
+    desc = "Queue a podcast episode, resuming where you left off."
+)]
+pub struct PodcastPlayCommand {
+    #[command(desc = "The podcast", autocomplete = true)]
+    show: String,
+    #[command(
+        desc = "Episode number from /podcast episodes, or part of its title; defaults to the newest"
+    )]
+    episode: Option<String>,
+}
+
+#[async_trait]
+impl Command<GlobalState> for PodcastCommand {
+    async fn execute<'ctx>(state: GlobalState, mut cmd_ctx: CommandContext<'ctx>) -> Result<()> {
+        let guild_id = cmd_ctx
+            .guild_id()
+            .ok_or_else(|| anyhow!("This command must be used in a guild."))?;
+        let author = cmd_ctx
+            .author()
+            .ok_or_else(|| anyhow!("Interaction is missing author information."))?
+            .clone();
+
+        let subcommand = cmd_ctx.subcommand().map(str::to_owned);
+        let response = match subcommand.as_deref() {
+            Some("subscribe") => {
+                let url: String = cmd_ctx
+                    .get_arg("url")
+                    .ok_or_else(|| anyhow!("You must provide the feed URL."))?;
+                if !url.starts_with("http://") && !url.starts_with("https://") {
+                    return Err(anyhow!("The feed URL must start with http:// or https://."));
+                }
+                let feed = fetch_feed(&state.reqwest, &url).await?;
+                state
+                    .storage
+                    .subscribe_podcast(guild_id, &url, &feed.title, author.id)?;
+                CommandResponseBuilder::new()
+                    .content(format!(
+                        "🎙️ Subscribed to **{}** ({} episodes).",
+                        feed.title,
+                        feed.episodes.len()
+                    ))
+                    .build()
+            }
+            Some("unsubscribe") => {
+                let show: String = cmd_ctx
+                    .get_remainder_arg("show")
+                    .ok_or_else(|| anyhow!("You must provide the podcast to remove."))?;
+                let subscription = find_subscription(&state, guild_id, &show)?;
+                state
+                    .storage
+                    .unsubscribe_podcast(guild_id, &subscription.feed_url)?;
+                CommandResponseBuilder::new()
+                    .content(format!("🗑️ Unsubscribed from **{}**.", subscription.title))
+                    .build()
+            }
+            Some("list") => {
+                let subscriptions = state.storage.podcast_subscriptions(guild_id)?;
+                let content = if subscriptions.is_empty() {
+                    "No podcasts yet. Add one with `/podcast subscribe`.".to_string()
+                } else {
+                    subscriptions
+                        .iter()
+                        .map(|sub| format!("🎙️ **{}** — <{}>", sub.title, sub.feed_url))
+                        .collect::<Vec<_>>()
+                        .join("\n")
+                };
+                CommandResponseBuilder::new().content(content).build()
+            }
+            Some("episodes") => {
+                let show: String = cmd_ctx
+                    .get_arg("show")
+                    .ok_or_else(|| anyhow!("You must provide the podcast."))?;
+                let page = cmd_ctx
+                    .get_arg::<i64>("page")
+                    .map_or(0, |page| usize::try_from(page - 1).unwrap_or(0));
+                let subscription = find_subscription(&state, guild_id, &show)?;
+                let feed = fetch_feed(&state.reqwest, &subscription.feed_url).await?;
+                CommandResponseBuilder::new()
+                    .embed(build_episodes_embed(&feed, page))
+                    .build()
+            }
+            Some("play") => {
+                let show: String = cmd_ctx
+                    .get_arg("show")
+                    .ok_or_else(|| anyhow!("You must provide the podcast to play."))?;
+                let episode = cmd_ctx.get_remainder_arg("episode");
+                if state.cache.voice_state(author.id, guild_id).is_none() {
+                    return Err(anyhow!(
+                        "You must be in a voice channel to use this command."
+                    ));
+                }
+
+                let subscription = find_subscription(&state, guild_id, &show)?;
+                let feed = fetch_feed(&state.reqwest, &subscription.feed_url).await?;
+                let episode = select_episode(&feed, episode.as_deref())
+                    .ok_or_else(|| anyhow!("No episode of {} matches that.", feed.title))?;
+
+                let loaded = state.lavalink().load_tracks(guild_id, &episode.url).await?;
+                let mut track = match loaded.data {
+                    Some(TrackLoadData::Track(track)) => track,
+                    Some(TrackLoadData::Error(e)) => {
+                        return Err(anyhow!("Error loading episode: {}", e.message));
+                    }
+                    _ => return Err(anyhow!("Lavalink could not load the episode audio.")),
+                };
+                // Direct audio links rarely carry tags, so use the feed's names.
+                track.info.title = episode.title.clone();
+                track.info.author = feed.title.clone();
+                track.user_data = Some(serde_json::json!({ "podcast_episode": episode.id }));
+
+                let length_ms = Some(track.info.length)
+                    .filter(|length| *length > 0)
+                    .or(episode.duration_secs.map(|secs| secs * 1000));
+                let resume_at = state
+                    .storage
+                    .episode_position(guild_id, &episode.id)?
+                    .and_then(|saved| resume_position(saved, length_ms));
+
+                let track = TrackInQueue {
+                    start_time: resume_at.map(Duration::from_millis),
+                    ..TrackInQueue::from(track)
+                };
+                let content = match enqueue(
+                    &state,
+                    guild_id,
+                    vec![track],
+                    author.id,
+                    cmd_ctx.member_permissions(),
+                    cmd_ctx.channel_id(),
+                    false,
+                )
+                .await?
+                {
+                    Enqueued::Queued(_) => {
+                        let mut content =
+                            format!("`＋` Queued **{}** from {}", episode.title, feed.title);
+                        if let Some(resume_at) = resume_at {
+                            content.push_str(&format!(
+                                ", resuming at {}",
+                                humantime::format_duration(Duration::from_secs(resume_at / 1000))
+                            ));
+                        }
+                        content
+                    }
+                    Enqueued::PendingApproval(reply) => reply,
+                };
+                CommandResponseBuilder::new().content(content).build()
+            }
+            Some(other) => return Err(anyhow!("Unknown podcast subcommand: {}", other)),
+            None => {
+                return Err(anyhow!(
+                    "Use `podcast subscribe|unsubscribe|list|episodes|play`."
+                ));
+            }
+        };
+
+        cmd_ctx.reply(response).await?;
+
+        Ok(())
+    }
+
+    async fn autocomplete(
+        state: GlobalState,
+        interaction: Interaction,
+        data: CommandData,
+    ) -> Result<()> {
+        let Some(guild_id) = interaction.guild_id else {
+            return Ok(());
+        };
+        let query = focused_option(&data).map_or("", |(_, value)| value);
+        let choices = state
+            .storage
+            .podcast_subscriptions(guild_id)?
+            .into_iter()
+            .filter(|sub| query.trim().is_empty() || fuzzy::matches(query, &sub.title))
+            .map(|sub| {
+                let title: String = sub.title.chars().take(MAX_CHOICE_LEN).collect();
+                (title.clone(), title)
+            })
+            .collect();
+
+        state
+            .http
+            .interaction(interaction.application_id)
+            .create_response(
+                interaction.id,
+                &interaction.token,
+                &autocomplete_response(choices),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+/// Finds the subscription by feed URL or title, falling back to a fuzzy match.
+fn find_subscription(
+    state: &GlobalState,
+    guild_id: Id<GuildMarker>,
+    query: &str,
+) -> Result<PodcastSubscription> {
+    let subscriptions = state.storage.podcast_subscriptions(guild_id)?;
+    subscriptions
+        .iter()
+        .find(|sub| sub.feed_url == query || sub.title.eq_ignore_ascii_case(query))
+        .or_else(|| {
+            subscriptions
+                .iter()
+                .find(|sub| fuzzy::matches(query, &sub.title))
+        })
+        .cloned()
+        .ok_or_else(|| {
+            anyhow!(
+                "This server has no podcast called `{}`. See `/podcast list`.",
+                query
+            )
+        })
+}
+
+fn build_episodes_embed(feed: &Feed, page: usize) -> Embed {
+    if feed.episodes.is_empty() {
+        return EmbedBuilder::new()
+            .title(format!("🎙️ {}", feed.title))
+            .description("This feed has no playable episodes.")
+            .color(0x1DB954)
+            .build();
+    }
+
+    let page_count = feed.episodes.len().div_ceil(EPISODES_PAGE_SIZE);
+    let page = page.min(page_count - 1);
+    let listing = feed
+        .episodes
+        .iter()
+        .enumerate()
+        .skip(page * EPISODES_PAGE_SIZE)
+        .take(EPISODES_PAGE_SIZE)
+        .map(|(idx, episode)| {
+            format!(
+                "`{}.` **{}**\n{}",
+                idx + 1,
+                episode.title,
+                episode.details()
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    EmbedBuilder::new()
+        .title(format!("🎙️ {}", feed.title))
+        .description(listing)
+        .color(0x1DB954)
+        .footer(EmbedFooterBuilder::new(format!(
+            "Page {}/{} • {} episodes",
+            page + 1,
+            page_count,
+            feed.episodes.len()
+        )))
+        .build()
+}
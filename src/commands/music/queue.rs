@@ -1,37 +1,234 @@
-use super::check_voice_state;
+use std::collections::{HashSet, VecDeque};
+use std::str::FromStr;
+use std::time::Duration;
+
+use super::approval::approval_needed;
+use super::{check_voice_state, connect};
 use crate::command_handler::{
-    Command, CommandContext, CommandResponseBuilder, GlobalState, StateExt,
+    Command, CommandContext, CommandResponseBuilder, FromCommandOptionValue, GlobalState,
+    StateExt,
+};
+use crate::utils::eta::{queue_start_times, queued_length_ms};
+use crate::utils::fuzzy;
+use crate::utils::queue_file::{
+    ExportedCurrent, ExportedTrack, QUEUE_FILE_VERSION, QueueFile, parse_queue_file,
+    restored_user_data,
+};
+use crate::utils::queue_history::{QueueSnapshot, snapshot_queue};
+use crate::utils::track::{
+    RESUMED_FLAG, added_at, flag_track, format_length, requester_id, track_key,
 };
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use async_trait::async_trait;
-use lavalink_rs::player_context::QueueRef;
-use twilight_interactions::command::{CommandModel, CreateCommand};
-use twilight_model::channel::message::Embed;
-use twilight_util::builder::embed::EmbedBuilder;
+use lavalink_rs::model::track::TrackData;
+use lavalink_rs::prelude::TrackInQueue;
+use twilight_interactions::command::{CommandModel, CommandOption, CreateCommand, CreateOption};
+use twilight_model::application::interaction::application_command::CommandOptionValue;
+use twilight_model::channel::Attachment;
+use twilight_model::channel::message::{
+    Component, Embed,
+    component::{ActionRow, Button, ButtonStyle},
+};
+use twilight_model::id::{Id, marker::GuildMarker};
+use twilight_util::builder::embed::{EmbedBuilder, EmbedFooterBuilder};
+
+pub const QUEUE_PAGE_SIZE: usize = 10;
+/// Discord allows at most five action rows per message.
+const MAX_FIND_BUTTON_ROWS: usize = 5;
+/// Exports of the largest allowed queue stay well below this.
+const MAX_QUEUE_FILE_SIZE: u64 = 2 * 1024 * 1024;
+
+#[derive(CommandModel, CreateCommand)]
+#[command(name = "queue", desc = "Show or rearrange the music queue.")]
+pub enum QueueCommand {
+    #[command(name = "show")]
+    Show(QueueShowCommand),
+    #[command(name = "sort")]
+    Sort(QueueSortCommand),
+    #[command(name = "reverse")]
+    Reverse(QueueReverseCommand),
+    #[command(name = "dedupe")]
+    Dedupe(QueueDedupeCommand),
+    #[command(name = "find")]
+    Find(QueueFindCommand),
+    #[command(name = "export")]
+    Export(QueueExportCommand),
+    #[command(name = "import")]
+    Import(QueueImportCommand),
+}
+
+#[allow(unused)]
+#[derive(CommandModel, CreateCommand)]
+#[command(name = "show", desc = "Show the current music queue.")]
+pub struct QueueShowCommand {
+    #[command(desc = "The page to show", min_value = 1)]
+    page: Option<i64>,
+}
+
+#[allow(unused)]
+#[derive(CommandModel, CreateCommand)]
+#[command(name = "sort", desc = "Sort the queued tracks.")]
+pub struct QueueSortCommand {
+    #[command(desc = "What to sort the queue by")]
+    by: QueueSortKey,
+}
+
+#[derive(CommandModel, CreateCommand)]
+#[command(name = "reverse", desc = "Reverse the order of the queued tracks.")]
+pub struct QueueReverseCommand;
+
+#[derive(CommandModel, CreateCommand)]
+#[command(name = "dedupe", desc = "Remove duplicate tracks from the queue.")]
+pub struct QueueDedupeCommand;
+
+#[allow(unused)]
+#[derive(CommandModel, CreateCommand)]
+#[command(name = "find", desc = "Search the queue by title, artist or URL.")]
+pub struct QueueFindCommand {
+    #[command(desc = "The text to search for")]
+    text: String,
+}
 
 #[derive(CommandModel, CreateCommand)]
-#[command(name = "queue", desc = "Show the current music queue.")]
-pub struct QueueCommand;
+#[command(name = "export", desc = "Save the current track and queue to a file.")]
+pub struct QueueExportCommand;
+
+#[allow(unused)]
+#[derive(CommandModel, CreateCommand)]
+#[command(name = "import", desc = "Replace the queue with one saved by /queue export.")]
+pub struct QueueImportCommand {
+    #[command(desc = "The exported queue file")]
+    file: Attachment,
+}
+
+#[derive(CommandOption, CreateOption, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum QueueSortKey {
+    #[option(name = "Title", value = "title")]
+    Title,
+    #[option(name = "Artist", value = "artist")]
+    Artist,
+    #[option(name = "Duration", value = "duration")]
+    Duration,
+    #[option(name = "Requester", value = "requester")]
+    Requester,
+    #[option(name = "Added", value = "added")]
+    Added,
+}
+
+impl FromStr for QueueSortKey {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "title" => Ok(Self::Title),
+            "artist" => Ok(Self::Artist),
+            "duration" => Ok(Self::Duration),
+            "requester" => Ok(Self::Requester),
+            "added" => Ok(Self::Added),
+            other => Err(anyhow!("Unknown sort key: {}", other)),
+        }
+    }
+}
+
+impl FromCommandOptionValue for QueueSortKey {
+    fn from_option_value(value: &CommandOptionValue) -> Option<Self> {
+        if let CommandOptionValue::String(s) = value {
+            s.parse().ok()
+        } else {
+            None
+        }
+    }
+}
 
 #[async_trait]
 impl Command<GlobalState> for QueueCommand {
-    async fn execute<'ctx>(state: GlobalState, cmd_ctx: CommandContext<'ctx>) -> Result<()> {
-        let guild_id = match &cmd_ctx {
-            CommandContext::Prefix(prefix_ctx) => prefix_ctx.message.guild_id,
-            CommandContext::Slash(slash_ctx) => slash_ctx.interaction.guild_id,
+    async fn execute<'ctx>(state: GlobalState, mut cmd_ctx: CommandContext<'ctx>) -> Result<()> {
+        let guild_id = cmd_ctx
+            .guild_id()
+            .ok_or_else(|| anyhow!("This command must be used in a guild."))?;
+
+        let subcommand = cmd_ctx.subcommand().map(str::to_owned);
+        match subcommand.as_deref() {
+            Some("export") => return export_queue(&state, &cmd_ctx, guild_id).await,
+            // Importing works in any guild, so there may be no player yet.
+            Some("import") => return import_queue(&state, &cmd_ctx, guild_id).await,
+            _ => {}
         }
-        .ok_or_else(|| anyhow::anyhow!("This command must be used in a guild."))?;
 
         check_voice_state(state.clone(), &cmd_ctx).await?;
 
         let player = state
             .lavalink()
             .get_player_context(guild_id)
-            .ok_or_else(|| anyhow::anyhow!("No player found for this guild."))?;
+            .ok_or_else(|| anyhow!("No player found for this guild."))?;
+        let player_data = player.get_player().await?;
         let queue = player.get_queue();
+        let mut tracks = queue.get_queue().await?;
+        let before = QueueSnapshot {
+            current: player_data.track.clone(),
+            position: player_data.state.position,
+            queue: tracks.clone(),
+        };
+
+        let content = match subcommand.as_deref() {
+            None | Some("show") => {
+                let page = cmd_ctx
+                    .get_arg::<i64>("page")
+                    .map_or(0, |page| usize::try_from(page.saturating_sub(1)).unwrap_or(0));
+                let start_times = queue_start_times(&player_data, &tracks);
+                let embed = build_queue_embed(&tracks, &start_times, page);
+                cmd_ctx
+                    .reply(CommandResponseBuilder::new().embed(embed).build())
+                    .await?;
+                return Ok(());
+            }
+            Some("sort") => {
+                let key: QueueSortKey = cmd_ctx.get_arg("by").ok_or_else(|| {
+                    anyhow!("Sort key must be one of: title, artist, duration, requester, added.")
+                })?;
+                sort_queue(&mut tracks, key);
+                "🔀 Sorted the queue.".to_string()
+            }
+            Some("reverse") => {
+                tracks.make_contiguous().reverse();
+                "🔃 Reversed the queue.".to_string()
+            }
+            Some("find") => {
+                let text = cmd_ctx
+                    .get_remainder_arg("text")
+                    .ok_or_else(|| anyhow!("You must provide some text to search for."))?;
+                let matches = find_in_queue(&tracks, &text);
+                let mut response = CommandResponseBuilder::new()
+                    .embed(build_find_embed(&tracks, &matches, &text));
+                for &idx in matches.iter().take(MAX_FIND_BUTTON_ROWS) {
+                    response = response.component(find_action_row(idx, &tracks[idx].track));
+                }
+                cmd_ctx.reply(response.build()).await?;
+                return Ok(());
+            }
+            Some("dedupe") => {
+                let removed = dedupe_queue(&mut tracks);
+                format!("🧹 Removed {} duplicate track(s).", removed)
+            }
+            Some(other) => return Err(anyhow!("Unknown queue subcommand: {}", other)),
+        };
 
-        let embed = build_queue_embed(&queue).await?;
-        let response = CommandResponseBuilder::new().embed(embed).build();
+        if tracks.is_empty() {
+            return Err(anyhow!("The queue is currently empty."));
+        }
+
+        let start_times = queue_start_times(&player_data, &tracks);
+        let embed = build_queue_embed(&tracks, &start_times, 0);
+
+        // Replace straight after the read that was transformed, and undo to
+        // that same read, so nothing queued in between is lost or doubled.
+        queue.replace(tracks)?;
+        state.queue_history.lock().await.push(guild_id, before);
+
+        let response = CommandResponseBuilder::new()
+            .content(content)
+            .embed(embed)
+            .build();
 
         cmd_ctx.reply(response).await?;
 
@@ -39,22 +236,327 @@ impl Command<GlobalState> for QueueCommand {
     }
 }
 
-pub async fn build_queue_embed(queue: &QueueRef) -> Result<Embed> {
+/// Uploads the current track, its position and the queue as a [`QueueFile`].
+async fn export_queue(
+    state: &GlobalState,
+    cmd_ctx: &CommandContext<'_>,
+    guild_id: Id<GuildMarker>,
+) -> Result<()> {
+    let player = state
+        .lavalink()
+        .get_player_context(guild_id)
+        .ok_or_else(|| anyhow!("No player found for this guild."))?;
+    let player_data = player.get_player().await?;
+    let tracks = player.get_queue().get_queue().await?;
+
+    let file = QueueFile {
+        version: QUEUE_FILE_VERSION,
+        exported_at: chrono::Utc::now().timestamp_millis(),
+        current: player_data.track.as_ref().map(|track| ExportedCurrent {
+            track: ExportedTrack::from_track(track),
+            position_ms: player_data.state.position,
+        }),
+        tracks: tracks
+            .iter()
+            .map(|t| ExportedTrack::from_track(&t.track))
+            .collect(),
+    };
+    if file.is_empty() {
+        return Err(anyhow!("There is nothing to export."));
+    }
+
+    let filename = format!("queue-{}.json", chrono::Utc::now().format("%Y%m%d-%H%M%S"));
+    let response = CommandResponseBuilder::new()
+        .content(format!(
+            "📤 Exported {} track(s). Restore them anywhere with `/queue import`.",
+            file.len()
+        ))
+        .attachment(filename, file.to_json()?)
+        .build();
+    cmd_ctx.reply(response).await?;
+
+    Ok(())
+}
+
+/// Replaces the queue with the tracks of an uploaded [`QueueFile`], resuming
+/// the exported current track where it left off.
+async fn import_queue(
+    state: &GlobalState,
+    cmd_ctx: &CommandContext<'_>,
+    guild_id: Id<GuildMarker>,
+) -> Result<()> {
+    let author = cmd_ctx
+        .author()
+        .ok_or_else(|| anyhow!("Interaction is missing author information."))?;
+    let attachment = cmd_ctx
+        .get_attachment("file")
+        .ok_or_else(|| anyhow!("You must attach a file made by `/queue export`."))?;
+    if attachment.size > MAX_QUEUE_FILE_SIZE {
+        return Err(anyhow!("That file is too big to be a queue export."));
+    }
+    let voice_state = state
+        .cache
+        .voice_state(author.id, guild_id)
+        .ok_or_else(|| anyhow!("You must be in a voice channel to use this command."))?;
+    if approval_needed(
+        state,
+        guild_id,
+        author.id,
+        (&cmd_ctx.member_roles(), cmd_ctx.member_permissions()),
+    )?
+    .is_some()
+    {
+        return Err(anyhow!(
+            "Requests need approval here, so ask a DJ to restore a queue."
+        ));
+    }
+
+    let contents = state
+        .reqwest
+        .get(&attachment.url)
+        .send()
+        .await?
+        .error_for_status()?
+        .text()
+        .await?;
+    let file = parse_queue_file(&contents)?;
+
+    connect(state.clone(), voice_state.channel_id(), cmd_ctx.channel_id(), guild_id).await?;
+
+    let exported: Vec<&ExportedTrack> = file
+        .current
+        .iter()
+        .map(|current| &current.track)
+        .chain(&file.tracks)
+        .collect();
+    let encoded: Vec<String> = exported.iter().map(|t| t.encoded.clone()).collect();
+    let decoded = state
+        .lavalink()
+        .decode_tracks(guild_id, &encoded)
+        .await
+        .map_err(|e| anyhow!("Could not decode the exported tracks: {}", e))?;
+    if decoded.len() != encoded.len() {
+        return Err(anyhow!(
+            "Only {} of {} exported tracks could be decoded.",
+            decoded.len(),
+            encoded.len()
+        ));
+    }
+
+    let mut decoded: VecDeque<TrackData> = decoded
+        .into_iter()
+        .zip(exported)
+        .map(|(mut track, exported)| {
+            track.user_data = Some(restored_user_data(exported.user_data.clone(), author.id));
+            track
+        })
+        .collect();
+    let current = match &file.current {
+        Some(current) => decoded.pop_front().map(|mut track| {
+            flag_track(&mut track, RESUMED_FLAG);
+            (track, current.position_ms)
+        }),
+        None => None,
+    };
+    let restored_count = decoded.len();
+
+    let player = state
+        .lavalink()
+        .get_player_context(guild_id)
+        .ok_or_else(|| anyhow!("Player context not found. Is the bot in a voice channel?"))?;
+
+    snapshot_queue(state, guild_id).await?;
+    let queue = player.get_queue();
+    queue.replace(decoded.into_iter().map(TrackInQueue::from).collect())?;
+
+    let content = if let Some((track, position)) = current {
+        let title = track.info.title.clone();
+        // Livestreams can't be seeked, so they just start again.
+        let start_time = (!track.info.is_stream && position > 0)
+            .then(|| Duration::from_millis(position));
+        queue.push_to_front(TrackInQueue {
+            start_time,
+            ..TrackInQueue::from(track)
+        })?;
+        player.skip()?;
+        format!(
+            "📥 Restored `{}` and {} queued track(s) from **{}**.",
+            title, restored_count, attachment.filename
+        )
+    } else {
+        if player.get_player().await?.track.is_none() {
+            player.skip()?;
+        }
+        format!(
+            "📥 Restored {} queued track(s) from **{}**.",
+            restored_count, attachment.filename
+        )
+    };
+
+    cmd_ctx
+        .reply(CommandResponseBuilder::new().content(content).build())
+        .await?;
+
+    Ok(())
+}
+
+pub fn sort_queue(tracks: &mut VecDeque<TrackInQueue>, key: QueueSortKey) {
+    let tracks = tracks.make_contiguous();
+    match key {
+        QueueSortKey::Title => tracks.sort_by_cached_key(|t| t.track.info.title.to_lowercase()),
+        QueueSortKey::Artist => tracks.sort_by_cached_key(|t| t.track.info.author.to_lowercase()),
+        QueueSortKey::Duration => {
+            tracks.sort_by_key(|t| (t.track.info.is_stream, t.track.info.length));
+        }
+        QueueSortKey::Requester => {
+            tracks.sort_by_key(|t| requester_id(&t.track).map_or(u64::MAX, |id| id.get()));
+        }
+        QueueSortKey::Added => tracks.sort_by_key(|t| added_at(&t.track).unwrap_or(i64::MAX)),
+    }
+}
+
+/// Drops every track that already appeared earlier in the queue and returns
+/// how many were removed.
+pub fn dedupe_queue(tracks: &mut VecDeque<TrackInQueue>) -> usize {
+    let before = tracks.len();
+    let mut seen = HashSet::new();
+    tracks.retain(|t| {
+        seen.insert((
+            t.track.info.source_name.clone(),
+            t.track.info.identifier.clone(),
+        ))
+    });
+    before - tracks.len()
+}
+
+/// Queue indices of every track whose title, author or URI loosely matches `text`.
+pub fn find_in_queue(tracks: &VecDeque<TrackInQueue>, text: &str) -> Vec<usize> {
+    tracks
+        .iter()
+        .enumerate()
+        .filter(|(_, t)| {
+            let info = &t.track.info;
+            fuzzy::matches(text, &info.title)
+                || fuzzy::matches(text, &info.author)
+                || fuzzy::matches(text, &format!("{} {}", info.title, info.author))
+                || info
+                    .uri
+                    .as_ref()
+                    .is_some_and(|uri| uri.to_lowercase().contains(&text.to_lowercase()))
+        })
+        .map(|(idx, _)| idx)
+        .collect()
+}
+
+fn build_find_embed(tracks: &VecDeque<TrackInQueue>, matches: &[usize], text: &str) -> Embed {
+    let embed = EmbedBuilder::new()
+        .title(format!("🔎 Queue results for \"{}\"", text))
+        .color(0x1DB954);
+
+    if matches.is_empty() {
+        return embed.description("No queued tracks match that search.").build();
+    }
+
+    let lines: Vec<String> = matches
+        .iter()
+        .take(QUEUE_PAGE_SIZE)
+        .map(|&idx| {
+            format!(
+                "`{}.` {} - {} `[{}]`",
+                idx + 1,
+                tracks[idx].track.info.title,
+                tracks[idx].track.info.author,
+                format_length(&tracks[idx].track)
+            )
+        })
+        .collect();
+
+    embed
+        .description(lines.join("\n"))
+        .footer(EmbedFooterBuilder::new(format!(
+            "{} match(es) · buttons shown for the first {}",
+            matches.len(),
+            matches.len().min(MAX_FIND_BUTTON_ROWS)
+        )))
+        .build()
+}
+
+fn find_action_row(idx: usize, track: &TrackData) -> Component {
+    let key = track_key(track);
+    let button = |label: String, action: &str, style: ButtonStyle| {
+        Component::Button(Button {
+            label: Some(label),
+            custom_id: Some(format!("queue_find:{}:{}:{}", action, idx, key)),
+            style,
+            emoji: None,
+            disabled: false,
+            url: None,
+            sku_id: None,
+        })
+    };
+
+    Component::ActionRow(ActionRow {
+        components: vec![
+            button(format!("▶️ Jump to #{}", idx + 1), "jump", ButtonStyle::Secondary),
+            button(format!("⏭️ Play #{} next", idx + 1), "next", ButtonStyle::Secondary),
+            button(format!("🗑️ Remove #{}", idx + 1), "remove", ButtonStyle::Danger),
+        ],
+    })
+}
+
+/// Renders one page of the queue. `start_times` holds the expected start of
+/// each track as a unix timestamp, see [`queue_start_times`].
+pub fn build_queue_embed(
+    tracks: &VecDeque<TrackInQueue>,
+    start_times: &[Option<i64>],
+    page: usize,
+) -> Embed {
     let mut embed = EmbedBuilder::new()
         .title("🎶 Current Queue")
         .color(0x1DB954);
 
-    if queue.get_count().await? == 0 {
+    if tracks.is_empty() {
         embed = embed.description("The queue is currently empty.");
     } else {
-        let queue_list: Vec<String> = queue
-            .get_queue()
-            .await?
+        let page_count = tracks.len().div_ceil(QUEUE_PAGE_SIZE);
+        let page = page.min(page_count - 1);
+        let queue_list: Vec<String> = tracks
             .iter()
-            .map(|track| format!("{} - {}", track.track.info.title, track.track.info.author))
+            .enumerate()
+            .skip(page * QUEUE_PAGE_SIZE)
+            .take(QUEUE_PAGE_SIZE)
+            .map(|(idx, track)| {
+                let eta = match start_times.get(idx).copied().flatten() {
+                    Some(timestamp) => format!("<t:{}:R>", timestamp),
+                    None => "ETA unknown".to_string(),
+                };
+                format!(
+                    "`{}.` {} - {} `[{}]` · {}",
+                    idx + 1,
+                    track.track.info.title,
+                    track.track.info.author,
+                    format_length(&track.track),
+                    eta
+                )
+            })
             .collect();
-        embed = embed.description(format!("{}\n", queue_list.join("\n")));
+        // Livestreams have no meaningful length, so they are counted separately.
+        let stream_count = tracks.iter().filter(|t| t.track.info.is_stream).count();
+        let total_ms: u64 = tracks.iter().filter_map(queued_length_ms).sum();
+        let mut footer = format!(
+            "Page {}/{} · {} tracks · {} total",
+            page + 1,
+            page_count,
+            tracks.len(),
+            humantime::format_duration(Duration::from_secs(total_ms / 1000))
+        );
+        if stream_count > 0 {
+            footer.push_str(&format!(" (+{} livestreams)", stream_count));
+        }
+        embed = embed
+            .description(format!("{}\n", queue_list.join("\n")))
+            .footer(EmbedFooterBuilder::new(footer));
     }
 
-    Ok(embed.build())
+    embed.build()
 }
@@ -0,0 +1,488 @@
+use super::connect;
+use crate::command_handler::{
+    Command, CommandContext, CommandResponseBuilder, GlobalState, StateExt,
+};
+use crate::utils::quiz::{EXCERPT_MS, QuizGame, QuizHit, QuizRound, excerpt_start};
+use crate::utils::track::{QUIZ_FLAG, flag_track};
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+use lavalink_rs::model::track::TrackData;
+use lavalink_rs::prelude::{SearchEngines, TrackInQueue, TrackLoadData};
+use std::hash::{BuildHasher, RandomState};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Notify;
+use twilight_interactions::command::{CommandModel, CreateCommand};
+use twilight_model::channel::Message;
+use twilight_model::channel::message::Embed;
+use twilight_model::id::{
+    Id,
+    marker::{GuildMarker, UserMarker},
+};
+use twilight_util::builder::embed::{EmbedBuilder, EmbedFooterBuilder};
+
+const DEFAULT_ROUNDS: usize = 10;
+const MAX_ROUNDS: usize = 30;
+/// Tracks shorter than this don't leave room for a proper excerpt.
+const MIN_TRACK_MS: u64 = 30_000;
+/// How long the answer stays up before the next round starts.
+const ROUND_BREAK: Duration = Duration::from_secs(5);
+
+#[derive(CommandModel, CreateCommand)]
+#[command(name = "quiz", desc = "Guess the song from a short excerpt.")]
+pub enum QuizCommand {
+    #[command(name = "start")]
+    Start(QuizStartCommand),
+    #[command(name = "stop")]
+    Stop(QuizStopCommand),
+}
+
+#[allow(unused)]
+#[derive(CommandModel, CreateCommand)]
+#[command(name = "start", desc = "Start a quiz in this channel.")]
+pub struct QuizStartCommand {
+    #[command(
+        desc = "How many rounds to play (default 10)",
+        min_value = 1,
+        max_value = 30
+    )]
+    rounds: Option<i64>,
+    #[command(desc = "One of your playlists to take the songs from")]
+    playlist: Option<String>,
+    #[command(desc = "A genre to search for songs, e.g. disco")]
+    genre: Option<String>,
+}
+
+#[derive(CommandModel, CreateCommand)]
+#[command(name = "stop", desc = "End the quiz and show the final scores.")]
+pub struct QuizStopCommand;
+
+/// Where the quiz gets its songs.
+enum QuizSource {
+    Playlist(String),
+    Genre(String),
+}
+
+#[async_trait]
+impl Command<GlobalState> for QuizCommand {
+    async fn execute<'ctx>(state: GlobalState, mut cmd_ctx: CommandContext<'ctx>) -> Result<()> {
+        let guild_id = cmd_ctx
+            .guild_id()
+            .ok_or_else(|| anyhow!("This command must be used in a guild."))?;
+        let author = cmd_ctx
+            .author()
+            .ok_or_else(|| anyhow!("Interaction is missing author information."))?
+            .clone();
+
+        let subcommand = cmd_ctx.subcommand().map(str::to_owned);
+        let response = match subcommand.as_deref() {
+            Some("start") => {
+                let channel_id = cmd_ctx
+                    .channel_id()
+                    .ok_or_else(|| anyhow!("This command must be used in a channel."))?;
+                let (rounds, source) = quiz_args(&mut cmd_ctx)?;
+                let voice_state =
+                    state
+                        .cache
+                        .voice_state(author.id, guild_id)
+                        .ok_or_else(|| {
+                            anyhow!("You must be in a voice channel to use this command.")
+                        })?;
+                if state.quiz_games.lock().await.contains_key(&guild_id) {
+                    return Err(anyhow!(
+                        "A quiz is already running. End it with `/quiz stop`."
+                    ));
+                }
+
+                let tracks = quiz_tracks(&state, guild_id, author.id, &source, rounds).await?;
+                connect(
+                    state.clone(),
+                    voice_state.channel_id(),
+                    Some(channel_id),
+                    guild_id,
+                )
+                .await?;
+                let player = state
+                    .lavalink()
+                    .get_player_context(guild_id)
+                    .ok_or_else(|| {
+                        anyhow!("Player context not found. Is the bot in a voice channel?")
+                    })?;
+                let busy = player.get_player().await?.track.is_some()
+                    || !player.get_queue().get_queue().await?.is_empty();
+                if busy {
+                    return Err(anyhow!(
+                        "Something is playing. Stop it with `/stop` before starting a quiz."
+                    ));
+                }
+
+                let rounds = tracks.len();
+                {
+                    let mut games = state.quiz_games.lock().await;
+                    if games.contains_key(&guild_id) {
+                        return Err(anyhow!(
+                            "A quiz is already running. End it with `/quiz stop`."
+                        ));
+                    }
+                    games.insert(guild_id, QuizGame::new(channel_id, rounds));
+                }
+                let game_state = state.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = run_quiz(&game_state, guild_id, tracks).await {
+                        tracing::warn!(error = ?e, "Quiz ended with an error");
+                        game_state.quiz_games.lock().await.remove(&guild_id);
+                    }
+                });
+
+                CommandResponseBuilder::new()
+                    .content(format!(
+                        "🎲 Quiz time! {} rounds of {} seconds. Type the title or the artist \
+                         in this channel: the title is worth 2 points, the artist 1.",
+                        rounds,
+                        EXCERPT_MS / 1000
+                    ))
+                    .build()
+            }
+            Some("stop") => {
+                let game = state
+                    .quiz_games
+                    .lock()
+                    .await
+                    .remove(&guild_id)
+                    .ok_or_else(|| anyhow!("No quiz is running."))?;
+                game.wake.notify_one();
+                if let Some(player) = state.lavalink().get_player_context(guild_id) {
+                    player.stop_now().await?;
+                }
+                CommandResponseBuilder::new()
+                    .content("🏁 Quiz stopped.")
+                    .embed(scoreboard_embed(&game, true))
+                    .build()
+            }
+            Some(other) => return Err(anyhow!("Unknown quiz subcommand: {}", other)),
+            None => return Err(anyhow!("Use `quiz start|stop`.")),
+        };
+
+        cmd_ctx.reply(response).await?;
+
+        Ok(())
+    }
+}
+
+/// Slash commands take `rounds`, `playlist` and `genre`. Prefix commands
+/// take an optional round count followed by `playlist <name>`,
+/// `genre <name>` or just a genre.
+fn quiz_args(cmd_ctx: &mut CommandContext<'_>) -> Result<(usize, QuizSource)> {
+    let (rounds, source) = if matches!(cmd_ctx, CommandContext::Prefix(_)) {
+        let rest = cmd_ctx.get_remainder_arg("source").unwrap_or_default();
+        let (rounds, rest) = match rest.split_once(' ') {
+            Some((first, rest)) if first.parse::<usize>().is_ok() => (first.parse().ok(), rest),
+            _ => match rest.parse::<usize>() {
+                Ok(rounds) => (Some(rounds), ""),
+                Err(_) => (None, rest.as_str()),
+            },
+        };
+        let rest = rest.trim();
+        let source = if let Some(name) = rest.strip_prefix("playlist ") {
+            Some(QuizSource::Playlist(name.trim().to_string()))
+        } else if let Some(genre) = rest.strip_prefix("genre ") {
+            Some(QuizSource::Genre(genre.trim().to_string()))
+        } else {
+            (!rest.is_empty()).then(|| QuizSource::Genre(rest.to_string()))
+        };
+        (rounds, source)
+    } else {
+        let rounds = cmd_ctx
+            .get_arg::<i64>("rounds")
+            .and_then(|rounds| usize::try_from(rounds).ok());
+        let playlist = cmd_ctx.get_arg::<String>("playlist");
+        let source = match (playlist, cmd_ctx.get_arg::<String>("genre")) {
+            (Some(_), Some(_)) => {
+                return Err(anyhow!("Pick either a playlist or a genre, not both."));
+            }
+            (Some(name), None) => Some(QuizSource::Playlist(name)),
+            (None, Some(genre)) => Some(QuizSource::Genre(genre)),
+            (None, None) => None,
+        };
+        (rounds, source)
+    };
+
+    let source =
+        source.ok_or_else(|| anyhow!("Pick a playlist or a genre to take the songs from."))?;
+    Ok((
+        rounds.unwrap_or(DEFAULT_ROUNDS).clamp(1, MAX_ROUNDS),
+        source,
+    ))
+}
+
+/// Up to `rounds` random songs from the source that are long enough for an
+/// excerpt.
+async fn quiz_tracks(
+    state: &GlobalState,
+    guild_id: Id<GuildMarker>,
+    user_id: Id<UserMarker>,
+    source: &QuizSource,
+    rounds: usize,
+) -> Result<Vec<TrackData>> {
+    let mut tracks = match source {
+        QuizSource::Playlist(name) => {
+            let playlist = state
+                .storage
+                .playlist(user_id, name)?
+                .ok_or_else(|| anyhow!("You have no playlist called `{}`.", name))?;
+            let encoded: Vec<String> = state
+                .storage
+                .playlist_tracks(playlist.id)?
+                .into_iter()
+                .map(|track| track.encoded)
+                .collect();
+            state
+                .lavalink()
+                .decode_tracks(guild_id, &encoded)
+                .await
+                .map_err(|e| anyhow!("Could not load the saved tracks: {}", e))?
+        }
+        QuizSource::Genre(genre) => {
+            let query = SearchEngines::Spotify.to_query(&format!("genre:{}", genre))?;
+            match state.lavalink().load_tracks(guild_id, &query).await?.data {
+                Some(TrackLoadData::Search(results)) => results,
+                Some(TrackLoadData::Playlist(playlist)) => playlist.tracks,
+                Some(TrackLoadData::Error(e)) => {
+                    return Err(anyhow!("Error searching for {}: {}", genre, e.message));
+                }
+                _ => Vec::new(),
+            }
+        }
+    };
+
+    tracks.retain(|track| !track.info.is_stream && track.info.length >= MIN_TRACK_MS);
+    let random = RandomState::new();
+    tracks.sort_by_cached_key(|track| random.hash_one(&track.encoded));
+    tracks.truncate(rounds);
+    if tracks.is_empty() {
+        return Err(anyhow!("Found no songs to quiz on there."));
+    }
+    Ok(tracks)
+}
+
+/// Plays the rounds one after the other until they run out or the quiz is
+/// stopped, which removes the game from the state.
+async fn run_quiz(
+    state: &GlobalState,
+    guild_id: Id<GuildMarker>,
+    tracks: Vec<TrackData>,
+) -> Result<()> {
+    let rounds = tracks.len();
+    for (idx, track) in tracks.into_iter().enumerate() {
+        let (channel_id, wake) = {
+            let mut games = state.quiz_games.lock().await;
+            let Some(game) = games.get_mut(&guild_id) else {
+                return Ok(());
+            };
+            game.round = idx + 1;
+            game.current = Some(QuizRound::new(
+                track.info.title.clone(),
+                track.info.author.clone(),
+            ));
+            // A fresh one per round, so a late wake-up can't cut the next short.
+            game.wake = Arc::new(Notify::new());
+            (game.channel_id, game.wake.clone())
+        };
+
+        play_excerpt(state, guild_id, track).await?;
+        state
+            .http
+            .create_message(channel_id)
+            .content(&format!(
+                "🎵 **Round {}/{}**: what's playing?",
+                idx + 1,
+                rounds
+            ))
+            .await?;
+        // Either the excerpt runs out or someone gets both title and artist.
+        let _ = tokio::time::timeout(Duration::from_millis(EXCERPT_MS), wake.notified()).await;
+
+        let round = {
+            let mut games = state.quiz_games.lock().await;
+            let Some(round) = games
+                .get_mut(&guild_id)
+                .and_then(|game| game.current.take())
+            else {
+                return Ok(());
+            };
+            round
+        };
+        state
+            .http
+            .create_message(channel_id)
+            .content(&reveal_message(&round))
+            .await?;
+        if idx + 1 < rounds {
+            tokio::time::sleep(ROUND_BREAK).await;
+        }
+    }
+
+    let Some(game) = state.quiz_games.lock().await.remove(&guild_id) else {
+        return Ok(());
+    };
+    if let Some(player) = state.lavalink().get_player_context(guild_id) {
+        player.stop_now().await?;
+    }
+    state
+        .http
+        .create_message(game.channel_id)
+        .content("🏁 That's the quiz!")
+        .embeds(&[scoreboard_embed(&game, true)])
+        .await?;
+
+    Ok(())
+}
+
+/// Plays a random stretch of `track`, replacing whatever excerpt is playing.
+async fn play_excerpt(
+    state: &GlobalState,
+    guild_id: Id<GuildMarker>,
+    mut track: TrackData,
+) -> Result<()> {
+    let player = state
+        .lavalink()
+        .get_player_context(guild_id)
+        .ok_or_else(|| anyhow!("Player context not found. Is the bot in a voice channel?"))?;
+
+    let start = excerpt_start(
+        track.info.length,
+        RandomState::new().hash_one(&track.encoded),
+    );
+    flag_track(&mut track, QUIZ_FLAG);
+    player.get_queue().push_to_front(TrackInQueue {
+        start_time: Some(Duration::from_millis(start)),
+        end_time: Some(Duration::from_millis(start + EXCERPT_MS)),
+        ..TrackInQueue::from(track)
+    })?;
+    player.skip()?;
+
+    Ok(())
+}
+
+/// Treats `message` as a guess if a quiz is running in its channel. Returns
+/// whether it was, so the caller knows not to run it as a prefix command.
+pub async fn quiz_guess(state: &GlobalState, message: &Message) -> Result<bool> {
+    let Some(guild_id) = message.guild_id else {
+        return Ok(false);
+    };
+    let (hit, channel_id, scoreboard) = {
+        let mut games = state.quiz_games.lock().await;
+        let Some(game) = games
+            .get_mut(&guild_id)
+            .filter(|game| game.channel_id == message.channel_id)
+        else {
+            return Ok(false);
+        };
+        // `!quiz stop` still has to get through.
+        let is_quiz_command =
+            crate::prefix_parser::parse(&message.content, &state.config.configured_prefix)
+                .is_some_and(|parsed| parsed.command == "quiz");
+        if is_quiz_command {
+            return Ok(false);
+        }
+
+        let Some(hit) = game.guess(message.author.id, &message.content) else {
+            return Ok(true);
+        };
+        if game.current.as_ref().is_some_and(QuizRound::is_solved) {
+            game.wake.notify_one();
+        }
+        (
+            hit,
+            game.channel_id,
+            (game.scoreboard_id, scoreboard_embed(game, false)),
+        )
+    };
+
+    let what = match hit {
+        QuizHit::Title => "the title",
+        QuizHit::Artist => "the artist",
+        QuizHit::Both => "the title and the artist",
+    };
+    state
+        .http
+        .create_message(channel_id)
+        .reply(message.id)
+        .content(&format!(
+            "✅ <@{}> got {}! +{}",
+            message.author.id,
+            what,
+            hit.points()
+        ))
+        .await?;
+
+    // One scoreboard per game, edited as the points come in.
+    let (scoreboard_id, embed) = scoreboard;
+    match scoreboard_id {
+        Some(scoreboard_id) => {
+            state
+                .http
+                .update_message(channel_id, scoreboard_id)
+                .embeds(Some(&[embed]))
+                .await?;
+        }
+        None => {
+            let scoreboard = state
+                .http
+                .create_message(channel_id)
+                .embeds(&[embed])
+                .await?
+                .model()
+                .await?;
+            if let Some(game) = state.quiz_games.lock().await.get_mut(&guild_id) {
+                game.scoreboard_id = Some(scoreboard.id);
+            }
+        }
+    }
+
+    Ok(true)
+}
+
+fn reveal_message(round: &QuizRound) -> String {
+    let credit = |by: Option<Id<UserMarker>>| match by {
+        Some(user_id) => format!("guessed by <@{}>", user_id),
+        None => "nobody got it".to_string(),
+    };
+    format!(
+        "⏱️ It was **{}** ({}) by **{}** ({}).",
+        round.title,
+        credit(round.title_by),
+        round.author,
+        credit(round.artist_by)
+    )
+}
+
+fn scoreboard_embed(game: &QuizGame, is_final: bool) -> Embed {
+    let standings = game.standings();
+    let description = if standings.is_empty() {
+        "No points yet.".to_string()
+    } else {
+        standings
+            .iter()
+            .enumerate()
+            .map(|(idx, (user_id, points))| {
+                format!("`{}.` <@{}> · {} pts", idx + 1, user_id, points)
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+    let footer = if is_final {
+        format!(
+            "Final scores after {} of {} rounds",
+            game.round, game.rounds
+        )
+    } else {
+        format!("Round {} of {}", game.round, game.rounds)
+    };
+    EmbedBuilder::new()
+        .title("🏆 Quiz scoreboard")
+        .description(description)
+        .color(0x1DB954)
+        .footer(EmbedFooterBuilder::new(footer))
+        .build()
+}
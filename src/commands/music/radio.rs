@@ -0,0 +1,218 @@
+use super::{Enqueued, enqueue};
+use crate::command_handler::{
+    Command, CommandContext, CommandResponse, CommandResponseBuilder, GlobalState, StateExt,
+    autocomplete_response, focused_option,
+};
+use crate::utils::radio::{RadioSession, Station};
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+use lavalink_rs::prelude::{TrackInQueue, TrackLoadData};
+use twilight_interactions::command::{CommandModel, CreateCommand};
+use twilight_model::application::interaction::{Interaction, application_command::CommandData};
+use twilight_model::channel::message::{
+    Component,
+    component::{ActionRow, SelectMenu, SelectMenuOption, SelectMenuType},
+};
+use twilight_model::guild::Permissions;
+use twilight_model::id::{
+    Id,
+    marker::{ChannelMarker, GuildMarker, UserMarker},
+};
+
+/// Discord allows five select menus per message.
+const MAX_GENRE_MENUS: usize = 5;
+/// And 25 options per select menu.
+const MAX_MENU_OPTIONS: usize = 25;
+
+#[allow(unused)]
+#[derive(CommandModel, CreateCommand)]
+#[command(name = "radio", desc = "Tune in to an internet radio station.")]
+pub struct RadioCommand {
+    #[command(
+        desc = "The station to play; leave empty to browse",
+        autocomplete = true
+    )]
+    station: Option<String>,
+}
+
+#[async_trait]
+impl Command<GlobalState> for RadioCommand {
+    async fn execute<'ctx>(state: GlobalState, mut cmd_ctx: CommandContext<'ctx>) -> Result<()> {
+        let guild_id = cmd_ctx
+            .guild_id()
+            .ok_or_else(|| anyhow!("This command must be used in a guild."))?;
+        let author = cmd_ctx
+            .author()
+            .ok_or_else(|| anyhow!("Interaction is missing author information."))?
+            .clone();
+
+        let Some(query) = cmd_ctx.get_remainder_arg("station") else {
+            cmd_ctx.reply(station_browser(&state)?).await?;
+            return Ok(());
+        };
+
+        let station = state
+            .radio_stations
+            .find(&query)
+            .or_else(|| state.radio_stations.search(&query).into_iter().next())
+            .cloned()
+            .ok_or_else(|| anyhow!("No station matches `{}`.", query))?;
+
+        if state.cache.voice_state(author.id, guild_id).is_none() {
+            return Err(anyhow!(
+                "You must be in a voice channel to use this command."
+            ));
+        }
+
+        let content = play_station(
+            &state,
+            guild_id,
+            cmd_ctx.channel_id(),
+            (author.id, cmd_ctx.member_permissions()),
+            station,
+        )
+        .await?;
+        cmd_ctx
+            .reply(CommandResponseBuilder::new().content(content).build())
+            .await?;
+
+        Ok(())
+    }
+
+    async fn autocomplete(
+        state: GlobalState,
+        interaction: Interaction,
+        data: CommandData,
+    ) -> Result<()> {
+        let query = focused_option(&data).map_or("", |(_, value)| value);
+        let choices = state
+            .radio_stations
+            .search(query)
+            .into_iter()
+            .map(|station| {
+                (
+                    format!("{} · {} · {}", station.name, station.genre, station.country),
+                    station.id.clone(),
+                )
+            })
+            .collect();
+
+        state
+            .http
+            .interaction(interaction.application_id)
+            .create_response(
+                interaction.id,
+                &interaction.token,
+                &autocomplete_response(choices),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+/// Switches the guild's player over to `station`, interrupting whatever is
+/// playing, and starts following its ICY titles.
+pub async fn play_station(
+    state: &GlobalState,
+    guild_id: Id<GuildMarker>,
+    text_channel_id: Option<Id<ChannelMarker>>,
+    (requester, permissions): (Id<UserMarker>, Option<Permissions>),
+    station: Station,
+) -> Result<String> {
+    let loaded = state.lavalink().load_tracks(guild_id, &station.url).await?;
+    let mut track = match loaded.data {
+        Some(TrackLoadData::Track(track)) => track,
+        Some(TrackLoadData::Error(e)) => {
+            return Err(anyhow!(
+                "Error tuning in to {}: {}",
+                station.name,
+                e.message
+            ));
+        }
+        _ => return Err(anyhow!("{} is not reachable right now.", station.name)),
+    };
+    track.user_data = Some(serde_json::json!({
+        "station_id": station.id,
+        "station_name": station.name,
+    }));
+
+    let player = state.lavalink().get_player_context(guild_id);
+    let was_playing = match &player {
+        Some(player) => player.get_player().await?.track.is_some(),
+        None => false,
+    };
+    let tracks = vec![TrackInQueue::from(track)];
+    if let Enqueued::PendingApproval(reply) = enqueue(
+        state,
+        guild_id,
+        tracks,
+        requester,
+        permissions,
+        text_channel_id,
+        true,
+    )
+    .await?
+    {
+        return Ok(reply);
+    }
+    if was_playing && let Some(player) = player {
+        player.skip()?;
+    }
+
+    let content = format!("📻 Tuning in to **{}** ({})", station.name, station.genre);
+    state.radio_sessions.lock().await.insert(
+        guild_id,
+        RadioSession {
+            station,
+            last_title: None,
+            last_checked: None,
+        },
+    );
+
+    Ok(content)
+}
+
+/// One select menu per genre, for browsing when no station was named.
+fn station_browser(state: &GlobalState) -> Result<CommandResponse> {
+    let genres = state.radio_stations.by_genre();
+    if genres.is_empty() {
+        return Err(anyhow!("No radio stations are configured."));
+    }
+
+    let mut response = CommandResponseBuilder::new().content(if genres.len() > MAX_GENRE_MENUS {
+        "📻 Pick a station, or search all of them with `/radio station:`".to_string()
+    } else {
+        "📻 Pick a station".to_string()
+    });
+
+    for (genre, stations) in genres.into_iter().take(MAX_GENRE_MENUS) {
+        let options = stations
+            .into_iter()
+            .take(MAX_MENU_OPTIONS)
+            .map(|station| SelectMenuOption {
+                default: false,
+                description: Some(station.country.clone()),
+                emoji: None,
+                label: station.name.clone(),
+                value: station.id.clone(),
+            })
+            .collect();
+
+        response = response.component(Component::ActionRow(ActionRow {
+            components: vec![Component::SelectMenu(SelectMenu {
+                channel_types: None,
+                custom_id: format!("radio:{}", genre),
+                default_values: None,
+                disabled: false,
+                kind: SelectMenuType::Text,
+                max_values: Some(1),
+                min_values: Some(1),
+                options: Some(options),
+                placeholder: Some(genre.to_string()),
+            })],
+        }));
+    }
+
+    Ok(response.build())
+}
@@ -0,0 +1,202 @@
+//! The recap posted when the bot leaves voice. A session is everything the
+//! play log holds for the guild between joining and leaving.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use super::playlist::{MAX_PLAYLIST_TRACKS, MAX_PLAYLISTS};
+use crate::command_handler::CommandResponseBuilder;
+use crate::state::{PlayerData, State};
+use crate::storage::{Play, PlayFilter};
+use crate::utils::voice::listeners;
+use anyhow::{Result, anyhow};
+use chrono::DateTime;
+use twilight_model::channel::message::{
+    Component,
+    component::{ActionRow, Button, ButtonStyle},
+};
+use twilight_model::id::{
+    Id,
+    marker::{GuildMarker, UserMarker},
+};
+use twilight_util::builder::embed::{EmbedBuilder, EmbedFieldBuilder};
+
+/// The most plays the tracklist file lists.
+const MAX_TRACKLIST_PLAYS: usize = 5000;
+const RECAP_TOP_REQUESTERS: usize = 3;
+/// How long the bot stays with nothing playing, or nobody listening.
+const IDLE_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+const IDLE_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Leaves voice, recap and all, once the session started at `started_at` has
+/// been idle for [`IDLE_TIMEOUT`]. Stops watching when the session ends.
+pub(super) fn leave_when_idle(state: Arc<State>, guild_id: Id<GuildMarker>, started_at: i64) {
+    tokio::spawn(async move {
+        let mut idle_since = None;
+        loop {
+            tokio::time::sleep(IDLE_CHECK_INTERVAL).await;
+            let Some(player) = state.lavalink.get_player_context(guild_id) else {
+                return;
+            };
+            // A later session has a watcher of its own.
+            if player.data::<PlayerData>().map(|data| data.started_at).ok() != Some(started_at) {
+                return;
+            }
+            let playing = match player.get_player().await {
+                Ok(player) => player.track.is_some() && !player.paused,
+                Err(e) => {
+                    tracing::warn!(error = ?e, "Failed to check whether the player is idle");
+                    continue;
+                }
+            };
+            if playing && !listeners(&state.cache, guild_id).is_empty() {
+                idle_since = None;
+                continue;
+            }
+            if idle_since.get_or_insert_with(Instant::now).elapsed() >= IDLE_TIMEOUT {
+                if let Err(e) = super::leave(state.clone(), guild_id).await {
+                    tracing::error!(error = ?e, "Error leaving an idle voice channel");
+                }
+                return;
+            }
+        }
+    });
+}
+
+/// Posts the recap of the session that just ended, unless nothing played.
+pub(super) async fn post_recap(
+    state: &State,
+    guild_id: Id<GuildMarker>,
+    session: &PlayerData,
+) -> Result<()> {
+    let until = chrono::Utc::now().timestamp_millis();
+    let filter = PlayFilter {
+        guild_id,
+        requester_id: None,
+        since: Some(session.started_at),
+    };
+    let stats = state.storage.play_stats(&filter, RECAP_TOP_REQUESTERS)?;
+    if stats.plays == 0 {
+        return Ok(());
+    }
+    let mut plays = state.storage.plays(&filter, MAX_TRACKLIST_PLAYS)?;
+    plays.reverse();
+
+    let duration = Duration::from_secs(
+        u64::try_from((until - session.started_at) / 60_000).unwrap_or_default() * 60,
+    );
+    let listened = Duration::from_secs(stats.listened_ms / 60_000 * 60);
+    let requesters = if stats.top_requesters.is_empty() {
+        "Nobody in particular".to_string()
+    } else {
+        stats
+            .top_requesters
+            .iter()
+            .map(|(user_id, plays)| format!("<@{}> · {} tracks", user_id, plays))
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+    let embed = EmbedBuilder::new()
+        .title("👋 Session recap")
+        .description("Thanks for listening! The full tracklist is attached.")
+        .color(0x1DB954)
+        .field(
+            EmbedFieldBuilder::new("Duration", humantime::format_duration(duration).to_string())
+                .inline(),
+        )
+        .field(EmbedFieldBuilder::new("Tracks played", stats.plays.to_string()).inline())
+        .field(
+            EmbedFieldBuilder::new(
+                "Listening time",
+                humantime::format_duration(listened).to_string(),
+            )
+            .inline(),
+        )
+        .field(EmbedFieldBuilder::new("Top requesters", requesters))
+        .build();
+
+    let component = Component::ActionRow(ActionRow {
+        components: vec![Component::Button(Button {
+            label: Some("💾 Save as playlist".to_string()),
+            custom_id: Some(format!("session_save:{}:{}", session.started_at, until)),
+            style: ButtonStyle::Secondary,
+            emoji: None,
+            disabled: false,
+            url: None,
+            sku_id: None,
+        })],
+    });
+    let filename = format!(
+        "session-{}.txt",
+        DateTime::from_timestamp_millis(session.started_at)
+            .unwrap_or_default()
+            .format("%Y%m%d-%H%M")
+    );
+    let response = CommandResponseBuilder::new()
+        .embed(embed)
+        .component(component)
+        .attachment(filename, tracklist(&plays).into_bytes())
+        .build();
+
+    state
+        .http
+        .create_message(session.text_channel_id)
+        .embeds(&response.embeds)
+        .components(&response.components)
+        .attachments(&response.attachments)
+        .await?;
+
+    Ok(())
+}
+
+/// Saves the session between `from` and `until`, as found on the recap's
+/// button, as a playlist for `user_id`. Returns the message to show them.
+pub async fn save_session_playlist(
+    state: &State,
+    user_id: Id<UserMarker>,
+    guild_id: Id<GuildMarker>,
+    (from, until): (i64, i64),
+) -> Result<String> {
+    if state.storage.playlists(user_id)?.len() >= MAX_PLAYLISTS {
+        return Err(anyhow!("You can have at most {} playlists.", MAX_PLAYLISTS));
+    }
+    let name = format!(
+        "Session {}",
+        DateTime::from_timestamp_millis(from)
+            .unwrap_or_default()
+            .format("%Y-%m-%d")
+    );
+    let playlist = state
+        .storage
+        .save_plays_as_playlist(user_id, &name, guild_id, (from, until), MAX_PLAYLIST_TRACKS)?
+        .ok_or_else(|| anyhow!("None of this session's tracks can be saved."))?;
+    Ok(format!(
+        "💾 Saved {} tracks to your playlist **{}**. Play it with `/playlist play`.",
+        playlist.track_count, playlist.name
+    ))
+}
+
+/// One line per play: when it started (UTC), what it was and where to find it.
+fn tracklist(plays: &[Play]) -> String {
+    plays
+        .iter()
+        .enumerate()
+        .map(|(idx, play)| {
+            let started = DateTime::from_timestamp_millis(play.started_at)
+                .unwrap_or_default()
+                .format("%H:%M");
+            let mut line = format!(
+                "{:>3}. {}  {} - {}",
+                idx + 1,
+                started,
+                play.title,
+                play.author
+            );
+            if let Some(uri) = &play.uri {
+                line.push_str(&format!("  <{}>", uri));
+            }
+            line.push('\n');
+            line
+        })
+        .collect()
+}
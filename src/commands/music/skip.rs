@@ -2,6 +2,7 @@ use super::check_voice_state;
 use crate::command_handler::{
     Command, CommandContext, CommandResponseBuilder, GlobalState, StateExt,
 };
+use crate::utils::queue_history::snapshot_queue;
 use anyhow::Result;
 use async_trait::async_trait;
 use twilight_interactions::command::{CommandModel, CreateCommand};
@@ -32,6 +33,7 @@ impl Command<GlobalState> for SkipCommand {
             .track
             .ok_or_else(|| anyhow::anyhow!("No track is currently playing."))?;
 
+        snapshot_queue(&state, guild_id).await?;
         player.skip()?;
 
         let response = CommandResponseBuilder::new()
@@ -0,0 +1,316 @@
+use crate::command_handler::{Command, CommandContext, CommandResponseBuilder, GlobalState};
+use crate::prefix_parser::parse_mention;
+use crate::storage::{Play, PlayFilter, PlayStats};
+use crate::utils::csv::csv_line;
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+use twilight_interactions::command::{CommandModel, CommandOption, CreateCommand, CreateOption};
+use twilight_model::channel::message::Embed;
+use twilight_model::id::{Id, marker::UserMarker};
+use twilight_util::builder::embed::{EmbedBuilder, EmbedFieldBuilder};
+
+/// How many entries each leaderboard shows.
+const LEADERBOARD_SIZE: usize = 5;
+/// The most plays one CSV export holds, newest first.
+const MAX_EXPORTED_PLAYS: usize = 50_000;
+const MAX_TITLE_LEN: usize = 60;
+const DAY_MS: i64 = 24 * 60 * 60 * 1000;
+
+#[derive(CommandModel, CreateCommand)]
+#[command(name = "stats", desc = "Listening statistics from the play history.")]
+pub enum StatsCommand {
+    #[command(name = "guild")]
+    Guild(StatsGuildCommand),
+    #[command(name = "user")]
+    User(StatsUserCommand),
+}
+
+#[allow(unused)]
+#[derive(CommandModel, CreateCommand)]
+#[command(name = "guild", desc = "What this server listens to.")]
+pub struct StatsGuildCommand {
+    #[command(desc = "The time to cover; all time if left out")]
+    window: Option<StatsWindow>,
+    #[command(desc = "Attach every play in the window as a CSV file")]
+    csv: Option<bool>,
+}
+
+#[allow(unused)]
+#[derive(CommandModel, CreateCommand)]
+#[command(name = "user", desc = "What someone has requested in this server.")]
+pub struct StatsUserCommand {
+    #[command(desc = "Whose stats to show; yours if left out")]
+    user: Option<Id<UserMarker>>,
+    #[command(desc = "The time to cover; all time if left out")]
+    window: Option<StatsWindow>,
+    #[command(desc = "Attach every play in the window as a CSV file")]
+    csv: Option<bool>,
+}
+
+#[derive(CommandOption, CreateOption, Clone, Copy, PartialEq)]
+pub enum StatsWindow {
+    #[option(name = "Last 7 days", value = "7d")]
+    Week,
+    #[option(name = "Last 30 days", value = "30d")]
+    Month,
+    #[option(name = "All time", value = "all")]
+    AllTime,
+}
+
+impl StatsWindow {
+    fn from_value(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "7d" => Some(Self::Week),
+            "30d" => Some(Self::Month),
+            "all" => Some(Self::AllTime),
+            _ => None,
+        }
+    }
+
+    fn value(self) -> &'static str {
+        match self {
+            Self::Week => "7d",
+            Self::Month => "30d",
+            Self::AllTime => "all",
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Week => "Last 7 days",
+            Self::Month => "Last 30 days",
+            Self::AllTime => "All time",
+        }
+    }
+
+    /// Unix timestamp in milliseconds the window starts at.
+    fn since(self, now: i64) -> Option<i64> {
+        match self {
+            Self::Week => Some(now - 7 * DAY_MS),
+            Self::Month => Some(now - 30 * DAY_MS),
+            Self::AllTime => None,
+        }
+    }
+}
+
+struct StatsArgs {
+    user: Option<Id<UserMarker>>,
+    window: StatsWindow,
+    csv: bool,
+}
+
+#[async_trait]
+impl Command<GlobalState> for StatsCommand {
+    async fn execute<'ctx>(state: GlobalState, mut cmd_ctx: CommandContext<'ctx>) -> Result<()> {
+        let author = cmd_ctx
+            .author()
+            .ok_or_else(|| anyhow!("Interaction is missing author information."))?
+            .clone();
+        let guild_id = cmd_ctx
+            .guild_id()
+            .ok_or_else(|| anyhow!("This command must be used in a guild."))?;
+
+        let subcommand = cmd_ctx.subcommand().map(str::to_owned);
+        let by_user = match subcommand.as_deref() {
+            Some("guild") => false,
+            Some("user") => true,
+            Some(other) => return Err(anyhow!("Unknown stats subcommand: {}", other)),
+            None => return Err(anyhow!("Use `stats guild` or `stats user [@user]`.")),
+        };
+        let args = stats_args(&mut cmd_ctx)?;
+        let requester_id = by_user.then(|| args.user.unwrap_or(author.id));
+
+        let filter = PlayFilter {
+            guild_id,
+            requester_id,
+            since: args.window.since(chrono::Utc::now().timestamp_millis()),
+        };
+        let stats = state.storage.play_stats(&filter, LEADERBOARD_SIZE)?;
+        let subject = match requester_id {
+            Some(user_id) => format!("Tracks requested by <@{}>", user_id),
+            None => format!(
+                "Everything played in **{}**",
+                state.cache.guild(guild_id).map_or_else(
+                    || "this server".to_string(),
+                    |guild| guild.name().to_string()
+                )
+            ),
+        };
+
+        let embed = build_stats_embed(&stats, &subject, args.window, by_user);
+        let mut response = CommandResponseBuilder::new().embed(embed);
+        if args.csv {
+            let plays = state.storage.plays(&filter, MAX_EXPORTED_PLAYS)?;
+            let filename = match requester_id {
+                Some(user_id) => format!("stats-user-{}-{}.csv", user_id, args.window.value()),
+                None => format!("stats-guild-{}-{}.csv", guild_id, args.window.value()),
+            };
+            response = response.attachment(filename, plays_csv(&plays).into_bytes());
+        }
+
+        cmd_ctx.reply(response.build()).await?;
+
+        Ok(())
+    }
+}
+
+/// Prefix commands take the options in any order, e.g. `stats user @someone 7d csv`.
+fn stats_args(cmd_ctx: &mut CommandContext<'_>) -> Result<StatsArgs> {
+    if !matches!(cmd_ctx, CommandContext::Prefix(_)) {
+        return Ok(StatsArgs {
+            user: cmd_ctx.get_id::<UserMarker>("user"),
+            window: cmd_ctx
+                .get_arg::<String>("window")
+                .and_then(|window| StatsWindow::from_value(&window))
+                .unwrap_or(StatsWindow::AllTime),
+            csv: cmd_ctx.get_arg::<bool>("csv").unwrap_or(false),
+        });
+    }
+
+    let mut args = StatsArgs {
+        user: None,
+        window: StatsWindow::AllTime,
+        csv: false,
+    };
+    let rest = cmd_ctx.get_remainder_arg("options").unwrap_or_default();
+    for arg in rest.split_whitespace() {
+        if let Some(window) = StatsWindow::from_value(arg) {
+            args.window = window;
+        } else if arg.eq_ignore_ascii_case("csv") {
+            args.csv = true;
+        } else if let Some(user) = parse_mention(arg).and_then(Id::new_checked) {
+            args.user = Some(user);
+        } else {
+            return Err(anyhow!(
+                "Unknown option `{}`. Use a user, `7d`, `30d`, `all` or `csv`.",
+                arg
+            ));
+        }
+    }
+    Ok(args)
+}
+
+fn build_stats_embed(
+    stats: &PlayStats,
+    subject: &str,
+    window: StatsWindow,
+    by_user: bool,
+) -> Embed {
+    let mut embed = EmbedBuilder::new()
+        .title(format!("📊 Listening stats · {}", window.label()))
+        .color(0x1DB954);
+    if stats.plays == 0 {
+        return embed
+            .description(format!("{}\n\nNothing was played in this time.", subject))
+            .build();
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    let hours = stats.listened_ms as f64 / 3_600_000.0;
+    embed = embed
+        .description(subject)
+        .field(EmbedFieldBuilder::new("Plays", stats.plays.to_string()).inline())
+        .field(EmbedFieldBuilder::new("Listening time", format!("{:.1} hours", hours)).inline())
+        .field(EmbedFieldBuilder::new(
+            "Top tracks",
+            leaderboard(stats.top_tracks.iter().map(|track| {
+                (
+                    format!("**{}** - {}", shorten(&track.title), shorten(&track.author)),
+                    track.plays,
+                )
+            })),
+        ))
+        .field(
+            EmbedFieldBuilder::new(
+                "Top artists",
+                leaderboard(
+                    stats
+                        .top_artists
+                        .iter()
+                        .map(|(artist, plays)| (shorten(artist), *plays)),
+                ),
+            )
+            .inline(),
+        );
+    // A user's own stats have only them as requester.
+    if !by_user {
+        embed = embed.field(
+            EmbedFieldBuilder::new(
+                "Top requesters",
+                leaderboard(
+                    stats
+                        .top_requesters
+                        .iter()
+                        .map(|(user_id, plays)| (format!("<@{}>", user_id), *plays)),
+                ),
+            )
+            .inline(),
+        );
+    }
+    embed
+        .field(
+            EmbedFieldBuilder::new(
+                "Busiest hours (UTC)",
+                leaderboard(
+                    stats
+                        .busiest_hours
+                        .iter()
+                        .map(|(hour, plays)| (format!("{:02}:00", hour), *plays)),
+                ),
+            )
+            .inline(),
+        )
+        .build()
+}
+
+/// Numbered `name · n plays` lines.
+fn leaderboard(entries: impl Iterator<Item = (String, u64)>) -> String {
+    let lines: Vec<_> = entries
+        .enumerate()
+        .map(|(idx, (name, plays))| format!("`{}.` {} · {} plays", idx + 1, name, plays))
+        .collect();
+    if lines.is_empty() {
+        "Nobody yet".to_string()
+    } else {
+        lines.join("\n")
+    }
+}
+
+fn shorten(text: &str) -> String {
+    if text.chars().count() > MAX_TITLE_LEN {
+        format!(
+            "{}…",
+            text.chars().take(MAX_TITLE_LEN - 1).collect::<String>()
+        )
+    } else {
+        text.to_string()
+    }
+}
+
+fn plays_csv(plays: &[Play]) -> String {
+    let mut csv = csv_line(&[
+        "started_at",
+        "title",
+        "artist",
+        "url",
+        "requester_id",
+        "listened_seconds",
+    ]);
+    for play in plays {
+        csv.push_str(&csv_line(&[
+            chrono::DateTime::from_timestamp_millis(play.started_at)
+                .map(|at| at.to_rfc3339())
+                .unwrap_or_default(),
+            play.title.clone(),
+            play.author.clone(),
+            play.uri.clone().unwrap_or_default(),
+            play.requester_id
+                .map(|id| id.to_string())
+                .unwrap_or_default(),
+            play.listened_ms
+                .map(|ms| (ms / 1000).to_string())
+                .unwrap_or_default(),
+        ]));
+    }
+    csv
+}
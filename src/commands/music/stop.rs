@@ -1,5 +1,6 @@
 use super::{check_voice_state, leave};
 use crate::command_handler::{Command, CommandContext, CommandResponseBuilder, GlobalState};
+use crate::utils::queue_history::snapshot_queue;
 use anyhow::Result;
 use async_trait::async_trait;
 use twilight_interactions::command::{CommandModel, CreateCommand};
@@ -18,6 +19,7 @@ impl Command<GlobalState> for StopCommand {
         .ok_or_else(|| anyhow::anyhow!("This command must be used in a guild."))?;
 
         check_voice_state(state.clone(), &cmd_ctx).await?;
+        snapshot_queue(&state, guild_id).await?;
         leave(state, guild_id).await?;
 
         let response = CommandResponseBuilder::new().content("⏹️ Stopped").build();
@@ -0,0 +1,201 @@
+use std::time::{Duration, Instant};
+
+use super::play::to_query;
+use crate::command_handler::{
+    Command, CommandContext, CommandResponseBuilder, GlobalState, StateExt,
+};
+use crate::utils::track::format_length;
+use crate::utils::track_preview::TrackPreview;
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+use lavalink_rs::model::track::TrackData;
+use lavalink_rs::prelude::TrackLoadData;
+use twilight_interactions::command::{CommandModel, CreateCommand};
+use twilight_model::channel::message::{
+    Component, Embed,
+    component::{ActionRow, Button, ButtonStyle},
+};
+use twilight_util::builder::embed::{EmbedBuilder, EmbedFieldBuilder, ImageSource};
+
+/// How many tracks of a playlist are listed in the preview.
+const PLAYLIST_PREVIEW_LEN: usize = 5;
+
+#[allow(unused)]
+#[derive(CommandModel, CreateCommand)]
+#[command(
+    name = "trackinfo",
+    desc = "Look up a song or playlist without queueing it."
+)]
+pub struct TrackInfoCommand {
+    #[command(desc = "The song name or URL to look up")]
+    query: String,
+}
+
+#[async_trait]
+impl Command<GlobalState> for TrackInfoCommand {
+    async fn execute<'ctx>(state: GlobalState, mut cmd_ctx: CommandContext<'ctx>) -> Result<()> {
+        let query = cmd_ctx
+            .get_remainder_arg("query")
+            .ok_or_else(|| anyhow!("You must provide a song name or URL to look up."))?;
+
+        let author = cmd_ctx
+            .author()
+            .ok_or_else(|| anyhow!("Interaction is missing author information."))?;
+
+        let guild_id = cmd_ctx
+            .guild_id()
+            .ok_or_else(|| anyhow!("This command must be used in a guild."))?;
+
+        let loaded = state
+            .lavalink()
+            .load_tracks(guild_id, &to_query(query)?)
+            .await?;
+
+        let (tracks, playlist_name) = match loaded.data {
+            Some(TrackLoadData::Track(track)) => (vec![track], None),
+            Some(TrackLoadData::Search(results)) => (
+                vec![
+                    results
+                        .into_iter()
+                        .next()
+                        .ok_or_else(|| anyhow!("No tracks found from search."))?,
+                ],
+                None,
+            ),
+            Some(TrackLoadData::Playlist(playlist)) => (playlist.tracks, Some(playlist.info.name)),
+            Some(TrackLoadData::Error(e)) => {
+                return Err(anyhow!("Error loading tracks: {}", e.message));
+            }
+            None => {
+                return Err(anyhow!(
+                    "Failed to load tracks: received no data from Lavalink."
+                ));
+            }
+        };
+
+        if tracks.is_empty() {
+            return Err(anyhow!("No tracks were found for that query."));
+        }
+
+        let embed = match &playlist_name {
+            Some(name) => build_playlist_embed(name, &tracks),
+            None => build_track_embed(&tracks[0]),
+        };
+
+        let key = state.track_previews.lock().await.insert(TrackPreview {
+            tracks,
+            playlist_name,
+            requester: author.id,
+            created_at: Instant::now(),
+        });
+
+        let response = CommandResponseBuilder::new()
+            .embed(embed)
+            .component(preview_buttons(&key))
+            .build();
+
+        cmd_ctx.reply(response).await?;
+
+        Ok(())
+    }
+}
+
+fn build_track_embed(track: &TrackData) -> Embed {
+    let info = &track.info;
+    let mut embed = EmbedBuilder::new()
+        .title(info.title.clone())
+        .description(format!("by **{}**", info.author))
+        .color(0x1DB954)
+        .field(EmbedFieldBuilder::new("Duration", format_length(track)).inline())
+        .field(EmbedFieldBuilder::new("Source", info.source_name.clone()).inline())
+        .field(
+            EmbedFieldBuilder::new("ISRC", info.isrc.clone().unwrap_or_else(|| "—".to_string()))
+                .inline(),
+        );
+
+    if let Some(uri) = &info.uri {
+        embed = embed.url(uri.clone());
+    }
+    if let Some(artwork) = info
+        .artwork_url
+        .as_ref()
+        .and_then(|url| ImageSource::url(url).ok())
+    {
+        embed = embed.thumbnail(artwork);
+    }
+
+    embed.build()
+}
+
+fn build_playlist_embed(name: &str, tracks: &[TrackData]) -> Embed {
+    let total_ms: u64 = tracks
+        .iter()
+        .filter(|track| !track.info.is_stream)
+        .map(|track| track.info.length)
+        .sum();
+
+    let mut listing: Vec<String> = tracks
+        .iter()
+        .take(PLAYLIST_PREVIEW_LEN)
+        .enumerate()
+        .map(|(idx, track)| {
+            format!(
+                "`{}.` {} - {} `[{}]`",
+                idx + 1,
+                track.info.title,
+                track.info.author,
+                format_length(track)
+            )
+        })
+        .collect();
+    if tracks.len() > PLAYLIST_PREVIEW_LEN {
+        listing.push(format!("…and {} more", tracks.len() - PLAYLIST_PREVIEW_LEN));
+    }
+
+    let mut embed = EmbedBuilder::new()
+        .title(format!("📃 {}", name))
+        .description(listing.join("\n"))
+        .color(0x1DB954)
+        .field(EmbedFieldBuilder::new("Tracks", tracks.len().to_string()).inline())
+        .field(
+            EmbedFieldBuilder::new(
+                "Total length",
+                humantime::format_duration(Duration::from_secs(total_ms / 1000)).to_string(),
+            )
+            .inline(),
+        )
+        .field(EmbedFieldBuilder::new("Source", tracks[0].info.source_name.clone()).inline());
+
+    if let Some(artwork) = tracks[0]
+        .info
+        .artwork_url
+        .as_ref()
+        .and_then(|url| ImageSource::url(url).ok())
+    {
+        embed = embed.thumbnail(artwork);
+    }
+
+    embed.build()
+}
+
+fn preview_buttons(key: &str) -> Component {
+    let button = |label: &str, action: &str, style: ButtonStyle| {
+        Component::Button(Button {
+            label: Some(label.to_string()),
+            custom_id: Some(format!("trackinfo:{}:{}", action, key)),
+            style,
+            emoji: None,
+            disabled: false,
+            url: None,
+            sku_id: None,
+        })
+    };
+
+    Component::ActionRow(ActionRow {
+        components: vec![
+            button("➕ Queue", "queue", ButtonStyle::Primary),
+            button("⏭️ Play next", "next", ButtonStyle::Secondary),
+            button("✖️ Cancel", "cancel", ButtonStyle::Danger),
+        ],
+    })
+}
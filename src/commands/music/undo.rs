@@ -0,0 +1,80 @@
+use std::time::Duration;
+
+use super::join;
+use super::play::reply_to_join;
+use crate::command_handler::{
+    Command, CommandContext, CommandResponseBuilder, GlobalState, StateExt,
+};
+use crate::utils::track::{RESUMED_FLAG, flag_track};
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+use lavalink_rs::prelude::TrackInQueue;
+use twilight_interactions::command::{CommandModel, CreateCommand};
+
+#[derive(CommandModel, CreateCommand)]
+#[command(
+    name = "undo",
+    desc = "Restore the queue as it was before the last change."
+)]
+pub struct UndoCommand;
+
+#[async_trait]
+impl Command<GlobalState> for UndoCommand {
+    async fn execute<'ctx>(state: GlobalState, cmd_ctx: CommandContext<'ctx>) -> Result<()> {
+        let guild_id = cmd_ctx
+            .guild_id()
+            .ok_or_else(|| anyhow!("This command must be used in a guild."))?;
+
+        let author = cmd_ctx
+            .author()
+            .ok_or_else(|| anyhow!("Interaction is missing author information."))?;
+
+        let voice_state = state
+            .cache
+            .voice_state(author.id, guild_id)
+            .ok_or_else(|| anyhow!("You must be in a voice channel to use this command."))?;
+
+        let snapshot = state
+            .queue_history
+            .lock()
+            .await
+            .pop(guild_id)
+            .ok_or_else(|| anyhow!("There is nothing to undo."))?;
+
+        // `/stop` tears the player down, so bring it back before restoring.
+        let msg = join(state.clone(), &cmd_ctx, voice_state.channel_id(), guild_id).await?;
+
+        let player = state
+            .lavalink()
+            .get_player_context(guild_id)
+            .ok_or_else(|| anyhow!("Player context not found. Is the bot in a voice channel?"))?;
+
+        let queue = player.get_queue();
+        let restored_count = snapshot.queue.len();
+        queue.replace(snapshot.queue)?;
+
+        let reply_message = if let Some(mut track) = snapshot.current {
+            flag_track(&mut track, RESUMED_FLAG);
+            let title = track.info.title.clone();
+            queue.push_to_front(TrackInQueue {
+                start_time: Some(Duration::from_millis(snapshot.position)),
+                ..TrackInQueue::from(track)
+            })?;
+            player.skip()?;
+            format!(
+                "↩️ Restored `{}` and {} queued track(s).",
+                title, restored_count
+            )
+        } else {
+            if player.get_player().await?.track.is_none() && restored_count > 0 {
+                player.skip()?;
+            }
+            format!("↩️ Restored {} queued track(s).", restored_count)
+        };
+
+        let response_builder = CommandResponseBuilder::new().content(reply_message);
+        reply_to_join(&state, &cmd_ctx, &msg, response_builder).await?;
+
+        Ok(())
+    }
+}
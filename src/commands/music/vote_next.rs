@@ -0,0 +1,429 @@
+use super::{Enqueued, enqueue, to_query};
+use crate::command_handler::{
+    Command, CommandContext, CommandResponseBuilder, GlobalState, StateExt,
+};
+use crate::utils::queue_history::snapshot_queue;
+use crate::utils::vote::{MAX_CANDIDATES, MIN_CANDIDATES, VoteCandidate, VotePoll, poll_duration};
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+use lavalink_rs::model::track::TrackData;
+use lavalink_rs::prelude::{TrackInQueue, TrackLoadData};
+use std::collections::HashMap;
+use std::time::Duration;
+use twilight_interactions::command::{CommandModel, CreateCommand};
+use twilight_model::channel::message::{
+    Component, Embed,
+    component::{ActionRow, Button, ButtonStyle},
+};
+use twilight_model::id::{Id, marker::GuildMarker};
+use twilight_util::builder::embed::{EmbedBuilder, EmbedFooterBuilder};
+
+#[allow(unused)]
+#[derive(CommandModel, CreateCommand)]
+#[command(
+    name = "vote-next",
+    desc = "Let the listeners vote on what plays next."
+)]
+pub struct VoteNextCommand {
+    #[command(desc = "Search for the candidates instead of taking them from the queue")]
+    search: Option<String>,
+}
+
+#[async_trait]
+impl Command<GlobalState> for VoteNextCommand {
+    async fn execute<'ctx>(state: GlobalState, mut cmd_ctx: CommandContext<'ctx>) -> Result<()> {
+        let guild_id = cmd_ctx
+            .guild_id()
+            .ok_or_else(|| anyhow!("This command must be used in a guild."))?;
+        let channel_id = cmd_ctx
+            .channel_id()
+            .ok_or_else(|| anyhow!("This command must be used in a channel."))?;
+        let author = cmd_ctx
+            .author()
+            .ok_or_else(|| anyhow!("Interaction is missing author information."))?
+            .clone();
+
+        if !crate::utils::voice::listeners(&state.cache, guild_id).contains(&author.id) {
+            return Err(anyhow!("Join my voice channel to start a vote."));
+        }
+        if state.vote_polls.lock().await.contains_key(&guild_id) {
+            return Err(anyhow!("A vote is already running. Cast yours on it!"));
+        }
+        let player = state
+            .lavalink()
+            .get_player_context(guild_id)
+            .ok_or_else(|| anyhow!("Player context not found. Is the bot in a voice channel?"))?;
+        let player_data = player.get_player().await?;
+        let current = player_data
+            .track
+            .ok_or_else(|| anyhow!("Nothing is playing, so there is no next track to vote on."))?;
+
+        let search = cmd_ctx.get_remainder_arg("search");
+        let candidates = match search {
+            Some(search) => search_candidates(&state, guild_id, search).await?,
+            None => {
+                let queued: Vec<_> = player
+                    .get_queue()
+                    .get_queue()
+                    .await?
+                    .into_iter()
+                    .take(MAX_CANDIDATES)
+                    .map(|track| candidate(&track.track, true))
+                    .collect();
+                if queued.len() >= MIN_CANDIDATES {
+                    queued
+                } else {
+                    recommended_candidates(&state, guild_id, &current).await?
+                }
+            }
+        };
+        if candidates.len() < MIN_CANDIDATES {
+            return Err(anyhow!(
+                "I need at least {} tracks to choose from. Try `/vote-next search:`.",
+                MIN_CANDIDATES
+            ));
+        }
+
+        let remaining = (!current.info.is_stream).then(|| {
+            Duration::from_millis(
+                current
+                    .info
+                    .length
+                    .saturating_sub(player_data.state.position),
+            )
+        });
+        let duration = poll_duration(remaining);
+        let poll = VotePoll {
+            id: chrono::Utc::now().timestamp_millis(),
+            channel_id,
+            created_by: author.id,
+            creator_permissions: cmd_ctx.member_permissions(),
+            candidates,
+            votes: HashMap::new(),
+            track_ended: false,
+        };
+        let poll_id = poll.id;
+        let (embed, component) = vote_poll_message(&poll);
+        {
+            let mut polls = state.vote_polls.lock().await;
+            if polls.contains_key(&guild_id) {
+                return Err(anyhow!("A vote is already running. Cast yours on it!"));
+            }
+            polls.insert(guild_id, poll);
+        }
+
+        let closing_state = state.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(duration).await;
+            if let Err(e) = close_vote_poll(&closing_state, guild_id, poll_id).await {
+                tracing::warn!(error = ?e, "Failed to close the vote");
+            }
+        });
+
+        cmd_ctx
+            .reply(
+                CommandResponseBuilder::new()
+                    .content(format!(
+                        "🗳️ Vote for the next track! The poll closes <t:{}:R>.",
+                        (chrono::Utc::now() + duration).timestamp()
+                    ))
+                    .embed(embed)
+                    .component(component)
+                    .build(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+fn candidate(track: &TrackData, queued: bool) -> VoteCandidate {
+    VoteCandidate {
+        encoded: track.encoded.clone(),
+        title: track.info.title.clone(),
+        author: track.info.author.clone(),
+        queued,
+    }
+}
+
+async fn search_candidates(
+    state: &GlobalState,
+    guild_id: Id<GuildMarker>,
+    search: String,
+) -> Result<Vec<VoteCandidate>> {
+    let loaded = state
+        .lavalink()
+        .load_tracks(guild_id, &to_query(search)?)
+        .await?;
+    let tracks = match loaded.data {
+        Some(TrackLoadData::Search(results)) => results,
+        Some(TrackLoadData::Playlist(playlist)) => playlist.tracks,
+        Some(TrackLoadData::Track(track)) => vec![track],
+        Some(TrackLoadData::Error(e)) => return Err(anyhow!("Error searching: {}", e.message)),
+        _ => Vec::new(),
+    };
+    Ok(tracks
+        .iter()
+        .take(MAX_CANDIDATES)
+        .map(|track| candidate(track, false))
+        .collect())
+}
+
+/// Tracks like the one playing: Spotify recommendations for Spotify tracks,
+/// the YouTube mix for YouTube ones.
+async fn recommended_candidates(
+    state: &GlobalState,
+    guild_id: Id<GuildMarker>,
+    current: &TrackData,
+) -> Result<Vec<VoteCandidate>> {
+    let identifier = &current.info.identifier;
+    let query = match current.info.source_name.as_str() {
+        "spotify" => format!("sprec:seed_tracks={}", identifier),
+        "youtube" => format!(
+            "https://www.youtube.com/watch?v={}&list=RD{}",
+            identifier, identifier
+        ),
+        _ => return Ok(Vec::new()),
+    };
+    let tracks = match state.lavalink().load_tracks(guild_id, &query).await?.data {
+        Some(TrackLoadData::Search(results)) => results,
+        Some(TrackLoadData::Playlist(playlist)) => playlist.tracks,
+        _ => Vec::new(),
+    };
+    Ok(tracks
+        .iter()
+        .filter(|track| track.info.identifier != current.info.identifier)
+        .take(MAX_CANDIDATES)
+        .map(|track| candidate(track, false))
+        .collect())
+}
+
+/// The poll's standings and one button per candidate. Rebuilt after every
+/// vote.
+pub fn vote_poll_message(poll: &VotePoll) -> (Embed, Component) {
+    let tallies = poll.tallies();
+    let description = poll
+        .candidates
+        .iter()
+        .zip(&tallies)
+        .enumerate()
+        .map(|(idx, (candidate, votes))| {
+            format!(
+                "`{}.` **{}** - {} · {} vote{}",
+                idx + 1,
+                candidate.title,
+                candidate.author,
+                votes,
+                if *votes == 1 { "" } else { "s" }
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    let embed = EmbedBuilder::new()
+        .title("🗳️ What should play next?")
+        .description(description)
+        .color(0x1DB954)
+        .footer(EmbedFooterBuilder::new(
+            "Only listeners in the voice channel can vote. You can change your vote.",
+        ))
+        .build();
+
+    let component = Component::ActionRow(ActionRow {
+        components: (0..poll.candidates.len())
+            .map(|idx| {
+                Component::Button(Button {
+                    label: Some(format!("{}", idx + 1)),
+                    custom_id: Some(format!("vote_next:{}:{}", poll.id, idx)),
+                    style: ButtonStyle::Primary,
+                    emoji: None,
+                    disabled: false,
+                    url: None,
+                    sku_id: None,
+                })
+            })
+            .collect(),
+    });
+    (embed, component)
+}
+
+/// What became of a poll's winner.
+enum WinnerPlacement {
+    /// It is at the front of the queue.
+    UpNext,
+    /// It went to the DJs for approval.
+    PendingApproval,
+    /// It was a queued track that has since been played or removed.
+    Gone,
+}
+
+/// Closes poll `poll_id` if it is still open and moves the winner up next.
+/// Called at the deadline.
+pub async fn close_vote_poll(
+    state: &GlobalState,
+    guild_id: Id<GuildMarker>,
+    poll_id: i64,
+) -> Result<()> {
+    let poll = {
+        let mut polls = state.vote_polls.lock().await;
+        if !polls.get(&guild_id).is_some_and(|poll| poll.id == poll_id) {
+            return Ok(());
+        }
+        polls.remove(&guild_id)
+    };
+    let Some(poll) = poll else {
+        return Ok(());
+    };
+    settle_vote_poll(state, guild_id, &poll, None).await?;
+    Ok(())
+}
+
+/// Closes the guild's poll if the track it was opened during has finished,
+/// now that the player has moved on to `started`. A winner other than
+/// `started` plays right away, with `started` after it. Returns whether
+/// `started` was put back.
+pub async fn close_vote_poll_on_start(
+    state: &GlobalState,
+    guild_id: Id<GuildMarker>,
+    started: &TrackData,
+) -> Result<bool> {
+    let poll = {
+        let mut polls = state.vote_polls.lock().await;
+        if !polls.get(&guild_id).is_some_and(|poll| poll.track_ended) {
+            return Ok(false);
+        }
+        polls.remove(&guild_id)
+    };
+    let Some(poll) = poll else {
+        return Ok(false);
+    };
+    settle_vote_poll(state, guild_id, &poll, Some(started)).await
+}
+
+/// Applies the poll's outcome and announces it where the poll was posted.
+async fn settle_vote_poll(
+    state: &GlobalState,
+    guild_id: Id<GuildMarker>,
+    poll: &VotePoll,
+    started: Option<&TrackData>,
+) -> Result<bool> {
+    let mut replaced = false;
+    let content = match poll.winner() {
+        Some(winner) => {
+            let candidate = &poll.candidates[winner];
+            let already_playing =
+                started.is_some_and(|started| started.encoded == candidate.encoded);
+            let placement = if already_playing {
+                WinnerPlacement::UpNext
+            } else {
+                play_winner_next(state, guild_id, poll, candidate).await?
+            };
+            match placement {
+                WinnerPlacement::UpNext if already_playing => format!(
+                    "🗳️ The votes are in: **{}** by **{}** is playing now.",
+                    candidate.title, candidate.author
+                ),
+                WinnerPlacement::UpNext => {
+                    if let Some(started) = started {
+                        play_winner_now(state, guild_id, started).await?;
+                        replaced = true;
+                    }
+                    format!(
+                        "🗳️ The votes are in: **{}** by **{}** plays {}.",
+                        candidate.title,
+                        candidate.author,
+                        if replaced { "now" } else { "next" }
+                    )
+                }
+                WinnerPlacement::PendingApproval => format!(
+                    "🗳️ The votes are in: **{}** by **{}** won and goes to the DJs for approval.",
+                    candidate.title, candidate.author
+                ),
+                WinnerPlacement::Gone => format!(
+                    "🗳️ The votes are in: **{}** by **{}** won, but it was played or removed \
+                     while the vote was open, so the queue stays as it is.",
+                    candidate.title, candidate.author
+                ),
+            }
+        }
+        None => "🗳️ Nobody voted, so the queue stays as it is.".to_string(),
+    };
+    state
+        .http
+        .create_message(poll.channel_id)
+        .content(&content)
+        .await?;
+
+    Ok(replaced)
+}
+
+/// Moves a queued winner to the front of the queue, or queues a new one
+/// there for whoever started the poll, which may need a DJ's approval.
+async fn play_winner_next(
+    state: &GlobalState,
+    guild_id: Id<GuildMarker>,
+    poll: &VotePoll,
+    candidate: &VoteCandidate,
+) -> Result<WinnerPlacement> {
+    let player = state
+        .lavalink()
+        .get_player_context(guild_id)
+        .ok_or_else(|| anyhow!("Player context not found. Is the bot in a voice channel?"))?;
+
+    if candidate.queued {
+        let queue = player.get_queue();
+        let mut pending = queue.get_queue().await?;
+        // It may have been played or removed while the poll was open.
+        let Some(position) = pending
+            .iter()
+            .position(|track| track.track.encoded == candidate.encoded)
+        else {
+            return Ok(WinnerPlacement::Gone);
+        };
+        snapshot_queue(state, guild_id).await?;
+        if let Some(track) = pending.remove(position) {
+            pending.push_front(track);
+        }
+        queue.replace(pending)?;
+        Ok(WinnerPlacement::UpNext)
+    } else {
+        let track = state
+            .lavalink()
+            .decode_track(guild_id, &candidate.encoded)
+            .await
+            .map_err(|e| anyhow!("Could not load the winning track: {}", e))?;
+        let tracks = vec![TrackInQueue::from(track)];
+        let enqueued = enqueue(
+            state,
+            guild_id,
+            tracks,
+            poll.created_by,
+            poll.creator_permissions,
+            Some(poll.channel_id),
+            true,
+        )
+        .await?;
+        Ok(match enqueued {
+            Enqueued::Queued(_) => WinnerPlacement::UpNext,
+            Enqueued::PendingApproval(_) => WinnerPlacement::PendingApproval,
+        })
+    }
+}
+
+/// Puts `started`, which the player moved on to before the poll closed,
+/// back behind the winner at the front of the queue and skips to the winner.
+async fn play_winner_now(
+    state: &GlobalState,
+    guild_id: Id<GuildMarker>,
+    started: &TrackData,
+) -> Result<()> {
+    let player = state
+        .lavalink()
+        .get_player_context(guild_id)
+        .ok_or_else(|| anyhow!("Player context not found. Is the bot in a voice channel?"))?;
+    let queue = player.get_queue();
+    let mut pending = queue.get_queue().await?;
+    pending.insert(pending.len().min(1), TrackInQueue::from(started.clone()));
+    queue.replace(pending)?;
+    player.skip()?;
+    Ok(())
+}
@@ -0,0 +1,70 @@
+use super::check_voice_state;
+use crate::command_handler::{
+    Command, CommandContext, CommandResponseBuilder, GlobalState, StateExt,
+};
+use crate::utils::eta::queue_start_times;
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+use twilight_interactions::command::{CommandModel, CreateCommand};
+
+#[allow(unused)]
+#[derive(CommandModel, CreateCommand)]
+#[command(name = "when", desc = "Show when a queued track will start playing.")]
+pub struct WhenCommand {
+    #[command(
+        desc = "The queue position of the track (1 for the next song)",
+        min_value = 1
+    )]
+    position: i64,
+}
+
+#[async_trait]
+impl Command<GlobalState> for WhenCommand {
+    async fn execute<'ctx>(state: GlobalState, mut cmd_ctx: CommandContext<'ctx>) -> Result<()> {
+        let guild_id = cmd_ctx
+            .guild_id()
+            .ok_or_else(|| anyhow!("This command must be used in a guild."))?;
+
+        let position: i64 = cmd_ctx.get_arg("position").ok_or_else(|| {
+            anyhow!("Position argument is missing or invalid. Please provide a number (e.g., 1 for the next song).")
+        })?;
+
+        check_voice_state(state.clone(), &cmd_ctx).await?;
+
+        let player = state
+            .lavalink()
+            .get_player_context(guild_id)
+            .ok_or_else(|| anyhow!("I'm not playing anything in this guild."))?;
+
+        let tracks = player.get_queue().get_queue().await?;
+        let idx = usize::try_from(position - 1)
+            .ok()
+            .filter(|idx| *idx < tracks.len())
+            .ok_or_else(|| {
+                anyhow!(
+                    "There is no track at position {}. The queue has {} tracks.",
+                    position,
+                    tracks.len()
+                )
+            })?;
+
+        let start_times = queue_start_times(&player.get_player().await?, &tracks);
+        let title = &tracks[idx].track.info.title;
+        let content = match start_times[idx] {
+            Some(timestamp) => format!(
+                "🕒 `{}` (#{}) should start <t:{}:R> at <t:{}:t>.",
+                title, position, timestamp, timestamp
+            ),
+            None => format!(
+                "🕒 `{}` (#{}) is queued behind a livestream, so its start time is unknown.",
+                title, position
+            ),
+        };
+
+        cmd_ctx
+            .reply(CommandResponseBuilder::new().content(content).build())
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,236 @@
+use crate::command_handler::{Command, CommandContext, CommandResponseBuilder, GlobalState};
+use crate::prefix_parser::parse_mention;
+use crate::storage::Wrapped;
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+use chrono::{Datelike, NaiveDate};
+use twilight_interactions::command::{CommandModel, CreateCommand};
+use twilight_model::channel::message::{
+    Component, Embed,
+    component::{ActionRow, Button, ButtonStyle},
+};
+use twilight_model::id::{
+    Id,
+    marker::{GuildMarker, UserMarker},
+};
+use twilight_util::builder::embed::{EmbedBuilder, EmbedFooterBuilder};
+
+const WRAPPED_PAGES: usize = 5;
+/// How many entries each top list shows.
+const WRAPPED_TOP: usize = 5;
+
+#[derive(CommandModel, CreateCommand)]
+#[command(
+    name = "wrapped",
+    desc = "A year of listening on this server, page by page."
+)]
+pub struct WrappedCommand {
+    #[allow(unused)]
+    #[command(
+        desc = "The year; this year if left out",
+        min_value = 2000,
+        max_value = 9999
+    )]
+    year: Option<i64>,
+    #[allow(unused)]
+    #[command(desc = "Whose year to show; yours if left out")]
+    user: Option<Id<UserMarker>>,
+}
+
+#[async_trait]
+impl Command<GlobalState> for WrappedCommand {
+    async fn execute<'ctx>(state: GlobalState, mut cmd_ctx: CommandContext<'ctx>) -> Result<()> {
+        let author = cmd_ctx
+            .author()
+            .ok_or_else(|| anyhow!("Interaction is missing author information."))?
+            .clone();
+        let guild_id = cmd_ctx
+            .guild_id()
+            .ok_or_else(|| anyhow!("This command must be used in a guild."))?;
+
+        let (year, user_id) = wrapped_args(&mut cmd_ctx)?;
+        let year = year.unwrap_or_else(|| chrono::Utc::now().year());
+        let user_id = user_id.unwrap_or(author.id);
+
+        let (embed, component) = wrapped_message(&state, guild_id, author.id, user_id, year, 0)?;
+        let response = CommandResponseBuilder::new()
+            .embed(embed)
+            .component(component)
+            .build();
+
+        cmd_ctx.reply(response).await?;
+
+        Ok(())
+    }
+}
+
+/// Prefix commands take the year and user in either order, e.g. `wrapped 2024 @someone`.
+fn wrapped_args(cmd_ctx: &mut CommandContext<'_>) -> Result<(Option<i32>, Option<Id<UserMarker>>)> {
+    if !matches!(cmd_ctx, CommandContext::Prefix(_)) {
+        let year = cmd_ctx
+            .get_arg::<i64>("year")
+            .and_then(|year| i32::try_from(year).ok());
+        return Ok((year, cmd_ctx.get_id::<UserMarker>("user")));
+    }
+
+    let (mut year, mut user) = (None, None);
+    let rest = cmd_ctx.get_remainder_arg("options").unwrap_or_default();
+    for arg in rest.split_whitespace() {
+        if let Ok(value) = arg.parse::<i32>() {
+            year = Some(value);
+        } else if let Some(id) = parse_mention(arg) {
+            user = Id::new_checked(id);
+        } else {
+            return Err(anyhow!(
+                "Unknown option `{}`. Use a year and/or a user.",
+                arg
+            ));
+        }
+    }
+    Ok((year, user))
+}
+
+/// Page `page` of a user's wrapped, with the buttons to flip through it.
+/// The buttons carry everything needed to rebuild the page, so they keep
+/// working after a restart.
+pub fn wrapped_message(
+    state: &GlobalState,
+    guild_id: Id<GuildMarker>,
+    viewer_id: Id<UserMarker>,
+    user_id: Id<UserMarker>,
+    year: i32,
+    page: usize,
+) -> Result<(Embed, Component)> {
+    let bounds =
+        year_bounds(year).ok_or_else(|| anyhow!("{} is not a year I can look up.", year))?;
+    let wrapped = state
+        .storage
+        .wrapped(guild_id, user_id, bounds, WRAPPED_TOP)?;
+    if wrapped.plays == 0 {
+        return Err(anyhow!(
+            "<@{}> has not listened to anything here in {}.",
+            user_id,
+            year
+        ));
+    }
+
+    let page = page.min(WRAPPED_PAGES - 1);
+    let embed = build_wrapped_page(&wrapped, user_id, year, page);
+    let custom_id = |page: usize| format!("wrapped:{}:{}:{}:{}", viewer_id, user_id, year, page);
+    let component = Component::ActionRow(ActionRow {
+        components: vec![
+            Component::Button(Button {
+                label: Some("◀️ Back".to_string()),
+                custom_id: Some(custom_id(page.saturating_sub(1))),
+                style: ButtonStyle::Secondary,
+                emoji: None,
+                disabled: page == 0,
+                url: None,
+                sku_id: None,
+            }),
+            Component::Button(Button {
+                label: Some("Next ▶️".to_string()),
+                custom_id: Some(custom_id((page + 1).min(WRAPPED_PAGES - 1))),
+                style: ButtonStyle::Primary,
+                emoji: None,
+                disabled: page + 1 == WRAPPED_PAGES,
+                url: None,
+                sku_id: None,
+            }),
+        ],
+    });
+    Ok((embed, component))
+}
+
+/// The start of `year` and of the year after, as Unix timestamps in milliseconds.
+fn year_bounds(year: i32) -> Option<(i64, i64)> {
+    let start = |year| {
+        NaiveDate::from_ymd_opt(year, 1, 1)?
+            .and_hms_opt(0, 0, 0)
+            .map(|at| at.and_utc().timestamp_millis())
+    };
+    Some((start(year)?, start(year.checked_add(1)?)?))
+}
+
+fn build_wrapped_page(wrapped: &Wrapped, user_id: Id<UserMarker>, year: i32, page: usize) -> Embed {
+    let (title, description) = match page {
+        0 => (
+            format!("🎁 {} Wrapped", year),
+            format!(
+                "<@{}>'s year in music.\n\n**{}** minutes listened across **{}** plays.",
+                user_id,
+                wrapped.listened_ms / 60_000,
+                wrapped.plays
+            ),
+        ),
+        1 => (
+            "🌅 First song of the year".to_string(),
+            match &wrapped.first_play {
+                Some(first) => {
+                    let title = match &first.uri {
+                        Some(uri) => format!("[{}](<{}>)", first.title, uri),
+                        None => first.title.clone(),
+                    };
+                    format!(
+                        "It all started with **{}** by **{}** on <t:{}:D>.",
+                        title,
+                        first.author,
+                        first.started_at / 1000
+                    )
+                }
+                None => "Lost to history.".to_string(),
+            },
+        ),
+        2 => (
+            "🎵 Top songs".to_string(),
+            numbered(wrapped.top_tracks.iter().map(|track| {
+                format!(
+                    "**{}** - {} · {} plays",
+                    track.title, track.author, track.plays
+                )
+            })),
+        ),
+        3 => (
+            "🎤 Top artists".to_string(),
+            numbered(
+                wrapped
+                    .top_artists
+                    .iter()
+                    .map(|(artist, plays)| format!("**{}** · {} plays", artist, plays)),
+            ),
+        ),
+        _ => (
+            "👥 Listened with most".to_string(),
+            if wrapped.companions.is_empty() {
+                "Just you and the music.".to_string()
+            } else {
+                numbered(
+                    wrapped
+                        .companions
+                        .iter()
+                        .map(|(id, plays)| format!("<@{}> · {} plays together", id, plays)),
+                )
+            },
+        ),
+    };
+
+    EmbedBuilder::new()
+        .title(title)
+        .description(description)
+        .color(0x1DB954)
+        .footer(EmbedFooterBuilder::new(format!(
+            "Page {}/{} • {}",
+            page + 1,
+            WRAPPED_PAGES,
+            year
+        )))
+        .build()
+}
+
+fn numbered(lines: impl Iterator<Item = String>) -> String {
+    lines
+        .enumerate()
+        .map(|(idx, line)| format!("`{}.` {}", idx + 1, line))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
@@ -0,0 +1,71 @@
+use crate::commands::music::{Decision, decide_request, is_dj};
+use crate::components::reply_ephemeral;
+use anyhow::anyhow;
+use std::sync::Arc;
+use twilight_model::{
+    application::interaction::{Interaction, message_component::MessageComponentInteractionData},
+    http::interaction::{InteractionResponse, InteractionResponseType},
+};
+use twilight_util::builder::InteractionResponseDataBuilder;
+
+/// Decides on a pending song request from its approval card. The custom id
+/// is `approval:<approve|next|reject>:<request>`.
+pub async fn approval_button_handler(
+    state: Arc<crate::state::State>,
+    interaction: Interaction,
+    data: MessageComponentInteractionData,
+) -> anyhow::Result<()> {
+    let guild_id = interaction
+        .guild_id
+        .ok_or_else(|| anyhow!("Interaction must be in a guild to decide on requests"))?;
+    let user_id = interaction
+        .author_id()
+        .ok_or_else(|| anyhow!("Interaction is missing author information."))?;
+
+    let parts: Vec<_> = data.custom_id.split(':').skip(1).collect();
+    let malformed = || anyhow!("Malformed approval custom id: {}", data.custom_id);
+    let [action, request_id] = parts[..] else {
+        return Err(malformed());
+    };
+    let decision = Decision::from_action(action).ok_or_else(malformed)?;
+    let request_id: i64 = request_id.parse().map_err(|_| malformed())?;
+
+    let (roles, permissions) = interaction
+        .member
+        .as_ref()
+        .map(|member| (member.roles.clone(), member.permissions))
+        .unwrap_or_default();
+    if !is_dj(&state, guild_id, user_id, &roles, permissions)? {
+        return reply_ephemeral(
+            &state,
+            &interaction,
+            "Only DJs and server managers can decide on requests.",
+        )
+        .await;
+    }
+
+    let embed = match decide_request(&state, guild_id, user_id, request_id, decision).await {
+        Ok(embed) => embed,
+        Err(e) => return reply_ephemeral(&state, &interaction, &e.to_string()).await,
+    };
+
+    state
+        .http
+        .interaction(interaction.application_id)
+        .create_response(
+            interaction.id,
+            &interaction.token,
+            &InteractionResponse {
+                kind: InteractionResponseType::UpdateMessage,
+                data: Some(
+                    InteractionResponseDataBuilder::new()
+                        .embeds([embed])
+                        .components([])
+                        .build(),
+                ),
+            },
+        )
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,170 @@
+use crate::command_handler::StateExt;
+use crate::components::reply_ephemeral;
+use crate::utils::track::format_length;
+use anyhow::anyhow;
+use lavalink_rs::model::track::TrackData;
+use std::sync::Arc;
+use std::time::Duration;
+use twilight_http::api_error::{ApiError, GeneralApiError};
+use twilight_http::error::ErrorType;
+use twilight_model::{
+    application::interaction::Interaction,
+    channel::message::{Embed, MessageFlags},
+    http::interaction::{InteractionResponse, InteractionResponseType},
+    id::{
+        Id,
+        marker::{ChannelMarker, GuildMarker, MessageMarker},
+    },
+    util::Timestamp,
+};
+use twilight_util::builder::InteractionResponseDataBuilder;
+use twilight_util::builder::embed::{EmbedBuilder, EmbedFieldBuilder, ImageSource};
+
+/// Discord's "Cannot send messages to this user".
+const CANNOT_MESSAGE_USER: u64 = 50007;
+
+/// DMs the playing track to the clicker. If their DMs are closed the same
+/// embed is shown to them in the channel instead.
+pub async fn grab_button_handler(
+    state: Arc<crate::state::State>,
+    interaction: Interaction,
+) -> anyhow::Result<()> {
+    let guild_id = interaction
+        .guild_id
+        .ok_or_else(|| anyhow!("Interaction must be in a guild to grab a track"))?;
+    let user_id = interaction
+        .author_id()
+        .ok_or_else(|| anyhow!("Interaction is missing author information."))?;
+    let message = interaction
+        .message
+        .as_ref()
+        .ok_or_else(|| anyhow!("Interaction must have a message to grab a track"))?;
+
+    let player_data = match state.lavalink().get_player_context(guild_id) {
+        Some(player) => Some(player.get_player().await?),
+        None => None,
+    };
+    let Some((track, position)) =
+        player_data.and_then(|data| data.track.map(|track| (track, data.state.position)))
+    else {
+        return reply_ephemeral(&state, &interaction, "Nothing is playing right now.").await;
+    };
+
+    let guild_name = state
+        .cache
+        .guild(guild_id)
+        .map(|guild| guild.name().to_string());
+    let embed = build_grab_embed(
+        &track,
+        position,
+        guild_name.as_deref(),
+        guild_id,
+        message.channel_id,
+        message.id,
+    )?;
+
+    let sent = async {
+        let channel = state
+            .http
+            .create_private_channel(user_id)
+            .await?
+            .model()
+            .await?;
+        state
+            .http
+            .create_message(channel.id)
+            .embeds(&[embed.clone()])
+            .await?;
+        anyhow::Ok(())
+    }
+    .await;
+
+    let data = match sent {
+        Ok(()) => InteractionResponseDataBuilder::new()
+            .content(format!("📩 Sent **{}** to your DMs.", track.info.title)),
+        Err(e) if dms_closed(&e) => {
+            tracing::debug!(error = ?e, "Could not DM a grabbed track");
+            InteractionResponseDataBuilder::new()
+                .content("📩 Your DMs are closed, so here it is instead.")
+                .embeds([embed])
+        }
+        Err(e) => {
+            tracing::warn!(error = ?e, "Failed to DM a grabbed track");
+            return reply_ephemeral(
+                &state,
+                &interaction,
+                "Couldn't send you the track right now, please try again.",
+            )
+            .await;
+        }
+    };
+    state
+        .http
+        .interaction(interaction.application_id)
+        .create_response(
+            interaction.id,
+            &interaction.token,
+            &InteractionResponse {
+                kind: InteractionResponseType::ChannelMessageWithSource,
+                data: Some(data.flags(MessageFlags::EPHEMERAL).build()),
+            },
+        )
+        .await?;
+
+    Ok(())
+}
+
+/// Whether sending a DM failed because the user doesn't accept them, rather
+/// than for a reason worth reporting.
+fn dms_closed(error: &anyhow::Error) -> bool {
+    error
+        .downcast_ref::<twilight_http::Error>()
+        .is_some_and(|error| {
+            matches!(
+                error.kind(),
+                ErrorType::Response {
+                    error: ApiError::General(GeneralApiError {
+                        code: CANNOT_MESSAGE_USER,
+                        ..
+                    }),
+                    ..
+                }
+            )
+        })
+}
+
+fn build_grab_embed(
+    track: &TrackData,
+    position_ms: u64,
+    guild_name: Option<&str>,
+    guild_id: Id<GuildMarker>,
+    channel_id: Id<ChannelMarker>,
+    message_id: Id<MessageMarker>,
+) -> anyhow::Result<Embed> {
+    let position = humantime::format_duration(Duration::from_secs(position_ms / 1000));
+    let source = format!(
+        "**{}** in <#{}> · [Jump to message](https://discord.com/channels/{}/{}/{})",
+        guild_name.unwrap_or("A server"),
+        channel_id,
+        guild_id,
+        channel_id,
+        message_id
+    );
+
+    let mut embed = EmbedBuilder::new()
+        .title(format!("📩 {}", track.info.title))
+        .color(0x1DB954)
+        .field(EmbedFieldBuilder::new("Artist", track.info.author.clone()).inline())
+        .field(EmbedFieldBuilder::new("Length", format_length(track)).inline())
+        .field(EmbedFieldBuilder::new("Grabbed at", position.to_string()).inline())
+        .field(EmbedFieldBuilder::new("From", source))
+        .timestamp(Timestamp::from_secs(chrono::Utc::now().timestamp())?);
+    if let Some(uri) = &track.info.uri {
+        embed = embed.url(uri.clone()).description(format!("<{}>", uri));
+    }
+    if let Some(artwork_url) = &track.info.artwork_url {
+        embed = embed.thumbnail(ImageSource::url(artwork_url.clone())?);
+    }
+
+    Ok(embed.build())
+}
@@ -0,0 +1,45 @@
+use crate::command_handler::StateExt;
+use crate::commands::music::new_playlist_track;
+use crate::components::reply_ephemeral;
+use crate::utils::attachment::{UPLOAD_NOT_SAVED, is_upload};
+use anyhow::anyhow;
+use std::sync::Arc;
+use twilight_model::application::interaction::Interaction;
+
+/// Saves the playing track to the clicker's liked tracks. Anyone can click
+/// it, so the answer is only shown to them.
+pub async fn like_button_handler(
+    state: Arc<crate::state::State>,
+    interaction: Interaction,
+) -> anyhow::Result<()> {
+    let guild_id = interaction
+        .guild_id
+        .ok_or_else(|| anyhow!("Interaction must be in a guild to like a track"))?;
+    let user_id = interaction
+        .author_id()
+        .ok_or_else(|| anyhow!("Interaction is missing author information."))?;
+
+    let track = match state.lavalink().get_player_context(guild_id) {
+        Some(player) => player.get_player().await?.track,
+        None => None,
+    };
+    let Some(track) = track else {
+        return reply_ephemeral(&state, &interaction, "Nothing is playing right now.").await;
+    };
+    if is_upload(&track) {
+        return reply_ephemeral(&state, &interaction, UPLOAD_NOT_SAVED).await;
+    }
+
+    let content = if state
+        .storage
+        .like_track(user_id, guild_id, &new_playlist_track(&track))?
+    {
+        format!("❤️ Added **{}** to your liked tracks.", track.info.title)
+    } else {
+        format!(
+            "**{}** is already in your liked tracks. See them with `/liked list`.",
+            track.info.title
+        )
+    };
+    reply_ephemeral(&state, &interaction, content).await
+}
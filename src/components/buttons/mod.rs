@@ -1,24 +1,86 @@
+pub mod approval;
+pub mod grab;
+pub mod like;
 pub mod lyrics;
 pub mod pause;
+pub mod queue_find;
+pub mod session;
 pub mod skip;
+pub mod track_info;
+pub mod vote_next;
+pub mod wrapped;
 
 pub fn register_buttons(reg: &mut crate::components::ComponentRegistry) {
-    reg.register("skip", |state, interaction| {
+    reg.register("skip", |state, interaction, _data| {
         Box::pin(crate::components::buttons::skip::skip_button_handler(
             state,
             interaction,
         ))
     });
-    reg.register("pause", |state, interaction| {
+    reg.register("pause", |state, interaction, _data| {
         Box::pin(crate::components::buttons::pause::pause_button_handler(
             state,
             interaction,
         ))
     });
-    reg.register("lyrics", |state, interaction| {
+    reg.register("grab", |state, interaction, _data| {
+        Box::pin(crate::components::buttons::grab::grab_button_handler(
+            state,
+            interaction,
+        ))
+    });
+    reg.register("like", |state, interaction, _data| {
+        Box::pin(crate::components::buttons::like::like_button_handler(
+            state,
+            interaction,
+        ))
+    });
+    reg.register("lyrics", |state, interaction, _data| {
         Box::pin(crate::components::buttons::lyrics::lyrics_button_handler(
             state,
             interaction,
         ))
     });
+    reg.register("queue_find", |state, interaction, data| {
+        Box::pin(crate::components::buttons::queue_find::queue_find_button_handler(
+            state,
+            interaction,
+            data,
+        ))
+    });
+    reg.register("approval", |state, interaction, data| {
+        Box::pin(crate::components::buttons::approval::approval_button_handler(
+            state,
+            interaction,
+            data,
+        ))
+    });
+    reg.register("session_save", |state, interaction, data| {
+        Box::pin(crate::components::buttons::session::session_save_button_handler(
+            state,
+            interaction,
+            data,
+        ))
+    });
+    reg.register("trackinfo", |state, interaction, data| {
+        Box::pin(crate::components::buttons::track_info::track_info_button_handler(
+            state,
+            interaction,
+            data,
+        ))
+    });
+    reg.register("vote_next", |state, interaction, data| {
+        Box::pin(crate::components::buttons::vote_next::vote_next_button_handler(
+            state,
+            interaction,
+            data,
+        ))
+    });
+    reg.register("wrapped", |state, interaction, data| {
+        Box::pin(crate::components::buttons::wrapped::wrapped_button_handler(
+            state,
+            interaction,
+            data,
+        ))
+    });
 }
@@ -69,6 +69,24 @@ pub fn action_menu(pause_resume: bool) -> Component {
                     url: None,
                     sku_id: None,
                 }),
+                Component::Button(Button {
+                    label: Some("❤️ Like".to_string()),
+                    custom_id: Some("like".to_string()),
+                    style: twilight_model::channel::message::component::ButtonStyle::Secondary,
+                    emoji: None,
+                    disabled: false,
+                    url: None,
+                    sku_id: None,
+                }),
+                Component::Button(Button {
+                    label: Some("📩 Grab".to_string()),
+                    custom_id: Some("grab".to_string()),
+                    style: twilight_model::channel::message::component::ButtonStyle::Secondary,
+                    emoji: None,
+                    disabled: false,
+                    url: None,
+                    sku_id: None,
+                }),
                 Component::Button(Button {
                     label: Some("⏩ Skip".to_string()),
                     custom_id: Some("skip".to_string()),
@@ -102,6 +120,24 @@ pub fn action_menu(pause_resume: bool) -> Component {
                 url: None,
                 sku_id: None,
             }),
+            Component::Button(Button {
+                label: Some("❤️ Like".to_string()),
+                custom_id: Some("like".to_string()),
+                style: twilight_model::channel::message::component::ButtonStyle::Secondary,
+                emoji: None,
+                disabled: false,
+                url: None,
+                sku_id: None,
+            }),
+            Component::Button(Button {
+                label: Some("📩 Grab".to_string()),
+                custom_id: Some("grab".to_string()),
+                style: twilight_model::channel::message::component::ButtonStyle::Secondary,
+                emoji: None,
+                disabled: false,
+                url: None,
+                sku_id: None,
+            }),
             Component::Button(Button {
                 label: Some("⏩ Skip".to_string()),
                 custom_id: Some("skip".to_string()),
@@ -0,0 +1,102 @@
+use crate::command_handler::StateExt;
+use crate::components::reply_ephemeral;
+use crate::utils::queue_history::snapshot_queue;
+use crate::utils::track::{locate_queue_entry, track_key};
+use crate::utils::voice::listeners;
+use anyhow::anyhow;
+use std::sync::Arc;
+use twilight_model::{
+    application::interaction::{Interaction, message_component::MessageComponentInteractionData},
+    http::interaction::{InteractionResponse, InteractionResponseType},
+};
+use twilight_util::builder::InteractionResponseDataBuilder;
+
+/// Handles the jump / play next / remove buttons attached to `/queue find`
+/// results. The custom id is `queue_find:<action>:<position>:<track key>`;
+/// see [`locate_queue_entry`] for how the entry is found if the queue changed
+/// since the search. Only listeners in the bot's voice channel may use them.
+pub async fn queue_find_button_handler(
+    state: Arc<crate::state::State>,
+    interaction: Interaction,
+    data: MessageComponentInteractionData,
+) -> anyhow::Result<()> {
+    let http = state.http.clone();
+    let interaction_client = http.interaction(interaction.application_id);
+    let guild_id = interaction
+        .guild_id
+        .ok_or_else(|| anyhow!("Interaction must be in a guild to change the queue"))?;
+
+    let user_id = interaction
+        .author_id()
+        .ok_or_else(|| anyhow!("Interaction is missing author information."))?;
+
+    let parts: Vec<_> = data.custom_id.split(':').skip(1).collect();
+    let malformed = || anyhow!("Malformed queue_find custom id: {}", data.custom_id);
+    let [action, position, key] = parts[..] else {
+        return Err(malformed());
+    };
+    let position: usize = position.parse().map_err(|_| malformed())?;
+
+    if !listeners(&state.cache, guild_id).contains(&user_id) {
+        return reply_ephemeral(
+            &state,
+            &interaction,
+            "Only listeners in my voice channel can change the queue.",
+        )
+        .await;
+    }
+
+    let player = state
+        .lavalink()
+        .get_player_context(guild_id)
+        .ok_or_else(|| anyhow!("No player found for guild: {}", guild_id))?;
+    let queue = player.get_queue();
+    let mut tracks = queue.get_queue().await?;
+
+    let keys: Vec<String> = tracks.iter().map(|t| track_key(&t.track)).collect();
+    let content = match locate_queue_entry(&keys, position, key) {
+        None => "That track is no longer where it was in the queue. Search again.".to_string(),
+        Some(idx) => {
+            snapshot_queue(&state, guild_id).await?;
+            let title = tracks[idx].track.info.title.clone();
+            match action {
+                "jump" => {
+                    tracks.drain(..idx);
+                    queue.replace(tracks)?;
+                    player.skip()?;
+                    format!("⬆️ Jumped to `{}`.", title)
+                }
+                "next" => {
+                    if let Some(track) = tracks.remove(idx) {
+                        tracks.push_front(track);
+                    }
+                    queue.replace(tracks)?;
+                    format!("⏭️ `{}` will play next.", title)
+                }
+                "remove" => {
+                    tracks.remove(idx);
+                    queue.replace(tracks)?;
+                    format!("🗑️ Removed `{}` from the queue.", title)
+                }
+                other => return Err(anyhow!("Unknown queue_find action: {}", other)),
+            }
+        }
+    };
+
+    interaction_client
+        .create_response(
+            interaction.id,
+            &interaction.token,
+            &InteractionResponse {
+                kind: InteractionResponseType::ChannelMessageWithSource,
+                data: Some(
+                    InteractionResponseDataBuilder::new()
+                        .content(content)
+                        .build(),
+                ),
+            },
+        )
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,37 @@
+use crate::commands::music::save_session_playlist;
+use crate::components::reply_ephemeral;
+use anyhow::anyhow;
+use std::sync::Arc;
+use twilight_model::application::interaction::{
+    Interaction, message_component::MessageComponentInteractionData,
+};
+
+/// Saves the tracks of a finished session as a playlist for whoever clicks.
+/// The custom id is `session_save:<from>:<until>`, so the button keeps
+/// working after a restart.
+pub async fn session_save_button_handler(
+    state: Arc<crate::state::State>,
+    interaction: Interaction,
+    data: MessageComponentInteractionData,
+) -> anyhow::Result<()> {
+    let guild_id = interaction
+        .guild_id
+        .ok_or_else(|| anyhow!("Interaction must be in a guild to save a session"))?;
+    let user_id = interaction
+        .author_id()
+        .ok_or_else(|| anyhow!("Interaction is missing author information."))?;
+
+    let parts: Vec<_> = data.custom_id.split(':').skip(1).collect();
+    let malformed = || anyhow!("Malformed session custom id: {}", data.custom_id);
+    let [from, until] = parts[..] else {
+        return Err(malformed());
+    };
+    let from = from.parse().map_err(|_| malformed())?;
+    let until = until.parse().map_err(|_| malformed())?;
+
+    let content = match save_session_playlist(&state, user_id, guild_id, (from, until)).await {
+        Ok(content) => content,
+        Err(e) => e.to_string(),
+    };
+    reply_ephemeral(&state, &interaction, content).await
+}
@@ -1,4 +1,5 @@
 use crate::command_handler::{CommandResponseBuilder, StateExt};
+use crate::utils::queue_history::snapshot_queue;
 use std::sync::Arc;
 use twilight_model::{
     application::interaction::Interaction,
@@ -32,6 +33,7 @@ pub async fn skip_button_handler(
         .as_ref()
         .ok_or_else(|| anyhow::anyhow!("No track is currently playing in guild: {}", guild_id))?;
 
+    snapshot_queue(&state, guild_id).await?;
     player.skip()?;
 
     interaction_client
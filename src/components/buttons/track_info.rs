@@ -0,0 +1,125 @@
+use crate::commands::music::{Enqueued, enqueue};
+use crate::components::reply_ephemeral;
+use anyhow::anyhow;
+use lavalink_rs::prelude::TrackInQueue;
+use std::sync::Arc;
+use twilight_model::{
+    application::interaction::{Interaction, message_component::MessageComponentInteractionData},
+    http::interaction::{InteractionResponse, InteractionResponseType},
+};
+use twilight_util::builder::InteractionResponseDataBuilder;
+
+/// Handles the Queue / Play next / Cancel buttons of a `/trackinfo` preview.
+/// The custom id is `trackinfo:<action>:<preview key>`.
+pub async fn track_info_button_handler(
+    state: Arc<crate::state::State>,
+    interaction: Interaction,
+    data: MessageComponentInteractionData,
+) -> anyhow::Result<()> {
+    let guild_id = interaction
+        .guild_id
+        .ok_or_else(|| anyhow!("Interaction must be in a guild to queue tracks"))?;
+    let user_id = interaction
+        .author_id()
+        .ok_or_else(|| anyhow!("Interaction is missing author information."))?;
+
+    let mut parts = data.custom_id.splitn(3, ':').skip(1);
+    let (action, key) = parts
+        .next()
+        .zip(parts.next())
+        .ok_or_else(|| anyhow!("Malformed trackinfo custom id: {}", data.custom_id))?;
+
+    let requester = state
+        .track_previews
+        .lock()
+        .await
+        .get(key)
+        .map(|preview| preview.requester);
+    match requester {
+        None => {
+            return reply_ephemeral(&state, &interaction, "This preview has expired.").await;
+        }
+        Some(requester) if requester != user_id => {
+            return reply_ephemeral(
+                &state,
+                &interaction,
+                "Only the person who looked this up can use these buttons.",
+            )
+            .await;
+        }
+        Some(_) => {}
+    }
+
+    let play_next = match action {
+        "queue" => false,
+        "next" => true,
+        "cancel" => {
+            state.track_previews.lock().await.remove(key);
+            return update_preview(&state, &interaction, "✖️ Cancelled.").await;
+        }
+        other => return Err(anyhow!("Unknown trackinfo action: {}", other)),
+    };
+
+    if state.cache.voice_state(user_id, guild_id).is_none() {
+        return reply_ephemeral(
+            &state,
+            &interaction,
+            "You must be in a voice channel to queue tracks.",
+        )
+        .await;
+    }
+
+    let Some(preview) = state.track_previews.lock().await.remove(key) else {
+        return reply_ephemeral(&state, &interaction, "This preview has expired.").await;
+    };
+
+    let description = preview.describe();
+    let tracks = preview.tracks.into_iter().map(TrackInQueue::from).collect();
+    let text_channel_id = interaction.channel.as_ref().map(|channel| channel.id);
+    let permissions = interaction
+        .member
+        .as_ref()
+        .and_then(|member| member.permissions);
+    let content = match enqueue(
+        &state,
+        guild_id,
+        tracks,
+        user_id,
+        permissions,
+        text_channel_id,
+        play_next,
+    )
+    .await?
+    {
+        Enqueued::Queued(_) if play_next => format!("⏭️ {} will play next.", description),
+        Enqueued::Queued(_) => format!("`＋` Queued {}", description),
+        Enqueued::PendingApproval(reply) => reply,
+    };
+    update_preview(&state, &interaction, content).await
+}
+
+/// Replaces the preview's buttons with `content`, keeping the embed.
+async fn update_preview(
+    state: &crate::state::State,
+    interaction: &Interaction,
+    content: impl Into<String>,
+) -> anyhow::Result<()> {
+    state
+        .http
+        .interaction(interaction.application_id)
+        .create_response(
+            interaction.id,
+            &interaction.token,
+            &InteractionResponse {
+                kind: InteractionResponseType::UpdateMessage,
+                data: Some(
+                    InteractionResponseDataBuilder::new()
+                        .content(content)
+                        .components(Vec::new())
+                        .build(),
+                ),
+            },
+        )
+        .await?;
+    Ok(())
+}
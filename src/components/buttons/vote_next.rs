@@ -0,0 +1,74 @@
+use crate::commands::music::vote_poll_message;
+use crate::components::reply_ephemeral;
+use crate::utils::voice::listeners;
+use anyhow::anyhow;
+use std::sync::Arc;
+use twilight_model::{
+    application::interaction::{Interaction, message_component::MessageComponentInteractionData},
+    http::interaction::{InteractionResponse, InteractionResponseType},
+};
+use twilight_util::builder::InteractionResponseDataBuilder;
+
+/// Casts a vote in a `/vote-next` poll. The custom id is
+/// `vote_next:<poll>:<candidate>`.
+pub async fn vote_next_button_handler(
+    state: Arc<crate::state::State>,
+    interaction: Interaction,
+    data: MessageComponentInteractionData,
+) -> anyhow::Result<()> {
+    let guild_id = interaction
+        .guild_id
+        .ok_or_else(|| anyhow!("Interaction must be in a guild to vote"))?;
+    let user_id = interaction
+        .author_id()
+        .ok_or_else(|| anyhow!("Interaction is missing author information."))?;
+
+    let parts: Vec<_> = data.custom_id.split(':').skip(1).collect();
+    let malformed = || anyhow!("Malformed vote custom id: {}", data.custom_id);
+    let [poll_id, candidate] = parts[..] else {
+        return Err(malformed());
+    };
+    let poll_id: i64 = poll_id.parse().map_err(|_| malformed())?;
+    let candidate: usize = candidate.parse().map_err(|_| malformed())?;
+
+    if !listeners(&state.cache, guild_id).contains(&user_id) {
+        return reply_ephemeral(
+            &state,
+            &interaction,
+            "Only listeners in my voice channel can vote.",
+        )
+        .await;
+    }
+
+    let message = {
+        let mut polls = state.vote_polls.lock().await;
+        match polls.get_mut(&guild_id).filter(|poll| poll.id == poll_id) {
+            Some(poll) if poll.vote(user_id, candidate) => Some(vote_poll_message(poll)),
+            Some(_) => return Err(malformed()),
+            None => None,
+        }
+    };
+    let Some((embed, component)) = message else {
+        return reply_ephemeral(&state, &interaction, "This vote is already closed.").await;
+    };
+
+    state
+        .http
+        .interaction(interaction.application_id)
+        .create_response(
+            interaction.id,
+            &interaction.token,
+            &InteractionResponse {
+                kind: InteractionResponseType::UpdateMessage,
+                data: Some(
+                    InteractionResponseDataBuilder::new()
+                        .embeds([embed])
+                        .components([component])
+                        .build(),
+                ),
+            },
+        )
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,73 @@
+use crate::commands::music::wrapped_message;
+use crate::components::reply_ephemeral;
+use anyhow::anyhow;
+use std::sync::Arc;
+use twilight_model::{
+    application::interaction::{Interaction, message_component::MessageComponentInteractionData},
+    http::interaction::{InteractionResponse, InteractionResponseType},
+    id::Id,
+};
+use twilight_util::builder::InteractionResponseDataBuilder;
+
+/// Flips the pages of a `/wrapped` story. The custom id is
+/// `wrapped:<viewer>:<user>:<year>:<page>`.
+pub async fn wrapped_button_handler(
+    state: Arc<crate::state::State>,
+    interaction: Interaction,
+    data: MessageComponentInteractionData,
+) -> anyhow::Result<()> {
+    let guild_id = interaction
+        .guild_id
+        .ok_or_else(|| anyhow!("Interaction must be in a guild to show a wrapped"))?;
+    let clicker_id = interaction
+        .author_id()
+        .ok_or_else(|| anyhow!("Interaction is missing author information."))?;
+
+    let parts: Vec<_> = data.custom_id.split(':').skip(1).collect();
+    let [viewer, user, year, page] = parts[..] else {
+        return Err(anyhow!("Malformed wrapped custom id: {}", data.custom_id));
+    };
+    let malformed = || anyhow!("Malformed wrapped custom id: {}", data.custom_id);
+    let viewer_id = viewer
+        .parse()
+        .ok()
+        .and_then(Id::new_checked)
+        .ok_or_else(malformed)?;
+    let user_id = user
+        .parse()
+        .ok()
+        .and_then(Id::new_checked)
+        .ok_or_else(malformed)?;
+    let year = year.parse().map_err(|_| malformed())?;
+    let page = page.parse().map_err(|_| malformed())?;
+
+    if clicker_id != viewer_id {
+        return reply_ephemeral(
+            &state,
+            &interaction,
+            "Only the person who ran `/wrapped` can turn its pages. Run it yourself to see yours!",
+        )
+        .await;
+    }
+
+    let (embed, component) = wrapped_message(&state, guild_id, viewer_id, user_id, year, page)?;
+    state
+        .http
+        .interaction(interaction.application_id)
+        .create_response(
+            interaction.id,
+            &interaction.token,
+            &InteractionResponse {
+                kind: InteractionResponseType::UpdateMessage,
+                data: Some(
+                    InteractionResponseDataBuilder::new()
+                        .embeds([embed])
+                        .components([component])
+                        .build(),
+                ),
+            },
+        )
+        .await?;
+
+    Ok(())
+}
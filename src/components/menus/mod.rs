@@ -0,0 +1,11 @@
+pub mod radio;
+
+pub fn register_menus(reg: &mut crate::components::ComponentRegistry) {
+    reg.register("radio", |state, interaction, data| {
+        Box::pin(crate::components::menus::radio::radio_menu_handler(
+            state,
+            interaction,
+            data,
+        ))
+    });
+}
@@ -0,0 +1,78 @@
+use crate::commands::music::play_station;
+use crate::components::reply_ephemeral;
+use anyhow::anyhow;
+use std::sync::Arc;
+use twilight_model::{
+    application::interaction::{Interaction, message_component::MessageComponentInteractionData},
+    http::interaction::{InteractionResponse, InteractionResponseType},
+};
+use twilight_util::builder::InteractionResponseDataBuilder;
+
+/// Handles the genre menus of the `/radio` station browser. The selected
+/// value is the station id.
+pub async fn radio_menu_handler(
+    state: Arc<crate::state::State>,
+    interaction: Interaction,
+    data: MessageComponentInteractionData,
+) -> anyhow::Result<()> {
+    let guild_id = interaction
+        .guild_id
+        .ok_or_else(|| anyhow!("Interaction must be in a guild to play the radio"))?;
+    let user_id = interaction
+        .author_id()
+        .ok_or_else(|| anyhow!("Interaction is missing author information."))?;
+
+    let Some(station) = data
+        .values
+        .first()
+        .and_then(|id| state.radio_stations.find(id))
+        .cloned()
+    else {
+        return reply_ephemeral(&state, &interaction, "That station is no longer available.").await;
+    };
+
+    if state.cache.voice_state(user_id, guild_id).is_none() {
+        return reply_ephemeral(
+            &state,
+            &interaction,
+            "You must be in a voice channel to play the radio.",
+        )
+        .await;
+    }
+
+    let text_channel_id = interaction.channel.as_ref().map(|channel| channel.id);
+    let permissions = interaction
+        .member
+        .as_ref()
+        .and_then(|member| member.permissions);
+    let content = match play_station(
+        &state,
+        guild_id,
+        text_channel_id,
+        (user_id, permissions),
+        station,
+    )
+    .await
+    {
+        Ok(content) => content,
+        Err(e) => return reply_ephemeral(&state, &interaction, e.to_string()).await,
+    };
+
+    state
+        .http
+        .interaction(interaction.application_id)
+        .create_response(
+            interaction.id,
+            &interaction.token,
+            &InteractionResponse {
+                kind: InteractionResponseType::ChannelMessageWithSource,
+                data: Some(
+                    InteractionResponseDataBuilder::new()
+                        .content(content)
+                        .build(),
+                ),
+            },
+        )
+        .await?;
+    Ok(())
+}
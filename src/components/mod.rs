@@ -1,16 +1,21 @@
 use crate::state::State;
 use std::collections::HashMap;
 use std::sync::Arc;
-use twilight_model::application::interaction::{
-    Interaction, message_component::MessageComponentInteractionData,
+use twilight_model::{
+    application::interaction::{Interaction, message_component::MessageComponentInteractionData},
+    channel::message::MessageFlags,
+    http::interaction::{InteractionResponse, InteractionResponseType},
 };
+use twilight_util::builder::InteractionResponseDataBuilder;
 
 pub mod buttons;
+pub mod menus;
 
 pub type ComponentHandlerFn =
     fn(
         Arc<State>,
         Interaction,
+        MessageComponentInteractionData,
     ) -> std::pin::Pin<Box<dyn std::future::Future<Output = anyhow::Result<()>> + Send>>;
 
 pub struct ComponentRegistry {
@@ -40,8 +45,14 @@ impl ComponentRegistry {
         interaction: Interaction,
         data: MessageComponentInteractionData,
     ) -> anyhow::Result<()> {
-        if let Some(handler) = self.handlers.get(&data.custom_id) {
-            (handler)(state, interaction).await
+        // Custom ids may carry arguments after a `:`, e.g. `queue_find:remove:<key>`.
+        let key = data
+            .custom_id
+            .split_once(':')
+            .map_or(data.custom_id.as_str(), |(key, _)| key);
+
+        if let Some(handler) = self.handlers.get(key) {
+            (handler)(state, interaction, data).await
         } else {
             tracing::warn!("No component handler for custom_id: {}", data.custom_id);
             Ok(())
@@ -49,8 +60,35 @@ impl ComponentRegistry {
     }
 }
 
+/// Answers an interaction with a message only its user can see.
+pub async fn reply_ephemeral(
+    state: &State,
+    interaction: &Interaction,
+    content: impl Into<String>,
+) -> anyhow::Result<()> {
+    state
+        .http
+        .interaction(interaction.application_id)
+        .create_response(
+            interaction.id,
+            &interaction.token,
+            &InteractionResponse {
+                kind: InteractionResponseType::ChannelMessageWithSource,
+                data: Some(
+                    InteractionResponseDataBuilder::new()
+                        .content(content)
+                        .flags(MessageFlags::EPHEMERAL)
+                        .build(),
+                ),
+            },
+        )
+        .await?;
+    Ok(())
+}
+
 pub fn build_registry() -> ComponentRegistry {
     let mut reg = ComponentRegistry::new();
     crate::components::buttons::register_buttons(&mut reg);
+    crate::components::menus::register_menus(&mut reg);
     reg
 }
@@ -1,4 +1,5 @@
 use std::env;
+use std::path::PathBuf;
 
 #[derive(Clone, Debug)]
 pub struct Config {
@@ -7,21 +8,45 @@ pub struct Config {
     pub lavalink_host: String,
     pub lavalink_port: u16,
     pub lavalink_password: String,
+    /// Directory for files the bot keeps between restarts, such as clips.
+    pub data_dir: PathBuf,
+    /// JSON file listing the stations offered by `/radio`.
+    pub radio_stations_path: PathBuf,
+    /// Folder of local music served by `/library`, if any.
+    pub library_dir: Option<PathBuf>,
 }
 
 impl Config {
+    /// The SQLite database, kept inside the data directory.
+    pub fn database_path(&self) -> PathBuf {
+        self.data_dir.join("chimera.db")
+    }
+
     pub fn from_env() -> anyhow::Result<Self> {
         let configured_prefix = env::var("PREFIX").unwrap_or_else(|_| ";".to_string());
         let token = env::var("DISCORD_TOKEN")?;
         let lavalink_host = env::var("LAVALINK_HOST")?;
         let lavalink_port = env::var("LAVALINK_PORT")?.parse()?;
         let lavalink_password = env::var("LAVALINK_PASSWORD")?;
+        let data_dir = env::var("DATA_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| PathBuf::from("data"));
+        let radio_stations_path = env::var("RADIO_STATIONS")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| PathBuf::from("stations.json"));
+        let library_dir = env::var("LIBRARY_DIR")
+            .ok()
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from);
         Ok(Self {
             configured_prefix,
             token,
             lavalink_host,
             lavalink_port,
             lavalink_password,
+            data_dir,
+            radio_stations_path,
+            library_dir,
         })
     }
 }
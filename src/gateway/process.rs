@@ -1,7 +1,7 @@
 use std::sync::Arc;
 
 use twilight_gateway::Event;
-use twilight_model::application::interaction::InteractionData;
+use twilight_model::application::interaction::{InteractionData, InteractionType};
 
 use crate::components;
 use crate::state::State;
@@ -16,6 +16,19 @@ pub async fn process(event: Event, state: Arc<State>) -> anyhow::Result<()> {
             let mut interaction = interaction_payload.0;
 
             match std::mem::take(&mut interaction.data) {
+                Some(InteractionData::ApplicationCommand(data))
+                    if interaction.kind == InteractionType::ApplicationCommandAutocomplete =>
+                {
+                    if let Err(e) = crate::command_handler::autocomplete_handler(
+                        interaction,
+                        *data,
+                        state.clone(),
+                    )
+                    .await
+                    {
+                        tracing::error!(error = ?e, "Error handling autocomplete");
+                    }
+                }
                 Some(InteractionData::ApplicationCommand(data)) => {
                     if let Err(e) =
                         crate::command_handler::slash_handler(interaction, *data, state.clone())
@@ -43,6 +56,17 @@ pub async fn process(event: Event, state: Arc<State>) -> anyhow::Result<()> {
                 return Ok(());
             }
 
+            // During a quiz every message in its channel is a guess, even
+            // one that happens to start with the prefix.
+            match crate::commands::music::quiz_guess(&state, &message).await {
+                Ok(true) => return Ok(()),
+                Ok(false) => {}
+                Err(e) => {
+                    tracing::error!(error = ?e, "Error handling quiz guess");
+                    return Ok(());
+                }
+            }
+
             if let Err(e) = crate::command_handler::prefix_handler(
                 message,
                 &state.as_ref().config.configured_prefix,
@@ -53,6 +77,23 @@ pub async fn process(event: Event, state: Arc<State>) -> anyhow::Result<()> {
                 tracing::error!(error = ?e, "Error handling prefix command");
             }
         }
+        Event::VoiceStateUpdate(voice_state) => {
+            // Someone disconnected the bot, or moved it nowhere. `leave()`
+            // drops the songbird call first, so teardowns it started itself
+            // are not handled twice.
+            let Some(guild_id) = voice_state.guild_id else {
+                return Ok(());
+            };
+            let is_bot =
+                state.cache.current_user().map(|user| user.id) == Some(voice_state.user_id);
+            if is_bot
+                && voice_state.channel_id.is_none()
+                && state.songbird.get(guild_id).is_some()
+                && let Err(e) = crate::commands::music::leave(state.clone(), guild_id).await
+            {
+                tracing::error!(error = ?e, "Error tearing down after a voice disconnect");
+            }
+        }
         _ => {}
     }
     Ok(())
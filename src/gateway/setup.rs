@@ -19,10 +19,14 @@ use twilight_model::id::Id;
 use twilight_model::id::marker::UserMarker;
 
 use crate::commands::COMMANDS;
+use crate::commands::music::{EXPIRY_SWEEP_INTERVAL, expire_requests};
 use crate::config::Config;
 use crate::gateway::runner;
 use crate::lavalink_events;
-use crate::state::State;
+use crate::state::{LavalinkData, State};
+use crate::storage::Storage;
+use crate::utils::library::rescan_library;
+use crate::utils::radio::RadioDirectory;
 
 #[derive(Debug, Clone)]
 pub struct ShardInfo {
@@ -98,6 +102,9 @@ async fn init_lavalink_client(
     let lavalink_events_handlers = LavalinkEventsModel::Events {
         ready: Some(lavalink_events::ready_event),
         raw: Some(lavalink_events::raw_event),
+        player_update: Some(lavalink_events::player_update_event),
+        track_start: Some(lavalink_events::track_start_event),
+        track_end: Some(lavalink_events::track_end_event),
         ..Default::default()
     };
 
@@ -111,10 +118,11 @@ async fn init_lavalink_client(
         session_id: None,
     };
 
-    let client = LavalinkClient::new(
+    let client = LavalinkClient::new_with_data(
         lavalink_events_handlers,
         vec![node_local],
         NodeDistributionStrategy::round_robin(),
+        Arc::new(LavalinkData::default()),
     )
     .await;
     Ok(Arc::new(client))
@@ -134,7 +142,13 @@ fn init_app_state(
     lavalink: Arc<LavalinkClient>,
     songbird: Arc<Songbird>,
     config: Config,
+    storage: Storage,
 ) -> Arc<State> {
+    let radio_stations = RadioDirectory::load(&config.radio_stations_path).unwrap_or_else(|e| {
+        tracing::error!("Failed to load radio stations: {:?}", e);
+        RadioDirectory::default()
+    });
+
     Arc::new(crate::state::State::new(
         http,
         lavalink,
@@ -146,6 +160,8 @@ fn init_app_state(
                 .build()
                 .expect("Failed to create reqwest client")
         ),
+        radio_stations,
+        storage,
     ))
 }
 
@@ -220,12 +236,47 @@ pub async fn initialize_and_run_bot() -> anyhow::Result<()> {
     .await
     .context("Failed to initialize Songbird client")?;
 
+    let storage = Storage::open(&config.database_path()).context("Failed to open storage")?;
+    // Players don't survive a restart, so nothing logged as playing still is.
+    storage
+        .abandon_open_plays()
+        .context("Failed to close the play log")?;
+
     let app_state = init_app_state(
         http_client.clone(),
         lavalink_client,
         songbird_client,
         config.clone(),
+        storage,
     );
+    app_state
+        .lavalink
+        .data::<LavalinkData>()
+        .context("Failed to get the Lavalink client data")?
+        .set_state(&app_state);
+
+    if app_state.config.library_dir.is_some() {
+        let state = app_state.clone();
+        tokio::spawn(async move {
+            match rescan_library(&state).await {
+                Ok(count) => tracing::info!("Indexed {} library tracks", count),
+                Err(e) => tracing::error!("Failed to scan the music library: {:?}", e),
+            }
+        });
+    }
+
+    // The first tick is immediate, which also clears up requests that
+    // expired while the bot was down.
+    let app_state_for_expiry_task = app_state.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(EXPIRY_SWEEP_INTERVAL);
+        loop {
+            interval.tick().await;
+            if let Err(e) = expire_requests(&app_state_for_expiry_task).await {
+                tracing::warn!(error = ?e, "Failed to expire song requests");
+            }
+        }
+    });
 
     let bot = Bot::new(initial_shard, app_state.clone(), shard_info_tx);
 
@@ -1,5 +1,22 @@
-use lavalink_rs::{client::LavalinkClient, hook, model::events};
+use std::sync::Arc;
+use std::time::Instant;
+
+use lavalink_rs::{
+    client::LavalinkClient,
+    hook,
+    model::{GuildId, events},
+};
 use tracing::debug;
+use twilight_model::id::{Id, marker::GuildMarker};
+
+use crate::commands::music::close_vote_poll_on_start;
+use crate::state::{LavalinkData, PlayerData, State};
+use crate::storage::NewPlay;
+use crate::utils::attachment::is_upload;
+use crate::utils::podcast::PodcastProgress;
+use crate::utils::radio::fetch_stream_title;
+use crate::utils::track::{is_interruption, is_new_play, podcast_episode_id, requester_id};
+use crate::utils::voice::listeners;
 
 #[hook]
 pub async fn ready_event(client: LavalinkClient, session_id: String, event: &events::Ready) {
@@ -13,3 +30,206 @@ pub async fn raw_event(_: LavalinkClient, session_id: String, event: &serde_json
         debug!("{:?} -> {:?}", session_id, event);
     }
 }
+
+/// The bot state, once it has been built.
+fn bot_state(client: &LavalinkClient) -> Option<Arc<State>> {
+    client.data::<LavalinkData>().ok()?.state()
+}
+
+/// What the guild's player was created with, while it is connected.
+fn player_data(client: &LavalinkClient, guild_id: GuildId) -> Option<Arc<PlayerData>> {
+    client
+        .get_player_context(guild_id)?
+        .data::<PlayerData>()
+        .ok()
+}
+
+#[hook]
+pub async fn player_update_event(
+    client: LavalinkClient,
+    _session_id: String,
+    event: &events::PlayerUpdate,
+) {
+    let Some(state) = bot_state(&client) else {
+        return;
+    };
+    let Some(data) = player_data(&client, event.guild_id) else {
+        return;
+    };
+    let guild_id = Id::new(event.guild_id.0);
+
+    if let Err(e) = refresh_radio_title(&state, &data, guild_id).await {
+        tracing::warn!(error = ?e, "Failed to refresh radio title");
+    }
+    if let Err(e) = save_podcast_position(&state, &data, guild_id, event.state.position).await {
+        tracing::warn!(error = ?e, "Failed to save podcast position");
+    }
+}
+
+/// Logs the play and who heard it for `/stats` and `/wrapped`.
+#[hook]
+pub async fn track_start_event(
+    client: LavalinkClient,
+    _session_id: String,
+    event: &events::TrackStart,
+) {
+    let Some(state) = bot_state(&client) else {
+        return;
+    };
+    let track = &event.track;
+    if let Some(data) = player_data(&client, event.guild_id) {
+        *data.now_playing.lock().await = Some(track.clone());
+        *data.podcast_progress.lock().await = PodcastProgress::default();
+    }
+    let guild_id = Id::new(event.guild_id.0);
+    if !is_interruption(track) {
+        match close_vote_poll_on_start(&state, guild_id, track).await {
+            // It plays again right after the winner, and is logged then.
+            Ok(true) => return,
+            Ok(false) => {}
+            Err(e) => tracing::warn!(error = ?e, "Failed to close the vote"),
+        }
+    }
+    if !is_new_play(track) {
+        return;
+    }
+    let listeners = listeners(&state.cache, guild_id);
+    // Uploads play from links that expire, so they are logged without them.
+    let upload = is_upload(track);
+    let play = NewPlay {
+        encoded: (!upload).then_some(track.encoded.as_str()),
+        requester_id: requester_id(track),
+        title: &track.info.title,
+        author: &track.info.author,
+        uri: track.info.uri.as_deref().filter(|_| !upload),
+        length_ms: track.info.length,
+        is_stream: track.info.is_stream,
+        listeners: &listeners,
+    };
+    if let Err(e) = state.storage.log_play_start(guild_id, &play) {
+        tracing::warn!(error = ?e, "Failed to log track start");
+    }
+}
+
+#[hook]
+pub async fn track_end_event(client: LavalinkClient, _session_id: String, event: &events::TrackEnd) {
+    let Some(state) = bot_state(&client) else {
+        return;
+    };
+    let guild_id = Id::new(event.guild_id.0);
+    if let Some(data) = player_data(&client, event.guild_id) {
+        let track = data.now_playing.lock().await.take();
+        let episode_id = track.as_ref().and_then(podcast_episode_id);
+        let position_ms = data.podcast_progress.lock().await.position_ms();
+        if let (Some(episode_id), Some(position_ms)) = (episode_id, position_ms)
+            && let Err(e) = state
+                .storage
+                .save_episode_position(guild_id, episode_id, position_ms)
+        {
+            tracing::warn!(error = ?e, "Failed to save podcast position");
+        }
+    }
+    if let Err(e) = state.storage.log_play_end(guild_id) {
+        tracing::warn!(error = ?e, "Failed to log track end");
+    }
+    // Skips, clips and the like don't settle a vote; the next track starting
+    // after this one finished does.
+    let finished = matches!(
+        event.reason,
+        events::TrackEndReason::Finished | events::TrackEndReason::LoadFailed
+    );
+    if finished
+        && !is_interruption(&event.track)
+        && let Some(poll) = state.vote_polls.lock().await.get_mut(&guild_id)
+    {
+        poll.track_ended = true;
+    }
+}
+
+/// Records how far into a podcast episode the guild is, so `/podcast play`
+/// can resume it later. Saved every so often, and once more when it ends.
+async fn save_podcast_position(
+    state: &State,
+    data: &PlayerData,
+    guild_id: Id<GuildMarker>,
+    position_ms: u64,
+) -> anyhow::Result<()> {
+    if !data
+        .podcast_progress
+        .lock()
+        .await
+        .report(position_ms, Instant::now())
+    {
+        return Ok(());
+    }
+    let episode_id = data
+        .now_playing
+        .lock()
+        .await
+        .as_ref()
+        .and_then(podcast_episode_id)
+        .map(str::to_owned);
+    if let Some(episode_id) = episode_id {
+        state
+            .storage
+            .save_episode_position(guild_id, &episode_id, position_ms)?;
+    }
+    Ok(())
+}
+
+/// Posts the station's new song title when it changes, and forgets the radio
+/// session once something else is playing.
+async fn refresh_radio_title(
+    state: &State,
+    data: &PlayerData,
+    guild_id: Id<GuildMarker>,
+) -> anyhow::Result<()> {
+    let station = {
+        let mut sessions = state.radio_sessions.lock().await;
+        let Some(session) = sessions.get_mut(&guild_id) else {
+            return Ok(());
+        };
+        if !session.needs_refresh() {
+            return Ok(());
+        }
+        session.last_checked = Some(std::time::Instant::now());
+        session.station.clone()
+    };
+
+    let still_tuned_in = data
+        .now_playing
+        .lock()
+        .await
+        .as_ref()
+        .and_then(|track| track.user_data.as_ref())
+        .and_then(|user_data| user_data.get("station_id"))
+        .and_then(|id| id.as_str())
+        == Some(station.id.as_str());
+    if !still_tuned_in {
+        state.radio_sessions.lock().await.remove(&guild_id);
+        return Ok(());
+    }
+
+    let Some(title) = fetch_stream_title(&state.reqwest, &station.url).await? else {
+        return Ok(());
+    };
+
+    {
+        let mut sessions = state.radio_sessions.lock().await;
+        let Some(session) = sessions.get_mut(&guild_id) else {
+            return Ok(());
+        };
+        if session.last_title.as_deref() == Some(title.as_str()) {
+            return Ok(());
+        }
+        session.last_title = Some(title.clone());
+    }
+
+    state
+        .http
+        .create_message(data.text_channel_id)
+        .content(&format!("📻 Now on **{}**: {}", station.name, title))
+        .await?;
+
+    Ok(())
+}
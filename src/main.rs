@@ -11,6 +11,7 @@ pub mod gateway;
 pub mod lavalink_events;
 pub mod prefix_parser;
 pub mod state;
+pub mod storage;
 pub mod utils;
 
 #[tokio::main]
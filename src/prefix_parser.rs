@@ -15,6 +15,21 @@ impl<'a> Arguments<'a> {
     pub fn remainder(&self) -> &'a str {
         self.remaining_slice_for_remainder.trim_start()
     }
+
+    /// Like `next`, but an argument in double quotes may contain spaces and
+    /// comes back without its quotes.
+    pub fn next_quoted(&mut self) -> Option<&'a str> {
+        let remainder = self.remainder();
+        let Some((quoted, rest)) = remainder
+            .strip_prefix('"')
+            .and_then(|inner| inner.split_once('"'))
+        else {
+            return self.next();
+        };
+        self.current_iter = rest.split_whitespace();
+        self.remaining_slice_for_remainder = rest;
+        Some(quoted)
+    }
 }
 
 impl<'a> Iterator for Arguments<'a> {
@@ -79,10 +94,33 @@ pub fn parse<'a>(message: &'a str, prefix: &str) -> Option<ParsedCommand<'a>> {
     Some(ParsedCommand { command, args_part })
 }
 
+/// The id in a user, role or channel mention such as `<@!123>`, or a bare id.
+pub fn parse_mention(arg: &str) -> Option<u64> {
+    let inner = match arg.strip_prefix('<') {
+        Some(rest) => rest
+            .strip_suffix('>')?
+            .trim_start_matches(['@', '#'])
+            .trim_start_matches(['!', '&']),
+        None => arg,
+    };
+    inner.parse().ok()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_parse_mention() {
+        assert_eq!(parse_mention("<@123>"), Some(123));
+        assert_eq!(parse_mention("<@!123>"), Some(123));
+        assert_eq!(parse_mention("<@&456>"), Some(456));
+        assert_eq!(parse_mention("<#789>"), Some(789));
+        assert_eq!(parse_mention("123"), Some(123));
+        assert_eq!(parse_mention("<@123"), None);
+        assert_eq!(parse_mention("@someone"), None);
+    }
+
     #[test]
     fn test_parse_simple_command() {
         let result = parse("!echo hello world", "!").unwrap();
@@ -220,6 +258,29 @@ mod tests {
         assert_eq!(args.next(), None);
     }
 
+    #[test]
+    fn test_quoted_argument_keeps_its_spaces() {
+        let parsed = parse("!playlist add \"Friday raid mix\" never gonna", "!").unwrap();
+        let mut args = parsed.arguments();
+        assert_eq!(args.next(), Some("add"));
+        assert_eq!(args.next_quoted(), Some("Friday raid mix"));
+        assert_eq!(args.remainder(), "never gonna");
+        assert_eq!(args.next(), Some("never"));
+    }
+
+    #[test]
+    fn test_unquoted_or_unclosed_argument_is_one_word() {
+        let parsed = parse("!cmd mix 2", "!").unwrap();
+        let mut args = parsed.arguments();
+        assert_eq!(args.next_quoted(), Some("mix"));
+        assert_eq!(args.remainder(), "2");
+
+        let parsed = parse("!cmd \"mix 2", "!").unwrap();
+        let mut args = parsed.arguments();
+        assert_eq!(args.next_quoted(), Some("\"mix"));
+        assert_eq!(args.remainder(), "2");
+    }
+
     #[test]
     fn test_args_str_with_internal_multiple_spaces() {
         let parsed = parse("!cmd  first   second  ", "!").unwrap();
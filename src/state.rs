@@ -1,11 +1,25 @@
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock, Weak};
 
+use lavalink_rs::model::track::TrackData;
 use songbird::Songbird;
 use twilight_cache_inmemory::{InMemoryCache, ResourceType};
 use twilight_http::Client;
+use twilight_model::id::{
+    Id,
+    marker::{ChannelMarker, GuildMarker},
+};
 
 use crate::command_handler::{HasHttpClient, StateExt};
 use crate::config::Config;
+use crate::storage::Storage;
+use crate::utils::library::LibraryIndex;
+use crate::utils::podcast::PodcastProgress;
+use crate::utils::queue_history::QueueHistory;
+use crate::utils::quiz::QuizGame;
+use crate::utils::radio::{RadioDirectory, RadioSession};
+use crate::utils::track_preview::TrackPreviews;
+use crate::utils::vote::VotePoll;
 use tokio::sync::Mutex;
 
 #[derive(Clone)]
@@ -17,6 +31,46 @@ pub struct State {
     pub config: Config,
     pub latency_ms: Arc<Mutex<Option<u128>>>,
     pub reqwest: Arc<reqwest::Client>,
+    pub queue_history: Arc<Mutex<QueueHistory>>,
+    pub track_previews: Arc<Mutex<TrackPreviews>>,
+    pub radio_stations: Arc<RadioDirectory>,
+    pub radio_sessions: Arc<Mutex<HashMap<Id<GuildMarker>, RadioSession>>>,
+    pub quiz_games: Arc<Mutex<HashMap<Id<GuildMarker>, QuizGame>>>,
+    pub vote_polls: Arc<Mutex<HashMap<Id<GuildMarker>, VotePoll>>>,
+    pub storage: Arc<Storage>,
+    pub library: Arc<Mutex<LibraryIndex>>,
+}
+
+/// Attached to every Lavalink player: the listening session it was created for.
+pub struct PlayerData {
+    /// The voice channel the player was created for.
+    pub channel_id: Id<ChannelMarker>,
+    /// Where the session recap goes.
+    pub text_channel_id: Id<ChannelMarker>,
+    /// When the bot joined, as a Unix timestamp in milliseconds.
+    pub started_at: i64,
+    /// The track playing now, as last reported by Lavalink.
+    pub now_playing: Mutex<Option<TrackData>>,
+    /// How far into `now_playing` the player is, if it is a podcast episode.
+    pub podcast_progress: Mutex<PodcastProgress>,
+}
+
+/// Attached to the Lavalink client so event hooks can reach the bot state.
+/// The state owns the client, so this holds it weakly, and only once the
+/// state has been built.
+#[derive(Default)]
+pub struct LavalinkData {
+    state: OnceLock<Weak<State>>,
+}
+
+impl LavalinkData {
+    pub fn set_state(&self, state: &Arc<State>) {
+        let _ = self.state.set(Arc::downgrade(state));
+    }
+
+    pub fn state(&self) -> Option<Arc<State>> {
+        self.state.get().and_then(Weak::upgrade)
+    }
 }
 
 impl HasHttpClient for State {
@@ -59,9 +113,14 @@ impl State {
         songbird: Arc<Songbird>,
         config: Config,
         reqwest: Arc<reqwest::Client>,
+        radio_stations: RadioDirectory,
+        storage: Storage,
     ) -> Self {
         const CACHE_EVENTS: ResourceType = ResourceType::GUILD
-            .union(ResourceType::VOICE_STATE);
+            .union(ResourceType::VOICE_STATE)
+            .union(ResourceType::USER_CURRENT)
+            // Members are cached from interactions and messages, for their roles.
+            .union(ResourceType::MEMBER);
 
         Self {
             http,
@@ -71,6 +130,14 @@ impl State {
             config,
             latency_ms: Arc::new(Mutex::new(None)),
             reqwest,
+            queue_history: Arc::new(Mutex::new(QueueHistory::default())),
+            track_previews: Arc::new(Mutex::new(TrackPreviews::default())),
+            radio_stations: Arc::new(radio_stations),
+            radio_sessions: Arc::new(Mutex::new(HashMap::new())),
+            quiz_games: Arc::new(Mutex::new(HashMap::new())),
+            vote_polls: Arc::new(Mutex::new(HashMap::new())),
+            storage: Arc::new(storage),
+            library: Arc::new(Mutex::new(LibraryIndex::default())),
         }
     }
 }
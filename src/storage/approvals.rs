@@ -0,0 +1,339 @@
+use anyhow::Result;
+use rusqlite::{OptionalExtension, Row, params};
+use twilight_model::id::{
+    Id,
+    marker::{ChannelMarker, GuildMarker, MessageMarker, RoleMarker, UserMarker},
+};
+
+use super::{NewPlaylistTrack, Storage};
+
+/// How a guild that vets requests is set up.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApprovalSettings {
+    /// Where the approval cards go.
+    pub dj_channel_id: Id<ChannelMarker>,
+    /// Members with this role skip approval and decide on requests.
+    pub dj_role_id: Option<Id<RoleMarker>>,
+    pub expire_after_ms: i64,
+}
+
+/// A track waiting for a DJ's decision.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PendingRequest {
+    pub id: i64,
+    pub guild_id: Id<GuildMarker>,
+    pub requester_id: Id<UserMarker>,
+    /// Where to play it once approved.
+    pub voice_channel_id: Id<ChannelMarker>,
+    /// Where it was requested.
+    pub text_channel_id: Option<Id<ChannelMarker>>,
+    /// The Lavalink-encoded track.
+    pub encoded: String,
+    pub title: String,
+    pub author: String,
+    pub uri: Option<String>,
+    pub length_ms: u64,
+    pub is_stream: bool,
+    /// The approval card, once posted.
+    pub card: Option<(Id<ChannelMarker>, Id<MessageMarker>)>,
+    /// Unix timestamps in milliseconds.
+    pub requested_at: i64,
+    pub expires_at: i64,
+}
+
+const REQUEST_COLUMNS: &str = "id, guild_id, requester_id, voice_channel_id, text_channel_id,
+     encoded, title, author, uri, length_ms, is_stream, card_channel_id, card_message_id,
+     requested_at, expires_at";
+
+fn request_from_row(row: &Row) -> rusqlite::Result<PendingRequest> {
+    let card = match (
+        row.get::<_, Option<u64>>(11)?,
+        row.get::<_, Option<u64>>(12)?,
+    ) {
+        (Some(channel_id), Some(message_id)) => Some((Id::new(channel_id), Id::new(message_id))),
+        _ => None,
+    };
+    Ok(PendingRequest {
+        id: row.get(0)?,
+        guild_id: Id::new(row.get(1)?),
+        requester_id: Id::new(row.get(2)?),
+        voice_channel_id: Id::new(row.get(3)?),
+        text_channel_id: row.get::<_, Option<u64>>(4)?.map(Id::new),
+        encoded: row.get(5)?,
+        title: row.get(6)?,
+        author: row.get(7)?,
+        uri: row.get(8)?,
+        length_ms: row.get(9)?,
+        is_stream: row.get(10)?,
+        card,
+        requested_at: row.get(13)?,
+        expires_at: row.get(14)?,
+    })
+}
+
+impl Storage {
+    /// Turns approval mode on, or updates its settings.
+    pub fn set_approval_settings(
+        &self,
+        guild_id: Id<GuildMarker>,
+        settings: &ApprovalSettings,
+    ) -> Result<()> {
+        self.conn().execute(
+            "INSERT INTO approval_settings (guild_id, dj_channel_id, dj_role_id, expire_after_ms)
+             VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT (guild_id) DO UPDATE
+             SET dj_channel_id = ?2, dj_role_id = ?3, expire_after_ms = ?4",
+            params![
+                guild_id.get(),
+                settings.dj_channel_id.get(),
+                settings.dj_role_id.map(Id::get),
+                settings.expire_after_ms
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Turns approval mode off. Requests already pending can still be
+    /// decided on. Returns `false` if it was not on.
+    pub fn disable_approval(&self, guild_id: Id<GuildMarker>) -> Result<bool> {
+        let removed = self.conn().execute(
+            "DELETE FROM approval_settings WHERE guild_id = ?1",
+            params![guild_id.get()],
+        )?;
+        Ok(removed > 0)
+    }
+
+    /// `None` unless the guild vets requests.
+    pub fn approval_settings(&self, guild_id: Id<GuildMarker>) -> Result<Option<ApprovalSettings>> {
+        let settings = self
+            .conn()
+            .query_row(
+                "SELECT dj_channel_id, dj_role_id, expire_after_ms FROM approval_settings
+                 WHERE guild_id = ?1",
+                params![guild_id.get()],
+                |row| {
+                    Ok(ApprovalSettings {
+                        dj_channel_id: Id::new(row.get(0)?),
+                        dj_role_id: row.get::<_, Option<u64>>(1)?.map(Id::new),
+                        expire_after_ms: row.get(2)?,
+                    })
+                },
+            )
+            .optional()?;
+        Ok(settings)
+    }
+
+    /// Adds a request to the guild's pending list and returns its id.
+    pub fn add_pending_request(
+        &self,
+        guild_id: Id<GuildMarker>,
+        requester_id: Id<UserMarker>,
+        voice_channel_id: Id<ChannelMarker>,
+        text_channel_id: Option<Id<ChannelMarker>>,
+        track: &NewPlaylistTrack,
+        expires_at: i64,
+    ) -> Result<i64> {
+        let conn = self.conn();
+        conn.execute(
+            "INSERT INTO pending_requests (guild_id, requester_id, voice_channel_id,
+                 text_channel_id, encoded, title, author, uri, length_ms, is_stream, requested_at,
+                 expires_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+            params![
+                guild_id.get(),
+                requester_id.get(),
+                voice_channel_id.get(),
+                text_channel_id.map(Id::get),
+                track.encoded,
+                track.title,
+                track.author,
+                track.uri,
+                track.length_ms,
+                track.is_stream,
+                chrono::Utc::now().timestamp_millis(),
+                expires_at
+            ],
+        )?;
+        Ok(conn.last_insert_rowid())
+    }
+
+    /// Puts a taken request back as it was, e.g. when queueing it failed.
+    pub fn restore_pending_request(&self, request: &PendingRequest) -> Result<()> {
+        self.conn().execute(
+            &format!(
+                "INSERT INTO pending_requests ({}) VALUES
+                 (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)",
+                REQUEST_COLUMNS
+            ),
+            params![
+                request.id,
+                request.guild_id.get(),
+                request.requester_id.get(),
+                request.voice_channel_id.get(),
+                request.text_channel_id.map(Id::get),
+                request.encoded,
+                request.title,
+                request.author,
+                request.uri,
+                request.length_ms,
+                request.is_stream,
+                request.card.map(|(channel_id, _)| channel_id.get()),
+                request.card.map(|(_, message_id)| message_id.get()),
+                request.requested_at,
+                request.expires_at
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Remembers the approval card so it can be updated when the request
+    /// expires.
+    pub fn set_pending_request_card(
+        &self,
+        id: i64,
+        channel_id: Id<ChannelMarker>,
+        message_id: Id<MessageMarker>,
+    ) -> Result<()> {
+        self.conn().execute(
+            "UPDATE pending_requests SET card_channel_id = ?2, card_message_id = ?3 WHERE id = ?1",
+            params![id, channel_id.get(), message_id.get()],
+        )?;
+        Ok(())
+    }
+
+    /// The guild's pending requests, oldest first.
+    pub fn pending_requests(&self, guild_id: Id<GuildMarker>) -> Result<Vec<PendingRequest>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM pending_requests WHERE guild_id = ?1 ORDER BY id",
+            REQUEST_COLUMNS
+        ))?;
+        let requests = stmt
+            .query_map(params![guild_id.get()], request_from_row)?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(requests)
+    }
+
+    /// Removes a pending request and returns it, so only the first decision
+    /// on it counts. `None` if it was already decided or expired.
+    pub fn take_pending_request(
+        &self,
+        guild_id: Id<GuildMarker>,
+        id: i64,
+    ) -> Result<Option<PendingRequest>> {
+        let request = self
+            .conn()
+            .query_row(
+                &format!(
+                    "DELETE FROM pending_requests WHERE guild_id = ?1 AND id = ?2 RETURNING {}",
+                    REQUEST_COLUMNS
+                ),
+                params![guild_id.get(), id],
+                request_from_row,
+            )
+            .optional()?;
+        Ok(request)
+    }
+
+    /// Removes and returns every request that expired by `now`.
+    pub fn take_expired_requests(&self, now: i64) -> Result<Vec<PendingRequest>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare(&format!(
+            "DELETE FROM pending_requests WHERE expires_at <= ?1 RETURNING {}",
+            REQUEST_COLUMNS
+        ))?;
+        let requests = stmt
+            .query_map(params![now], request_from_row)?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(requests)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::fixtures::{stream, track};
+
+    #[test]
+    fn test_approval_settings_can_be_changed_and_disabled() {
+        let storage = Storage::open_in_memory().unwrap();
+        let guild = Id::new(1);
+        assert_eq!(storage.approval_settings(guild).unwrap(), None);
+
+        let mut settings = ApprovalSettings {
+            dj_channel_id: Id::new(5),
+            dj_role_id: None,
+            expire_after_ms: 60_000,
+        };
+        storage.set_approval_settings(guild, &settings).unwrap();
+        settings.dj_role_id = Some(Id::new(7));
+        storage.set_approval_settings(guild, &settings).unwrap();
+        assert_eq!(storage.approval_settings(guild).unwrap(), Some(settings));
+
+        assert!(storage.disable_approval(guild).unwrap());
+        assert!(!storage.disable_approval(guild).unwrap());
+        assert_eq!(storage.approval_settings(guild).unwrap(), None);
+    }
+
+    #[test]
+    fn test_requests_are_taken_once_and_expire() {
+        let storage = Storage::open_in_memory().unwrap();
+        let (guild, user, voice) = (Id::new(1), Id::new(2), Id::new(3));
+        let now = chrono::Utc::now().timestamp_millis();
+
+        let soon = storage
+            .add_pending_request(guild, user, voice, None, &stream("a"), now + 1_000)
+            .unwrap();
+        let later = storage
+            .add_pending_request(
+                guild,
+                user,
+                voice,
+                Some(Id::new(4)),
+                &track("b"),
+                now + 60_000,
+            )
+            .unwrap();
+        storage
+            .set_pending_request_card(later, Id::new(5), Id::new(6))
+            .unwrap();
+        let pending = storage.pending_requests(guild).unwrap();
+        assert_eq!(
+            pending.iter().map(|r| r.id).collect::<Vec<_>>(),
+            [soon, later]
+        );
+        assert_eq!(pending[1].card, Some((Id::new(5), Id::new(6))));
+        assert!(pending[0].is_stream && !pending[1].is_stream);
+
+        assert!(
+            storage
+                .take_pending_request(Id::new(9), later)
+                .unwrap()
+                .is_none()
+        );
+        assert_eq!(
+            storage
+                .take_pending_request(guild, later)
+                .unwrap()
+                .unwrap()
+                .title,
+            "b"
+        );
+        assert!(
+            storage
+                .take_pending_request(guild, later)
+                .unwrap()
+                .is_none()
+        );
+        storage.restore_pending_request(&pending[1]).unwrap();
+        assert_eq!(
+            storage.take_pending_request(guild, later).unwrap().as_ref(),
+            Some(&pending[1])
+        );
+
+        assert!(storage.take_expired_requests(now).unwrap().is_empty());
+        let expired = storage.take_expired_requests(now + 1_000).unwrap();
+        assert_eq!(expired.iter().map(|r| r.id).collect::<Vec<_>>(), [soon]);
+        assert!(storage.pending_requests(guild).unwrap().is_empty());
+    }
+}
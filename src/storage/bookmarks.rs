@@ -0,0 +1,182 @@
+use anyhow::Result;
+use rusqlite::{OptionalExtension, Row, params};
+use twilight_model::id::{Id, marker::UserMarker};
+
+use super::Storage;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bookmark {
+    pub id: i64,
+    /// The Lavalink-encoded track.
+    pub encoded: String,
+    pub title: String,
+    pub author: String,
+    pub uri: Option<String>,
+    pub length_ms: u64,
+    pub position_ms: u64,
+    pub note: Option<String>,
+    /// Unix timestamp in milliseconds.
+    pub created_at: i64,
+}
+
+/// The fields of a bookmark that the caller provides.
+pub struct NewBookmark<'a> {
+    pub encoded: &'a str,
+    pub title: &'a str,
+    pub author: &'a str,
+    pub uri: Option<&'a str>,
+    pub length_ms: u64,
+    pub position_ms: u64,
+    pub note: Option<&'a str>,
+}
+
+const BOOKMARK_COLUMNS: &str =
+    "id, encoded, title, author, uri, length_ms, position_ms, note, created_at";
+
+fn bookmark_from_row(row: &Row) -> rusqlite::Result<Bookmark> {
+    Ok(Bookmark {
+        id: row.get(0)?,
+        encoded: row.get(1)?,
+        title: row.get(2)?,
+        author: row.get(3)?,
+        uri: row.get(4)?,
+        length_ms: row.get(5)?,
+        position_ms: row.get(6)?,
+        note: row.get(7)?,
+        created_at: row.get(8)?,
+    })
+}
+
+impl Storage {
+    /// Saves a bookmark for `user_id` and returns its id.
+    pub fn add_bookmark(&self, user_id: Id<UserMarker>, bookmark: &NewBookmark) -> Result<i64> {
+        let conn = self.conn();
+        conn.execute(
+            "INSERT INTO bookmarks (user_id, encoded, title, author, uri, length_ms, position_ms, note, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                user_id.get(),
+                bookmark.encoded,
+                bookmark.title,
+                bookmark.author,
+                bookmark.uri,
+                bookmark.length_ms,
+                bookmark.position_ms,
+                bookmark.note,
+                chrono::Utc::now().timestamp_millis()
+            ],
+        )?;
+        Ok(conn.last_insert_rowid())
+    }
+
+    /// One page of the user's bookmarks, newest first.
+    pub fn bookmarks(
+        &self,
+        user_id: Id<UserMarker>,
+        offset: usize,
+        limit: usize,
+    ) -> Result<Vec<Bookmark>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM bookmarks WHERE user_id = ?1 ORDER BY id DESC LIMIT ?2 OFFSET ?3",
+            BOOKMARK_COLUMNS
+        ))?;
+        let bookmarks = stmt
+            .query_map(params![user_id.get(), limit, offset], bookmark_from_row)?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(bookmarks)
+    }
+
+    pub fn bookmark_count(&self, user_id: Id<UserMarker>) -> Result<usize> {
+        let count = self.conn().query_row(
+            "SELECT COUNT(*) FROM bookmarks WHERE user_id = ?1",
+            params![user_id.get()],
+            |row| row.get(0),
+        )?;
+        Ok(count)
+    }
+
+    /// Looks up a bookmark, but only if it belongs to `user_id`.
+    pub fn bookmark(&self, user_id: Id<UserMarker>, id: i64) -> Result<Option<Bookmark>> {
+        let bookmark = self
+            .conn()
+            .query_row(
+                &format!(
+                    "SELECT {} FROM bookmarks WHERE user_id = ?1 AND id = ?2",
+                    BOOKMARK_COLUMNS
+                ),
+                params![user_id.get(), id],
+                bookmark_from_row,
+            )
+            .optional()?;
+        Ok(bookmark)
+    }
+
+    /// Returns `false` if the user has no bookmark with that id.
+    pub fn delete_bookmark(&self, user_id: Id<UserMarker>, id: i64) -> Result<bool> {
+        let removed = self.conn().execute(
+            "DELETE FROM bookmarks WHERE user_id = ?1 AND id = ?2",
+            params![user_id.get(), id],
+        )?;
+        Ok(removed > 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn new_bookmark(title: &str, position_ms: u64) -> NewBookmark<'_> {
+        NewBookmark {
+            encoded: "QAAA",
+            title,
+            author: "Some DJ",
+            uri: None,
+            length_ms: 7_200_000,
+            position_ms,
+            note: None,
+        }
+    }
+
+    #[test]
+    fn test_bookmarks_are_paged_newest_first() {
+        let storage = Storage::open_in_memory().unwrap();
+        let user = Id::new(1);
+        for (idx, title) in ["a", "b", "c"].into_iter().enumerate() {
+            storage
+                .add_bookmark(user, &new_bookmark(title, idx as u64))
+                .unwrap();
+        }
+
+        assert_eq!(storage.bookmark_count(user).unwrap(), 3);
+        let titles = |offset| -> Vec<String> {
+            storage
+                .bookmarks(user, offset, 2)
+                .unwrap()
+                .into_iter()
+                .map(|b| b.title)
+                .collect()
+        };
+        assert_eq!(titles(0), ["c", "b"]);
+        assert_eq!(titles(2), ["a"]);
+    }
+
+    #[test]
+    fn test_bookmarks_belong_to_their_user() {
+        let storage = Storage::open_in_memory().unwrap();
+        let owner = Id::new(1);
+        let other = Id::new(2);
+        let id = storage
+            .add_bookmark(owner, &new_bookmark("mix", 1_234))
+            .unwrap();
+
+        assert_eq!(storage.bookmark(other, id).unwrap(), None);
+        assert!(!storage.delete_bookmark(other, id).unwrap());
+        assert_eq!(
+            storage.bookmark(owner, id).unwrap().unwrap().position_ms,
+            1_234
+        );
+        assert!(storage.delete_bookmark(owner, id).unwrap());
+        assert_eq!(storage.bookmark_count(owner).unwrap(), 0);
+    }
+}
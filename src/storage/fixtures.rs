@@ -0,0 +1,44 @@
+//! Tracks and plays shared by the storage tests.
+
+use twilight_model::id::Id;
+
+use super::{NewPlay, NewPlaylistTrack};
+
+/// What Lavalink reports as the length of a livestream.
+pub const STREAM_LENGTH_MS: u64 = i64::MAX as u64;
+
+/// A three-minute track called `title`.
+pub fn track(title: &str) -> NewPlaylistTrack<'_> {
+    NewPlaylistTrack {
+        encoded: "QAAA",
+        title,
+        author: "Some Band",
+        uri: None,
+        length_ms: 180_000,
+        is_stream: false,
+    }
+}
+
+/// A livestream called `title`.
+pub fn stream(title: &str) -> NewPlaylistTrack<'_> {
+    NewPlaylistTrack {
+        length_ms: STREAM_LENGTH_MS,
+        is_stream: true,
+        ..track(title)
+    }
+}
+
+/// A play of [`track`] that nobody listened to, requested by nobody for 0.
+pub fn play(requester: u64, title: &str) -> NewPlay<'_> {
+    let track = track(title);
+    NewPlay {
+        encoded: Some(track.encoded),
+        requester_id: Id::new_checked(requester),
+        title,
+        author: track.author,
+        uri: track.uri,
+        length_ms: track.length_ms,
+        is_stream: track.is_stream,
+        listeners: &[],
+    }
+}
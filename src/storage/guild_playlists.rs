@@ -0,0 +1,774 @@
+//! Playlists shared by a whole guild. They live in their own tables so a
+//! guild's playlists can be backed up and restored without touching anyone's
+//! personal playlists.
+
+use anyhow::{Result, anyhow};
+use rusqlite::{Connection, OptionalExtension, Row, params};
+use serde::{Deserialize, Serialize};
+use twilight_model::id::{
+    Id,
+    marker::{GuildMarker, RoleMarker, UserMarker},
+};
+
+use super::playlists::playlist_track_from_row;
+use super::{NewPlaylistTrack, PlaylistTrack, Storage};
+
+/// Bumped whenever the layout of [`GuildPlaylistBackup`] changes incompatibly.
+pub const GUILD_PLAYLIST_BACKUP_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GuildPlaylist {
+    pub id: i64,
+    pub name: String,
+    pub owner_id: Id<UserMarker>,
+    /// Members with this role may edit the playlist.
+    pub edit_role_id: Option<Id<RoleMarker>>,
+    pub editors: Vec<Id<UserMarker>>,
+    pub track_count: usize,
+    /// Unix timestamp in milliseconds.
+    pub created_at: i64,
+}
+
+impl GuildPlaylist {
+    /// Whether a member with `roles` may add and remove tracks.
+    pub fn can_edit(&self, user_id: Id<UserMarker>, roles: &[Id<RoleMarker>]) -> bool {
+        user_id == self.owner_id
+            || self.editors.contains(&user_id)
+            || self.edit_role_id.is_some_and(|role| roles.contains(&role))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PlaylistChangeKind {
+    Created,
+    Added,
+    Removed,
+    EditorAdded,
+    EditorRemoved,
+    RoleChanged,
+}
+
+impl PlaylistChangeKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Created => "created",
+            Self::Added => "added",
+            Self::Removed => "removed",
+            Self::EditorAdded => "editor_added",
+            Self::EditorRemoved => "editor_removed",
+            Self::RoleChanged => "role_changed",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        Some(match value {
+            "created" => Self::Created,
+            "added" => Self::Added,
+            "removed" => Self::Removed,
+            "editor_added" => Self::EditorAdded,
+            "editor_removed" => Self::EditorRemoved,
+            "role_changed" => Self::RoleChanged,
+            _ => return None,
+        })
+    }
+}
+
+/// One entry of a playlist's change log.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlaylistChange {
+    pub user_id: Id<UserMarker>,
+    pub kind: PlaylistChangeKind,
+    /// What changed, e.g. the title of the added track.
+    pub detail: String,
+    /// Unix timestamp in milliseconds.
+    pub created_at: i64,
+}
+
+/// Everything `/gplaylist backup` saves for a guild.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GuildPlaylistBackup {
+    pub version: u32,
+    /// Unix timestamp in milliseconds.
+    pub exported_at: i64,
+    pub playlists: Vec<PlaylistBackup>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlaylistBackup {
+    pub name: String,
+    pub owner_id: Id<UserMarker>,
+    #[serde(default)]
+    pub edit_role_id: Option<Id<RoleMarker>>,
+    #[serde(default)]
+    pub editors: Vec<Id<UserMarker>>,
+    #[serde(default)]
+    pub tracks: Vec<TrackBackup>,
+    #[serde(default)]
+    pub changes: Vec<PlaylistChange>,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TrackBackup {
+    pub encoded: String,
+    pub title: String,
+    pub author: String,
+    #[serde(default)]
+    pub uri: Option<String>,
+    pub length_ms: u64,
+    #[serde(default)]
+    pub is_stream: bool,
+    pub added_at: i64,
+}
+
+impl GuildPlaylistBackup {
+    pub fn to_json(&self) -> Result<Vec<u8>> {
+        Ok(serde_json::to_vec_pretty(self)?)
+    }
+
+    /// Parses and validates an uploaded backup.
+    pub fn from_json(contents: &str) -> Result<Self> {
+        let backup: Self = serde_json::from_str(contents.trim_start_matches('\u{feff}'))
+            .map_err(|e| anyhow!("That is not a playlist backup: {}", e))?;
+        if backup.version == 0 || backup.version > GUILD_PLAYLIST_BACKUP_VERSION {
+            return Err(anyhow!(
+                "The backup is version {}, but only version {} is supported.",
+                backup.version,
+                GUILD_PLAYLIST_BACKUP_VERSION
+            ));
+        }
+        for playlist in &backup.playlists {
+            if playlist.name.trim().is_empty() {
+                return Err(anyhow!("The backup has a playlist without a name."));
+            }
+            if playlist.tracks.iter().any(|t| t.encoded.trim().is_empty()) {
+                return Err(anyhow!(
+                    "A track of `{}` has no encoded data.",
+                    playlist.name
+                ));
+            }
+        }
+        Ok(backup)
+    }
+
+    /// Checks that every playlist would fit the limits of one made by hand.
+    pub fn check_limits(&self, max_tracks: usize, max_name_len: usize) -> Result<()> {
+        for playlist in &self.playlists {
+            if playlist.name.chars().count() > max_name_len {
+                return Err(anyhow!(
+                    "The backup has a playlist name longer than {} characters.",
+                    max_name_len
+                ));
+            }
+            if playlist.tracks.len() > max_tracks {
+                return Err(anyhow!(
+                    "`{}` has more than {} tracks.",
+                    playlist.name,
+                    max_tracks
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+const GUILD_PLAYLIST_COLUMNS: &str = "p.id, p.name, p.owner_id, p.edit_role_id, p.created_at,
+     (SELECT COUNT(*) FROM guild_playlist_tracks t WHERE t.playlist_id = p.id)";
+
+fn guild_playlist_from_row(row: &Row) -> rusqlite::Result<GuildPlaylist> {
+    Ok(GuildPlaylist {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        owner_id: Id::new(row.get(2)?),
+        edit_role_id: row.get::<_, Option<u64>>(3)?.map(Id::new),
+        created_at: row.get(4)?,
+        track_count: row.get(5)?,
+        editors: Vec::new(),
+    })
+}
+
+fn load_editors(conn: &Connection, playlist: &mut GuildPlaylist) -> rusqlite::Result<()> {
+    let mut stmt = conn.prepare_cached(
+        "SELECT user_id FROM guild_playlist_editors WHERE playlist_id = ?1 ORDER BY rowid",
+    )?;
+    playlist.editors = stmt
+        .query_map(params![playlist.id], |row| Ok(Id::new(row.get(0)?)))?
+        .collect::<rusqlite::Result<_>>()?;
+    Ok(())
+}
+
+fn log_change(
+    conn: &Connection,
+    playlist_id: i64,
+    user_id: Id<UserMarker>,
+    kind: PlaylistChangeKind,
+    detail: &str,
+    created_at: i64,
+) -> rusqlite::Result<()> {
+    conn.execute(
+        "INSERT INTO guild_playlist_changes (playlist_id, user_id, action, detail, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![
+            playlist_id,
+            user_id.get(),
+            kind.as_str(),
+            detail,
+            created_at
+        ],
+    )?;
+    Ok(())
+}
+
+fn insert_track(
+    conn: &Connection,
+    playlist_id: i64,
+    track: &NewPlaylistTrack,
+    added_at: i64,
+) -> rusqlite::Result<()> {
+    conn.execute(
+        "INSERT INTO guild_playlist_tracks
+             (playlist_id, encoded, title, author, uri, length_ms, is_stream, added_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        params![
+            playlist_id,
+            track.encoded,
+            track.title,
+            track.author,
+            track.uri,
+            track.length_ms,
+            track.is_stream,
+            added_at
+        ],
+    )?;
+    Ok(())
+}
+
+impl Storage {
+    /// Returns `None` if the guild already has a playlist with that name.
+    pub fn create_guild_playlist(
+        &self,
+        guild_id: Id<GuildMarker>,
+        name: &str,
+        owner_id: Id<UserMarker>,
+    ) -> Result<Option<i64>> {
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        let now = chrono::Utc::now().timestamp_millis();
+        let inserted = tx.execute(
+            "INSERT OR IGNORE INTO guild_playlists (guild_id, name, owner_id, created_at)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![guild_id.get(), name, owner_id.get(), now],
+        )?;
+        if inserted == 0 {
+            return Ok(None);
+        }
+        let id = tx.last_insert_rowid();
+        log_change(&tx, id, owner_id, PlaylistChangeKind::Created, name, now)?;
+        tx.commit()?;
+        Ok(Some(id))
+    }
+
+    /// The guild's playlists, by name.
+    pub fn guild_playlists(&self, guild_id: Id<GuildMarker>) -> Result<Vec<GuildPlaylist>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM guild_playlists p WHERE p.guild_id = ?1 ORDER BY p.name",
+            GUILD_PLAYLIST_COLUMNS
+        ))?;
+        let mut playlists: Vec<GuildPlaylist> = stmt
+            .query_map(params![guild_id.get()], guild_playlist_from_row)?
+            .collect::<rusqlite::Result<_>>()?;
+        for playlist in &mut playlists {
+            load_editors(&conn, playlist)?;
+        }
+        Ok(playlists)
+    }
+
+    /// Looks up one of the guild's playlists by name, ignoring case.
+    pub fn guild_playlist(
+        &self,
+        guild_id: Id<GuildMarker>,
+        name: &str,
+    ) -> Result<Option<GuildPlaylist>> {
+        let conn = self.conn();
+        let playlist = conn
+            .query_row(
+                &format!(
+                    "SELECT {} FROM guild_playlists p WHERE p.guild_id = ?1 AND p.name = ?2",
+                    GUILD_PLAYLIST_COLUMNS
+                ),
+                params![guild_id.get(), name],
+                guild_playlist_from_row,
+            )
+            .optional()?;
+        let Some(mut playlist) = playlist else {
+            return Ok(None);
+        };
+        load_editors(&conn, &mut playlist)?;
+        Ok(Some(playlist))
+    }
+
+    /// Deletes the playlist with its tracks, editors and change log.
+    pub fn delete_guild_playlist(&self, guild_id: Id<GuildMarker>, id: i64) -> Result<bool> {
+        let removed = self.conn().execute(
+            "DELETE FROM guild_playlists WHERE guild_id = ?1 AND id = ?2",
+            params![guild_id.get(), id],
+        )?;
+        Ok(removed > 0)
+    }
+
+    /// Appends tracks and logs each one as added by `user_id`.
+    pub fn add_guild_playlist_tracks(
+        &self,
+        playlist_id: i64,
+        user_id: Id<UserMarker>,
+        tracks: &[NewPlaylistTrack],
+    ) -> Result<()> {
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        let now = chrono::Utc::now().timestamp_millis();
+        for track in tracks {
+            insert_track(&tx, playlist_id, track, now)?;
+            log_change(
+                &tx,
+                playlist_id,
+                user_id,
+                PlaylistChangeKind::Added,
+                track.title,
+                now,
+            )?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// All tracks of a guild playlist, in the order they were added.
+    pub fn guild_playlist_tracks(&self, playlist_id: i64) -> Result<Vec<PlaylistTrack>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare(
+            "SELECT id, encoded, title, author, uri, length_ms, is_stream, added_at FROM guild_playlist_tracks
+             WHERE playlist_id = ?1 ORDER BY id",
+        )?;
+        let tracks = stmt
+            .query_map(params![playlist_id], playlist_track_from_row)?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(tracks)
+    }
+
+    /// Removes the track at zero-based `index`, logs it and returns it.
+    pub fn remove_guild_playlist_track(
+        &self,
+        playlist_id: i64,
+        user_id: Id<UserMarker>,
+        index: usize,
+    ) -> Result<Option<PlaylistTrack>> {
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        let track = tx
+            .query_row(
+                "SELECT id, encoded, title, author, uri, length_ms, is_stream, added_at FROM guild_playlist_tracks
+                 WHERE playlist_id = ?1 ORDER BY id LIMIT 1 OFFSET ?2",
+                params![playlist_id, index],
+                playlist_track_from_row,
+            )
+            .optional()?;
+        if let Some(track) = &track {
+            tx.execute(
+                "DELETE FROM guild_playlist_tracks WHERE id = ?1",
+                params![track.id],
+            )?;
+            let now = chrono::Utc::now().timestamp_millis();
+            log_change(
+                &tx,
+                playlist_id,
+                user_id,
+                PlaylistChangeKind::Removed,
+                &track.title,
+                now,
+            )?;
+        }
+        tx.commit()?;
+        Ok(track)
+    }
+
+    /// Adds or removes an editor. Returns `false` if nothing changed.
+    pub fn set_guild_playlist_editor(
+        &self,
+        playlist_id: i64,
+        changed_by: Id<UserMarker>,
+        editor: Id<UserMarker>,
+        allowed: bool,
+    ) -> Result<bool> {
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        let (changed, kind) = if allowed {
+            let changed = tx.execute(
+                "INSERT OR IGNORE INTO guild_playlist_editors (playlist_id, user_id) VALUES (?1, ?2)",
+                params![playlist_id, editor.get()],
+            )?;
+            (changed, PlaylistChangeKind::EditorAdded)
+        } else {
+            let changed = tx.execute(
+                "DELETE FROM guild_playlist_editors WHERE playlist_id = ?1 AND user_id = ?2",
+                params![playlist_id, editor.get()],
+            )?;
+            (changed, PlaylistChangeKind::EditorRemoved)
+        };
+        if changed > 0 {
+            let now = chrono::Utc::now().timestamp_millis();
+            log_change(&tx, playlist_id, changed_by, kind, &editor.to_string(), now)?;
+        }
+        tx.commit()?;
+        Ok(changed > 0)
+    }
+
+    /// Sets or clears the role whose members may edit the playlist.
+    pub fn set_guild_playlist_role(
+        &self,
+        playlist_id: i64,
+        changed_by: Id<UserMarker>,
+        role_id: Option<Id<RoleMarker>>,
+    ) -> Result<()> {
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        tx.execute(
+            "UPDATE guild_playlists SET edit_role_id = ?2 WHERE id = ?1",
+            params![playlist_id, role_id.map(Id::get)],
+        )?;
+        let detail = role_id.map(|role| role.to_string()).unwrap_or_default();
+        let now = chrono::Utc::now().timestamp_millis();
+        log_change(
+            &tx,
+            playlist_id,
+            changed_by,
+            PlaylistChangeKind::RoleChanged,
+            &detail,
+            now,
+        )?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// One page of a playlist's change log, newest first.
+    pub fn guild_playlist_changes(
+        &self,
+        playlist_id: i64,
+        offset: usize,
+        limit: usize,
+    ) -> Result<Vec<PlaylistChange>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare(
+            "SELECT user_id, action, detail, created_at FROM guild_playlist_changes
+             WHERE playlist_id = ?1 ORDER BY id DESC LIMIT ?2 OFFSET ?3",
+        )?;
+        let changes = stmt
+            .query_map(params![playlist_id, limit, offset], |row| {
+                Ok((
+                    row.get::<_, u64>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, i64>(3)?,
+                ))
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?
+            .into_iter()
+            .filter_map(|(user_id, action, detail, created_at)| {
+                Some(PlaylistChange {
+                    user_id: Id::new(user_id),
+                    kind: PlaylistChangeKind::parse(&action)?,
+                    detail,
+                    created_at,
+                })
+            })
+            .collect();
+        Ok(changes)
+    }
+
+    pub fn guild_playlist_change_count(&self, playlist_id: i64) -> Result<usize> {
+        let count = self.conn().query_row(
+            "SELECT COUNT(*) FROM guild_playlist_changes WHERE playlist_id = ?1",
+            params![playlist_id],
+            |row| row.get(0),
+        )?;
+        Ok(count)
+    }
+
+    /// Everything about the guild's playlists, ready to be written to a file.
+    pub fn backup_guild_playlists(&self, guild_id: Id<GuildMarker>) -> Result<GuildPlaylistBackup> {
+        let mut playlists = Vec::new();
+        for playlist in self.guild_playlists(guild_id)? {
+            let tracks = self
+                .guild_playlist_tracks(playlist.id)?
+                .into_iter()
+                .map(|track| TrackBackup {
+                    encoded: track.encoded,
+                    title: track.title,
+                    author: track.author,
+                    uri: track.uri,
+                    length_ms: track.length_ms,
+                    is_stream: track.is_stream,
+                    added_at: track.added_at,
+                })
+                .collect();
+            // SQLite limits are signed, so this is "no limit".
+            let mut changes = self.guild_playlist_changes(playlist.id, 0, i64::MAX as usize)?;
+            changes.reverse();
+            playlists.push(PlaylistBackup {
+                name: playlist.name,
+                owner_id: playlist.owner_id,
+                edit_role_id: playlist.edit_role_id,
+                editors: playlist.editors,
+                tracks,
+                changes,
+                created_at: playlist.created_at,
+            });
+        }
+        Ok(GuildPlaylistBackup {
+            version: GUILD_PLAYLIST_BACKUP_VERSION,
+            exported_at: chrono::Utc::now().timestamp_millis(),
+            playlists,
+        })
+    }
+
+    /// Recreates the playlists of a backup in one transaction, if they all fit
+    /// `(max_tracks, max_name_len)`. Playlists whose name is taken are left
+    /// alone. Returns the names restored and skipped.
+    pub fn restore_guild_playlists(
+        &self,
+        guild_id: Id<GuildMarker>,
+        backup: &GuildPlaylistBackup,
+        (max_tracks, max_name_len): (usize, usize),
+    ) -> Result<(Vec<String>, Vec<String>)> {
+        backup.check_limits(max_tracks, max_name_len)?;
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        let (mut restored, mut skipped) = (Vec::new(), Vec::new());
+        for playlist in &backup.playlists {
+            let inserted = tx.execute(
+                "INSERT OR IGNORE INTO guild_playlists (guild_id, name, owner_id, edit_role_id, created_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    guild_id.get(),
+                    playlist.name,
+                    playlist.owner_id.get(),
+                    playlist.edit_role_id.map(Id::get),
+                    playlist.created_at
+                ],
+            )?;
+            if inserted == 0 {
+                skipped.push(playlist.name.clone());
+                continue;
+            }
+            let id = tx.last_insert_rowid();
+            for editor in &playlist.editors {
+                tx.execute(
+                    "INSERT OR IGNORE INTO guild_playlist_editors (playlist_id, user_id) VALUES (?1, ?2)",
+                    params![id, editor.get()],
+                )?;
+            }
+            for track in &playlist.tracks {
+                let new_track = NewPlaylistTrack {
+                    encoded: &track.encoded,
+                    title: &track.title,
+                    author: &track.author,
+                    uri: track.uri.as_deref(),
+                    length_ms: track.length_ms,
+                    is_stream: track.is_stream,
+                };
+                insert_track(&tx, id, &new_track, track.added_at)?;
+            }
+            for change in &playlist.changes {
+                log_change(
+                    &tx,
+                    id,
+                    change.user_id,
+                    change.kind,
+                    &change.detail,
+                    change.created_at,
+                )?;
+            }
+            restored.push(playlist.name.clone());
+        }
+        tx.commit()?;
+        Ok((restored, skipped))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::fixtures::track;
+
+    #[test]
+    fn test_edit_permission() {
+        let storage = Storage::open_in_memory().unwrap();
+        let guild = Id::new(10);
+        let (owner, editor, member) = (Id::new(1), Id::new(2), Id::new(3));
+        let role = Id::new(50);
+
+        let id = storage
+            .create_guild_playlist(guild, "Friday raid mix", owner)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            storage
+                .create_guild_playlist(guild, "friday RAID mix", member)
+                .unwrap(),
+            None
+        );
+        assert!(
+            storage
+                .set_guild_playlist_editor(id, owner, editor, true)
+                .unwrap()
+        );
+        assert!(
+            !storage
+                .set_guild_playlist_editor(id, owner, editor, true)
+                .unwrap()
+        );
+
+        let playlist = storage
+            .guild_playlist(guild, "friday raid mix")
+            .unwrap()
+            .unwrap();
+        assert!(playlist.can_edit(owner, &[]));
+        assert!(playlist.can_edit(editor, &[]));
+        assert!(!playlist.can_edit(member, &[role]));
+
+        storage
+            .set_guild_playlist_role(id, owner, Some(role))
+            .unwrap();
+        let playlist = storage
+            .guild_playlist(guild, "Friday raid mix")
+            .unwrap()
+            .unwrap();
+        assert!(playlist.can_edit(member, &[Id::new(49), role]));
+        assert!(!playlist.can_edit(member, &[]));
+    }
+
+    #[test]
+    fn test_changes_record_who_added_and_removed_what() {
+        let storage = Storage::open_in_memory().unwrap();
+        let (owner, editor) = (Id::new(1), Id::new(2));
+        let id = storage
+            .create_guild_playlist(Id::new(10), "mix", owner)
+            .unwrap()
+            .unwrap();
+        storage
+            .add_guild_playlist_tracks(id, editor, &[track("a"), track("b")])
+            .unwrap();
+        assert_eq!(
+            storage
+                .remove_guild_playlist_track(id, owner, 0)
+                .unwrap()
+                .unwrap()
+                .title,
+            "a"
+        );
+
+        assert_eq!(storage.guild_playlist_change_count(id).unwrap(), 4);
+        let changes = storage.guild_playlist_changes(id, 0, 2).unwrap();
+        assert_eq!(changes[0].kind, PlaylistChangeKind::Removed);
+        assert_eq!(
+            (changes[0].user_id, changes[0].detail.as_str()),
+            (owner, "a")
+        );
+        assert_eq!(changes[1].kind, PlaylistChangeKind::Added);
+        assert_eq!(
+            (changes[1].user_id, changes[1].detail.as_str()),
+            (editor, "b")
+        );
+    }
+
+    #[test]
+    fn test_backup_restores_into_another_guild() {
+        let storage = Storage::open_in_memory().unwrap();
+        let (source, target) = (Id::new(10), Id::new(20));
+        let owner = Id::new(1);
+        let id = storage
+            .create_guild_playlist(source, "mix", owner)
+            .unwrap()
+            .unwrap();
+        storage
+            .add_guild_playlist_tracks(id, owner, &[track("a")])
+            .unwrap();
+        storage
+            .set_guild_playlist_editor(id, owner, Id::new(2), true)
+            .unwrap();
+        storage
+            .create_guild_playlist(target, "taken", owner)
+            .unwrap();
+        storage
+            .create_guild_playlist(source, "taken", owner)
+            .unwrap();
+
+        let json = storage
+            .backup_guild_playlists(source)
+            .unwrap()
+            .to_json()
+            .unwrap();
+        let backup = GuildPlaylistBackup::from_json(std::str::from_utf8(&json).unwrap()).unwrap();
+        let (restored, skipped) = storage
+            .restore_guild_playlists(target, &backup, (500, 50))
+            .unwrap();
+        assert_eq!(restored, ["mix"]);
+        assert_eq!(skipped, ["taken"]);
+
+        let copy = storage.guild_playlist(target, "mix").unwrap().unwrap();
+        assert_eq!(copy.editors, [Id::new(2)]);
+        assert_eq!(
+            storage.guild_playlist_tracks(copy.id).unwrap()[0].title,
+            "a"
+        );
+        assert_eq!(storage.guild_playlist_change_count(copy.id).unwrap(), 3);
+        // The source guild and personal playlists are untouched.
+        assert_eq!(storage.guild_playlists(source).unwrap().len(), 2);
+        assert!(storage.playlists(owner).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_restore_rejects_backups_over_the_limits() {
+        let storage = Storage::open_in_memory().unwrap();
+        let owner = Id::new(1);
+        let id = storage
+            .create_guild_playlist(Id::new(10), "mix", owner)
+            .unwrap()
+            .unwrap();
+        storage
+            .add_guild_playlist_tracks(id, owner, &[track("a"), track("b")])
+            .unwrap();
+        let backup = storage.backup_guild_playlists(Id::new(10)).unwrap();
+
+        let target = Id::new(20);
+        assert!(
+            storage
+                .restore_guild_playlists(target, &backup, (1, 50))
+                .is_err()
+        );
+        assert!(
+            storage
+                .restore_guild_playlists(target, &backup, (500, 2))
+                .is_err()
+        );
+        assert!(storage.guild_playlists(target).unwrap().is_empty());
+        assert!(
+            storage
+                .restore_guild_playlists(target, &backup, (2, 3))
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_backup_rejects_other_files() {
+        assert!(GuildPlaylistBackup::from_json("[]").is_err());
+        assert!(
+            GuildPlaylistBackup::from_json(r#"{"version": 9, "exported_at": 0, "playlists": []}"#)
+                .is_err()
+        );
+        assert!(
+            GuildPlaylistBackup::from_json(r#"{"version": 1, "exported_at": 0, "playlists": []}"#)
+                .is_ok()
+        );
+    }
+}
@@ -0,0 +1,193 @@
+use anyhow::Result;
+use rusqlite::{OptionalExtension, params};
+use twilight_model::id::{
+    Id,
+    marker::{GuildMarker, UserMarker},
+};
+
+use super::playlists::playlist_track_from_row;
+use super::{NewPlaylistTrack, PlaylistTrack, Storage};
+
+/// A track and how many people liked it in a guild.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MostLikedTrack {
+    pub title: String,
+    pub author: String,
+    pub uri: Option<String>,
+    pub likes: u64,
+}
+
+impl Storage {
+    /// Returns `false` if the user already liked the track.
+    pub fn like_track(
+        &self,
+        user_id: Id<UserMarker>,
+        guild_id: Id<GuildMarker>,
+        track: &NewPlaylistTrack,
+    ) -> Result<bool> {
+        let inserted = self.conn().execute(
+            "INSERT OR IGNORE INTO liked_tracks
+                 (user_id, guild_id, encoded, title, author, uri, length_ms, is_stream, liked_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                user_id.get(),
+                guild_id.get(),
+                track.encoded,
+                track.title,
+                track.author,
+                track.uri,
+                track.length_ms,
+                track.is_stream,
+                chrono::Utc::now().timestamp_millis()
+            ],
+        )?;
+        Ok(inserted > 0)
+    }
+
+    /// The user's liked tracks, newest first.
+    pub fn liked_tracks(&self, user_id: Id<UserMarker>) -> Result<Vec<PlaylistTrack>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare(
+            "SELECT id, encoded, title, author, uri, length_ms, is_stream, liked_at FROM liked_tracks
+             WHERE user_id = ?1 ORDER BY id DESC",
+        )?;
+        let tracks = stmt
+            .query_map(params![user_id.get()], playlist_track_from_row)?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(tracks)
+    }
+
+    /// Removes the liked track at zero-based `index`, counted newest first,
+    /// and returns it.
+    pub fn unlike_track(
+        &self,
+        user_id: Id<UserMarker>,
+        index: usize,
+    ) -> Result<Option<PlaylistTrack>> {
+        let conn = self.conn();
+        let track = conn
+            .query_row(
+                "SELECT id, encoded, title, author, uri, length_ms, is_stream, liked_at FROM liked_tracks
+                 WHERE user_id = ?1 ORDER BY id DESC LIMIT 1 OFFSET ?2",
+                params![user_id.get(), index],
+                playlist_track_from_row,
+            )
+            .optional()?;
+        if let Some(track) = &track {
+            conn.execute("DELETE FROM liked_tracks WHERE id = ?1", params![track.id])?;
+        }
+        Ok(track)
+    }
+
+    /// The tracks liked most often in the guild, ties broken by the earliest like.
+    pub fn most_liked_tracks(
+        &self,
+        guild_id: Id<GuildMarker>,
+        limit: usize,
+    ) -> Result<Vec<MostLikedTrack>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare(
+            "SELECT title, author, uri, COUNT(*) AS likes FROM liked_tracks
+             WHERE guild_id = ?1
+             GROUP BY encoded
+             ORDER BY likes DESC, MIN(id)
+             LIMIT ?2",
+        )?;
+        let tracks = stmt
+            .query_map(params![guild_id.get(), limit], |row| {
+                Ok(MostLikedTrack {
+                    title: row.get(0)?,
+                    author: row.get(1)?,
+                    uri: row.get(2)?,
+                    likes: row.get(3)?,
+                })
+            })?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(tracks)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::fixtures::track;
+
+    fn new_track<'a>(encoded: &'a str, title: &'a str) -> NewPlaylistTrack<'a> {
+        NewPlaylistTrack {
+            encoded,
+            ..track(title)
+        }
+    }
+
+    #[test]
+    fn test_likes_are_per_user_and_newest_first() {
+        let storage = Storage::open_in_memory().unwrap();
+        let (alice, bob) = (Id::new(1), Id::new(2));
+        let guild = Id::new(10);
+
+        assert!(
+            storage
+                .like_track(alice, guild, &new_track("QA", "a"))
+                .unwrap()
+        );
+        assert!(
+            !storage
+                .like_track(alice, guild, &new_track("QA", "a"))
+                .unwrap()
+        );
+        assert!(
+            storage
+                .like_track(alice, guild, &new_track("QB", "b"))
+                .unwrap()
+        );
+        assert!(
+            storage
+                .like_track(bob, guild, &new_track("QA", "a"))
+                .unwrap()
+        );
+
+        let titles: Vec<_> = storage
+            .liked_tracks(alice)
+            .unwrap()
+            .into_iter()
+            .map(|t| t.title)
+            .collect();
+        assert_eq!(titles, ["b", "a"]);
+
+        assert_eq!(storage.unlike_track(alice, 0).unwrap().unwrap().title, "b");
+        assert_eq!(storage.unlike_track(alice, 1).unwrap(), None);
+        assert_eq!(storage.liked_tracks(alice).unwrap().len(), 1);
+        assert_eq!(storage.liked_tracks(bob).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_most_liked_counts_likes_given_in_the_guild() {
+        let storage = Storage::open_in_memory().unwrap();
+        let (guild, other_guild) = (Id::new(10), Id::new(20));
+
+        storage
+            .like_track(Id::new(1), guild, &new_track("QA", "a"))
+            .unwrap();
+        storage
+            .like_track(Id::new(1), guild, &new_track("QB", "b"))
+            .unwrap();
+        storage
+            .like_track(Id::new(2), guild, &new_track("QB", "b"))
+            .unwrap();
+        storage
+            .like_track(Id::new(3), other_guild, &new_track("QA", "a"))
+            .unwrap();
+        storage
+            .like_track(Id::new(4), other_guild, &new_track("QA", "a"))
+            .unwrap();
+
+        let top: Vec<_> = storage
+            .most_liked_tracks(guild, 10)
+            .unwrap()
+            .into_iter()
+            .map(|t| (t.title, t.likes))
+            .collect();
+        assert_eq!(top, [("b".to_string(), 2), ("a".to_string(), 1)]);
+        assert_eq!(storage.most_liked_tracks(other_guild, 1).unwrap().len(), 1);
+    }
+}
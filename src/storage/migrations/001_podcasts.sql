@@ -0,0 +1,16 @@
+CREATE TABLE podcast_subscriptions (
+    guild_id      INTEGER NOT NULL,
+    feed_url      TEXT    NOT NULL,
+    title         TEXT    NOT NULL,
+    subscribed_by INTEGER NOT NULL,
+    subscribed_at INTEGER NOT NULL,
+    PRIMARY KEY (guild_id, feed_url)
+);
+
+CREATE TABLE podcast_progress (
+    guild_id    INTEGER NOT NULL,
+    episode_id  TEXT    NOT NULL,
+    position_ms INTEGER NOT NULL,
+    updated_at  INTEGER NOT NULL,
+    PRIMARY KEY (guild_id, episode_id)
+);
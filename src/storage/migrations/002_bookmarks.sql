@@ -0,0 +1,14 @@
+CREATE TABLE bookmarks (
+    id          INTEGER PRIMARY KEY AUTOINCREMENT,
+    user_id     INTEGER NOT NULL,
+    encoded     TEXT    NOT NULL,
+    title       TEXT    NOT NULL,
+    author      TEXT    NOT NULL,
+    uri         TEXT,
+    length_ms   INTEGER NOT NULL,
+    position_ms INTEGER NOT NULL,
+    note        TEXT,
+    created_at  INTEGER NOT NULL
+);
+
+CREATE INDEX bookmarks_by_user ON bookmarks (user_id, id);
@@ -0,0 +1,21 @@
+CREATE TABLE playlists (
+    id         INTEGER PRIMARY KEY AUTOINCREMENT,
+    user_id    INTEGER NOT NULL,
+    name       TEXT    NOT NULL COLLATE NOCASE,
+    created_at INTEGER NOT NULL,
+    UNIQUE (user_id, name)
+);
+
+CREATE TABLE playlist_tracks (
+    id          INTEGER PRIMARY KEY AUTOINCREMENT,
+    playlist_id INTEGER NOT NULL REFERENCES playlists (id) ON DELETE CASCADE,
+    encoded     TEXT    NOT NULL,
+    title       TEXT    NOT NULL,
+    author      TEXT    NOT NULL,
+    uri         TEXT,
+    length_ms   INTEGER NOT NULL,
+    is_stream   INTEGER NOT NULL,
+    added_at    INTEGER NOT NULL
+);
+
+CREATE INDEX playlist_tracks_by_playlist ON playlist_tracks (playlist_id, id);
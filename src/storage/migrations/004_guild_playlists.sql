@@ -0,0 +1,40 @@
+CREATE TABLE guild_playlists (
+    id           INTEGER PRIMARY KEY AUTOINCREMENT,
+    guild_id     INTEGER NOT NULL,
+    name         TEXT    NOT NULL COLLATE NOCASE,
+    owner_id     INTEGER NOT NULL,
+    edit_role_id INTEGER,
+    created_at   INTEGER NOT NULL,
+    UNIQUE (guild_id, name)
+);
+
+CREATE TABLE guild_playlist_editors (
+    playlist_id INTEGER NOT NULL REFERENCES guild_playlists (id) ON DELETE CASCADE,
+    user_id     INTEGER NOT NULL,
+    PRIMARY KEY (playlist_id, user_id)
+);
+
+CREATE TABLE guild_playlist_tracks (
+    id          INTEGER PRIMARY KEY AUTOINCREMENT,
+    playlist_id INTEGER NOT NULL REFERENCES guild_playlists (id) ON DELETE CASCADE,
+    encoded     TEXT    NOT NULL,
+    title       TEXT    NOT NULL,
+    author      TEXT    NOT NULL,
+    uri         TEXT,
+    length_ms   INTEGER NOT NULL,
+    is_stream   INTEGER NOT NULL,
+    added_at    INTEGER NOT NULL
+);
+
+CREATE INDEX guild_playlist_tracks_by_playlist ON guild_playlist_tracks (playlist_id, id);
+
+CREATE TABLE guild_playlist_changes (
+    id          INTEGER PRIMARY KEY AUTOINCREMENT,
+    playlist_id INTEGER NOT NULL REFERENCES guild_playlists (id) ON DELETE CASCADE,
+    user_id     INTEGER NOT NULL,
+    action      TEXT    NOT NULL,
+    detail      TEXT    NOT NULL,
+    created_at  INTEGER NOT NULL
+);
+
+CREATE INDEX guild_playlist_changes_by_playlist ON guild_playlist_changes (playlist_id, id);
@@ -0,0 +1,18 @@
+CREATE TABLE playlist_shares (
+    code        TEXT    PRIMARY KEY,
+    playlist_id INTEGER NOT NULL REFERENCES playlists (id) ON DELETE CASCADE,
+    created_by  INTEGER NOT NULL,
+    created_at  INTEGER NOT NULL,
+    expires_at  INTEGER,
+    revoked_at  INTEGER,
+    uses        INTEGER NOT NULL DEFAULT 0
+);
+
+CREATE INDEX playlist_shares_by_creator ON playlist_shares (created_by);
+
+-- Where an imported playlist was cloned from. Copied rather than referenced,
+-- so it survives the original being deleted.
+ALTER TABLE playlists ADD COLUMN source_code TEXT;
+ALTER TABLE playlists ADD COLUMN source_name TEXT;
+ALTER TABLE playlists ADD COLUMN source_owner_id INTEGER;
+ALTER TABLE playlists ADD COLUMN imported_at INTEGER;
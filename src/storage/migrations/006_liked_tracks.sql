@@ -0,0 +1,17 @@
+CREATE TABLE liked_tracks (
+    id        INTEGER PRIMARY KEY AUTOINCREMENT,
+    user_id   INTEGER NOT NULL,
+    -- The guild the like was given in, for its "most liked" view.
+    guild_id  INTEGER NOT NULL,
+    encoded   TEXT    NOT NULL,
+    title     TEXT    NOT NULL,
+    author    TEXT    NOT NULL,
+    uri       TEXT,
+    length_ms INTEGER NOT NULL,
+    is_stream INTEGER NOT NULL,
+    liked_at  INTEGER NOT NULL,
+    UNIQUE (user_id, encoded)
+);
+
+CREATE INDEX liked_tracks_by_user ON liked_tracks (user_id, id);
+CREATE INDEX liked_tracks_by_guild ON liked_tracks (guild_id, encoded);
@@ -0,0 +1,19 @@
+CREATE TABLE plays (
+    id           INTEGER PRIMARY KEY AUTOINCREMENT,
+    guild_id     INTEGER NOT NULL,
+    requester_id INTEGER,
+    title        TEXT    NOT NULL,
+    author       TEXT    NOT NULL,
+    uri          TEXT,
+    -- The Lavalink-encoded track, so a session can be saved as a playlist.
+    encoded      TEXT,
+    length_ms    INTEGER NOT NULL,
+    is_stream    INTEGER NOT NULL,
+    started_at   INTEGER NOT NULL,
+    -- NULL until the track ends. Plays cut short by a restart never get a listened_ms.
+    ended_at     INTEGER,
+    listened_ms  INTEGER
+);
+
+CREATE INDEX plays_by_guild ON plays (guild_id, started_at);
+CREATE INDEX plays_by_requester ON plays (guild_id, requester_id, started_at);
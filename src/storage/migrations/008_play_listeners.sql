@@ -0,0 +1,8 @@
+-- Who was in the voice channel when a play started.
+CREATE TABLE play_listeners (
+    play_id INTEGER NOT NULL REFERENCES plays (id) ON DELETE CASCADE,
+    user_id INTEGER NOT NULL,
+    PRIMARY KEY (play_id, user_id)
+);
+
+CREATE INDEX play_listeners_by_user ON play_listeners (user_id, play_id);
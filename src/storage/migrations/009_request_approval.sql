@@ -0,0 +1,27 @@
+CREATE TABLE approval_settings (
+    guild_id        INTEGER PRIMARY KEY,
+    dj_channel_id   INTEGER NOT NULL,
+    dj_role_id      INTEGER,
+    expire_after_ms INTEGER NOT NULL
+);
+
+CREATE TABLE pending_requests (
+    id               INTEGER PRIMARY KEY AUTOINCREMENT,
+    guild_id         INTEGER NOT NULL,
+    requester_id     INTEGER NOT NULL,
+    voice_channel_id INTEGER NOT NULL,
+    text_channel_id  INTEGER,
+    encoded          TEXT    NOT NULL,
+    title            TEXT    NOT NULL,
+    author           TEXT    NOT NULL,
+    uri              TEXT,
+    length_ms        INTEGER NOT NULL,
+    is_stream        INTEGER NOT NULL,
+    card_channel_id  INTEGER,
+    card_message_id  INTEGER,
+    requested_at     INTEGER NOT NULL,
+    expires_at       INTEGER NOT NULL
+);
+
+CREATE INDEX pending_requests_by_guild ON pending_requests (guild_id, id);
+CREATE INDEX pending_requests_by_expiry ON pending_requests (expires_at);
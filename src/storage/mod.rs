@@ -0,0 +1,87 @@
+//! SQLite storage for everything the bot keeps between restarts. Each feature
+//! adds its queries as `impl Storage` blocks in its own submodule, and its
+//! tables as a numbered migration.
+
+use std::path::Path;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+use anyhow::Result;
+use rusqlite::Connection;
+
+mod approvals;
+mod bookmarks;
+#[cfg(test)]
+mod fixtures;
+mod guild_playlists;
+mod liked;
+mod play_log;
+mod playlist_shares;
+mod playlists;
+mod podcasts;
+mod wrapped;
+
+pub use approvals::{ApprovalSettings, PendingRequest};
+pub use bookmarks::{Bookmark, NewBookmark};
+pub use guild_playlists::{GuildPlaylist, GuildPlaylistBackup, PlaylistChange, PlaylistChangeKind};
+pub use liked::MostLikedTrack;
+pub use play_log::{NewPlay, Play, PlayFilter, PlayStats, TrackPlays};
+pub use playlist_shares::{PlaylistShare, normalize_share_code};
+pub use playlists::{NewPlaylistTrack, Playlist, PlaylistTrack};
+pub use podcasts::PodcastSubscription;
+pub use wrapped::Wrapped;
+
+/// Applied in order; `PRAGMA user_version` records how many have run.
+const MIGRATIONS: &[&str] = &[
+    include_str!("migrations/001_podcasts.sql"),
+    include_str!("migrations/002_bookmarks.sql"),
+    include_str!("migrations/003_playlists.sql"),
+    include_str!("migrations/004_guild_playlists.sql"),
+    include_str!("migrations/005_playlist_shares.sql"),
+    include_str!("migrations/006_liked_tracks.sql"),
+    include_str!("migrations/007_play_log.sql"),
+    include_str!("migrations/008_play_listeners.sql"),
+    include_str!("migrations/009_request_approval.sql"),
+];
+
+pub struct Storage {
+    conn: Mutex<Connection>,
+}
+
+impl Storage {
+    pub fn open(path: &Path) -> Result<Self> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        Self::from_connection(Connection::open(path)?)
+    }
+
+    #[cfg(test)]
+    pub fn open_in_memory() -> Result<Self> {
+        Self::from_connection(Connection::open_in_memory()?)
+    }
+
+    fn from_connection(mut conn: Connection) -> Result<Self> {
+        conn.pragma_update(None, "foreign_keys", "ON")?;
+        migrate(&mut conn)?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    /// Queries are short, so they run inline on the calling task.
+    fn conn(&self) -> MutexGuard<'_, Connection> {
+        self.conn.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+fn migrate(conn: &mut Connection) -> Result<()> {
+    let version: usize = conn.pragma_query_value(None, "user_version", |row| row.get(0))?;
+    for (idx, sql) in MIGRATIONS.iter().enumerate().skip(version) {
+        let tx = conn.transaction()?;
+        tx.execute_batch(sql)?;
+        tx.pragma_update(None, "user_version", idx + 1)?;
+        tx.commit()?;
+        tracing::info!("Applied storage migration {}", idx + 1);
+    }
+    Ok(())
+}
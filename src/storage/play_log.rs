@@ -0,0 +1,410 @@
+use anyhow::{Result, anyhow};
+use rusqlite::{Connection, Row, params};
+use twilight_model::id::{
+    Id,
+    marker::{GuildMarker, UserMarker},
+};
+
+use super::playlists::create_playlist_with_free_name;
+use super::{Playlist, Storage};
+
+/// One row of the play log.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Play {
+    pub requester_id: Option<Id<UserMarker>>,
+    pub title: String,
+    pub author: String,
+    pub uri: Option<String>,
+    /// Unix timestamp in milliseconds.
+    pub started_at: i64,
+    /// How long the track played, capped at its length. `None` while it
+    /// plays, or if the bot went away before it ended.
+    pub listened_ms: Option<u64>,
+}
+
+/// The fields of a play that the caller provides when a track starts.
+pub struct NewPlay<'a> {
+    /// The Lavalink-encoded track, `None` for ones that can't be replayed
+    /// later, such as uploaded files.
+    pub encoded: Option<&'a str>,
+    pub requester_id: Option<Id<UserMarker>>,
+    pub title: &'a str,
+    pub author: &'a str,
+    pub uri: Option<&'a str>,
+    pub length_ms: u64,
+    pub is_stream: bool,
+    /// Who else was in the voice channel.
+    pub listeners: &'a [Id<UserMarker>],
+}
+
+/// Which plays a statistic covers.
+#[derive(Debug, Clone, Copy)]
+pub struct PlayFilter {
+    pub guild_id: Id<GuildMarker>,
+    /// Only plays this user requested.
+    pub requester_id: Option<Id<UserMarker>>,
+    /// Only plays started at or after this Unix timestamp in milliseconds.
+    pub since: Option<i64>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrackPlays {
+    pub title: String,
+    pub author: String,
+    pub plays: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct PlayStats {
+    pub plays: u64,
+    pub listened_ms: u64,
+    pub top_tracks: Vec<TrackPlays>,
+    /// Artist and play count.
+    pub top_artists: Vec<(String, u64)>,
+    pub top_requesters: Vec<(Id<UserMarker>, u64)>,
+    /// Hour of the day in UTC and play count.
+    pub busiest_hours: Vec<(u32, u64)>,
+}
+
+/// `?1` to `?3` are bound by [`PlayFilter::params`].
+const PLAY_FILTER: &str =
+    "guild_id = ?1 AND (?2 IS NULL OR requester_id = ?2) AND started_at >= ?3";
+
+impl PlayFilter {
+    fn params(&self) -> (u64, Option<u64>, i64) {
+        (
+            self.guild_id.get(),
+            self.requester_id.map(Id::get),
+            self.since.unwrap_or(i64::MIN),
+        )
+    }
+}
+
+fn play_from_row(row: &Row) -> rusqlite::Result<Play> {
+    Ok(Play {
+        requester_id: row.get::<_, Option<u64>>(0)?.and_then(Id::new_checked),
+        title: row.get(1)?,
+        author: row.get(2)?,
+        uri: row.get(3)?,
+        started_at: row.get(4)?,
+        listened_ms: row.get(5)?,
+    })
+}
+
+/// Runs a `SELECT <key>, COUNT(*) ... GROUP BY` over the filtered plays,
+/// most plays first and the most recently played first among ties.
+fn ranked<T>(
+    conn: &Connection,
+    filter: &PlayFilter,
+    select: &str,
+    group_by: &str,
+    limit: usize,
+    from_row: impl Fn(&Row) -> rusqlite::Result<T>,
+) -> Result<Vec<T>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {}, COUNT(*) AS plays FROM plays WHERE {} GROUP BY {}
+         ORDER BY plays DESC, MAX(id) DESC LIMIT ?4",
+        select, PLAY_FILTER, group_by
+    ))?;
+    let (guild_id, requester_id, since) = filter.params();
+    let rows = stmt
+        .query_map(params![guild_id, requester_id, since, limit], from_row)?
+        .collect::<rusqlite::Result<_>>()?;
+    Ok(rows)
+}
+
+impl Storage {
+    /// Records that a track started playing in the guild.
+    pub fn log_play_start(&self, guild_id: Id<GuildMarker>, play: &NewPlay) -> Result<()> {
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        tx.execute(
+            "INSERT INTO plays (guild_id, requester_id, title, author, uri, length_ms, is_stream, started_at, encoded)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                guild_id.get(),
+                play.requester_id.map(Id::get),
+                play.title,
+                play.author,
+                play.uri,
+                play.length_ms,
+                play.is_stream,
+                chrono::Utc::now().timestamp_millis(),
+                play.encoded
+            ],
+        )?;
+        let play_id = tx.last_insert_rowid();
+        {
+            let mut stmt = tx.prepare(
+                "INSERT OR IGNORE INTO play_listeners (play_id, user_id) VALUES (?1, ?2)",
+            )?;
+            for listener in play.listeners {
+                stmt.execute(params![play_id, listener.get()])?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Closes the guild's latest open play. Time spent paused counts as
+    /// listened, but never more than the track's length.
+    pub fn log_play_end(&self, guild_id: Id<GuildMarker>) -> Result<()> {
+        self.conn().execute(
+            "UPDATE plays SET ended_at = ?2,
+                 listened_ms = CASE WHEN is_stream THEN ?2 - started_at
+                                    ELSE MIN(?2 - started_at, length_ms) END
+             WHERE id = (SELECT id FROM plays WHERE guild_id = ?1 AND ended_at IS NULL
+                         ORDER BY id DESC LIMIT 1)",
+            params![guild_id.get(), chrono::Utc::now().timestamp_millis()],
+        )?;
+        Ok(())
+    }
+
+    /// Closes every open play without a listening time.
+    pub fn abandon_open_plays(&self) -> Result<()> {
+        self.conn().execute(
+            "UPDATE plays SET ended_at = started_at WHERE ended_at IS NULL",
+            [],
+        )?;
+        Ok(())
+    }
+
+    /// Counts and the top `limit` of each leaderboard.
+    pub fn play_stats(&self, filter: &PlayFilter, limit: usize) -> Result<PlayStats> {
+        let conn = self.conn();
+        let (guild_id, requester_id, since) = filter.params();
+        let (plays, listened_ms) = conn.query_row(
+            &format!(
+                "SELECT COUNT(*), COALESCE(SUM(listened_ms), 0) FROM plays WHERE {}",
+                PLAY_FILTER
+            ),
+            params![guild_id, requester_id, since],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
+
+        Ok(PlayStats {
+            plays,
+            listened_ms,
+            top_tracks: ranked(
+                &conn,
+                filter,
+                "title, author",
+                "title, author",
+                limit,
+                |row| {
+                    Ok(TrackPlays {
+                        title: row.get(0)?,
+                        author: row.get(1)?,
+                        plays: row.get(2)?,
+                    })
+                },
+            )?,
+            top_artists: ranked(&conn, filter, "author", "author", limit, |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })?,
+            top_requesters: ranked(
+                &conn,
+                filter,
+                "requester_id",
+                "requester_id HAVING requester_id IS NOT NULL",
+                limit,
+                |row| Ok((Id::new(row.get(0)?), row.get(1)?)),
+            )?,
+            busiest_hours: ranked(
+                &conn,
+                filter,
+                "CAST(strftime('%H', started_at / 1000, 'unixepoch') AS INTEGER) AS hour",
+                "hour",
+                limit,
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )?,
+        })
+    }
+
+    /// Saves the guild's plays in `[from, until)`, both Unix timestamps in
+    /// milliseconds, as a new playlist for the user, in the order they played.
+    /// Keeps at most `limit` tracks. Returns `None` if nothing can be saved.
+    pub fn save_plays_as_playlist(
+        &self,
+        user_id: Id<UserMarker>,
+        name: &str,
+        guild_id: Id<GuildMarker>,
+        (from, until): (i64, i64),
+        limit: usize,
+    ) -> Result<Option<Playlist>> {
+        let name = {
+            let mut conn = self.conn();
+            let tx = conn.transaction()?;
+            let now = chrono::Utc::now().timestamp_millis();
+            let saveable: u64 = tx.query_row(
+                "SELECT COUNT(*) FROM plays
+                 WHERE guild_id = ?1 AND started_at >= ?2 AND started_at < ?3 AND encoded IS NOT NULL",
+                params![guild_id.get(), from, until],
+                |row| row.get(0),
+            )?;
+            if saveable == 0 {
+                return Ok(None);
+            }
+
+            let (id, name) = create_playlist_with_free_name(&tx, user_id, name, now)?;
+            tx.execute(
+                "INSERT INTO playlist_tracks (playlist_id, encoded, title, author, uri, length_ms, is_stream, added_at)
+                 SELECT ?1, encoded, title, author, uri, length_ms, is_stream, ?2 FROM plays
+                 WHERE guild_id = ?3 AND started_at >= ?4 AND started_at < ?5 AND encoded IS NOT NULL
+                 ORDER BY id LIMIT ?6",
+                params![id, now, guild_id.get(), from, until, limit],
+            )?;
+            tx.commit()?;
+            name
+        };
+
+        let playlist = self
+            .playlist(user_id, &name)?
+            .ok_or_else(|| anyhow!("The saved playlist disappeared."))?;
+        Ok(Some(playlist))
+    }
+
+    /// Up to `limit` of the filtered plays, newest first.
+    pub fn plays(&self, filter: &PlayFilter, limit: usize) -> Result<Vec<Play>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT requester_id, title, author, uri, started_at, listened_ms FROM plays
+             WHERE {} ORDER BY id DESC LIMIT ?4",
+            PLAY_FILTER
+        ))?;
+        let (guild_id, requester_id, since) = filter.params();
+        let plays = stmt
+            .query_map(params![guild_id, requester_id, since, limit], play_from_row)?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(plays)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::fixtures::play;
+
+    fn new_play<'a>(requester: u64, title: &'a str, author: &'a str) -> NewPlay<'a> {
+        NewPlay {
+            author,
+            ..play(requester, title)
+        }
+    }
+
+    #[test]
+    fn test_play_end_closes_the_latest_open_play() {
+        let storage = Storage::open_in_memory().unwrap();
+        let (guild, other_guild) = (Id::new(10), Id::new(20));
+        let filter = PlayFilter {
+            guild_id: guild,
+            requester_id: None,
+            since: None,
+        };
+
+        storage
+            .log_play_start(guild, &new_play(1, "a", "A"))
+            .unwrap();
+        storage.log_play_end(guild).unwrap();
+        storage
+            .log_play_start(guild, &new_play(1, "b", "A"))
+            .unwrap();
+        storage
+            .log_play_start(other_guild, &new_play(1, "c", "A"))
+            .unwrap();
+        storage.log_play_end(other_guild).unwrap();
+
+        let plays = storage.plays(&filter, 10).unwrap();
+        assert_eq!(plays.len(), 2);
+        assert_eq!((plays[0].title.as_str(), plays[0].listened_ms), ("b", None));
+        assert!(plays[1].listened_ms.is_some_and(|ms| ms <= 180_000));
+    }
+
+    #[test]
+    fn test_plays_are_saved_as_a_playlist_in_play_order() {
+        let storage = Storage::open_in_memory().unwrap();
+        let (guild, user) = (Id::new(10), Id::new(1));
+        for title in ["a", "b", "c"] {
+            storage
+                .log_play_start(guild, &new_play(1, title, "A"))
+                .unwrap();
+        }
+        storage
+            .log_play_start(Id::new(20), &new_play(1, "elsewhere", "A"))
+            .unwrap();
+        storage.create_playlist(user, "Session").unwrap();
+
+        let now = chrono::Utc::now().timestamp_millis();
+        let session = (now - 60_000, now + 60_000);
+        let playlist = storage
+            .save_plays_as_playlist(user, "Session", guild, session, 2)
+            .unwrap()
+            .unwrap();
+        assert_eq!(playlist.name, "Session (2)");
+        let titles: Vec<_> = storage
+            .playlist_tracks(playlist.id)
+            .unwrap()
+            .into_iter()
+            .map(|t| t.title)
+            .collect();
+        assert_eq!(titles, ["a", "b"]);
+
+        let before = (0, now - 60_000);
+        assert_eq!(
+            storage
+                .save_plays_as_playlist(user, "Old", guild, before, 2)
+                .unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_stats_rank_and_filter_plays() {
+        let storage = Storage::open_in_memory().unwrap();
+        let guild = Id::new(10);
+        for play in [
+            new_play(1, "a", "A"),
+            new_play(1, "a", "A"),
+            new_play(2, "b", "B"),
+            new_play(2, "c", "A"),
+            new_play(0, "d", "C"),
+        ] {
+            storage.log_play_start(guild, &play).unwrap();
+            storage.log_play_end(guild).unwrap();
+        }
+        let mut filter = PlayFilter {
+            guild_id: guild,
+            requester_id: None,
+            since: None,
+        };
+
+        let stats = storage.play_stats(&filter, 2).unwrap();
+        assert_eq!(stats.plays, 5);
+        assert_eq!(
+            stats.top_tracks[0],
+            TrackPlays {
+                title: "a".to_string(),
+                author: "A".to_string(),
+                plays: 2
+            }
+        );
+        assert_eq!(
+            stats.top_artists,
+            [("A".to_string(), 3), ("C".to_string(), 1)]
+        );
+        // Plays without a requester don't rank.
+        assert_eq!(stats.top_requesters, [(Id::new(2), 2), (Id::new(1), 2)]);
+        assert_eq!(stats.busiest_hours.iter().map(|(_, n)| n).sum::<u64>(), 5);
+
+        filter.requester_id = Some(Id::new(2));
+        let stats = storage.play_stats(&filter, 5).unwrap();
+        assert_eq!(stats.plays, 2);
+        assert_eq!(stats.top_artists.len(), 2);
+
+        filter.since = Some(chrono::Utc::now().timestamp_millis() + 60_000);
+        assert_eq!(
+            storage.play_stats(&filter, 5).unwrap(),
+            PlayStats::default()
+        );
+    }
+}
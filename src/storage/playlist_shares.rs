@@ -0,0 +1,286 @@
+use std::hash::{BuildHasher, Hasher, RandomState};
+
+use anyhow::{Result, anyhow};
+use rusqlite::{OptionalExtension, Row, params};
+use twilight_model::id::{Id, marker::UserMarker};
+
+use super::playlists::create_playlist_with_free_name;
+use super::{Playlist, Storage};
+
+/// Letters and digits that can't be mistaken for one another.
+const SHARE_CODE_ALPHABET: &[u8] = b"23456789ABCDEFGHJKMNPQRSTUVWXYZ";
+pub const SHARE_CODE_LEN: usize = 8;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlaylistShare {
+    pub code: String,
+    pub playlist_id: i64,
+    pub playlist_name: String,
+    pub created_by: Id<UserMarker>,
+    /// Unix timestamps in milliseconds.
+    pub created_at: i64,
+    pub expires_at: Option<i64>,
+    pub revoked_at: Option<i64>,
+    /// How many times the playlist was imported with this code.
+    pub uses: u64,
+}
+
+impl PlaylistShare {
+    /// Whether the code can still be imported at `now`.
+    pub fn is_active(&self, now: i64) -> bool {
+        self.revoked_at.is_none() && self.expires_at.is_none_or(|expires_at| now < expires_at)
+    }
+}
+
+/// A random code such as `K7QM3XDP`.
+pub fn generate_share_code() -> String {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_i64(chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default());
+    let mut bits = hasher.finish();
+    let base = SHARE_CODE_ALPHABET.len() as u64;
+    (0..SHARE_CODE_LEN)
+        .map(|_| {
+            let c = SHARE_CODE_ALPHABET[(bits % base) as usize];
+            bits /= base;
+            char::from(c)
+        })
+        .collect()
+}
+
+/// Accepts codes in any case and with dashes or spaces, as people retype them.
+pub fn normalize_share_code(input: &str) -> Option<String> {
+    let code: String = input
+        .chars()
+        .filter(|c| !matches!(c, '-' | ' '))
+        .map(|c| c.to_ascii_uppercase())
+        .collect();
+    let valid =
+        code.len() == SHARE_CODE_LEN && code.bytes().all(|b| SHARE_CODE_ALPHABET.contains(&b));
+    valid.then_some(code)
+}
+
+const SHARE_COLUMNS: &str = "s.code, s.playlist_id, p.name, s.created_by, s.created_at,
+     s.expires_at, s.revoked_at, s.uses";
+
+fn share_from_row(row: &Row) -> rusqlite::Result<PlaylistShare> {
+    Ok(PlaylistShare {
+        code: row.get(0)?,
+        playlist_id: row.get(1)?,
+        playlist_name: row.get(2)?,
+        created_by: Id::new(row.get(3)?),
+        created_at: row.get(4)?,
+        expires_at: row.get(5)?,
+        revoked_at: row.get(6)?,
+        uses: row.get(7)?,
+    })
+}
+
+impl Storage {
+    /// Creates a share code for one of the user's playlists and returns it.
+    pub fn create_playlist_share(
+        &self,
+        user_id: Id<UserMarker>,
+        playlist_id: i64,
+        expires_at: Option<i64>,
+    ) -> Result<String> {
+        let conn = self.conn();
+        // A clash is unlikely, but a retry is cheap.
+        for _ in 0..5 {
+            let code = generate_share_code();
+            let inserted = conn.execute(
+                "INSERT OR IGNORE INTO playlist_shares (code, playlist_id, created_by, created_at, expires_at)
+                 SELECT ?1, id, user_id, ?3, ?4 FROM playlists WHERE id = ?2 AND user_id = ?5",
+                params![
+                    code,
+                    playlist_id,
+                    chrono::Utc::now().timestamp_millis(),
+                    expires_at,
+                    user_id.get()
+                ],
+            )?;
+            if inserted > 0 {
+                return Ok(code);
+            }
+        }
+        Err(anyhow!("Could not create a share code, please try again."))
+    }
+
+    pub fn playlist_share(&self, code: &str) -> Result<Option<PlaylistShare>> {
+        let share = self
+            .conn()
+            .query_row(
+                &format!(
+                    "SELECT {} FROM playlist_shares s JOIN playlists p ON p.id = s.playlist_id
+                     WHERE s.code = ?1",
+                    SHARE_COLUMNS
+                ),
+                params![code],
+                share_from_row,
+            )
+            .optional()?;
+        Ok(share)
+    }
+
+    /// The user's codes that can still be imported, newest first.
+    pub fn active_playlist_shares(&self, user_id: Id<UserMarker>) -> Result<Vec<PlaylistShare>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM playlist_shares s JOIN playlists p ON p.id = s.playlist_id
+             WHERE s.created_by = ?1 AND s.revoked_at IS NULL
+               AND (s.expires_at IS NULL OR s.expires_at > ?2)
+             ORDER BY s.created_at DESC",
+            SHARE_COLUMNS
+        ))?;
+        let shares = stmt
+            .query_map(
+                params![user_id.get(), chrono::Utc::now().timestamp_millis()],
+                share_from_row,
+            )?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(shares)
+    }
+
+    /// Returns `false` if the user made no such code or already revoked it.
+    pub fn revoke_playlist_share(&self, user_id: Id<UserMarker>, code: &str) -> Result<bool> {
+        let revoked = self.conn().execute(
+            "UPDATE playlist_shares SET revoked_at = ?3
+             WHERE code = ?1 AND created_by = ?2 AND revoked_at IS NULL",
+            params![code, user_id.get(), chrono::Utc::now().timestamp_millis()],
+        )?;
+        Ok(revoked > 0)
+    }
+
+    /// Copies a shared playlist and its tracks into the user's library,
+    /// recording where it came from. Takes the first free name of `Name`,
+    /// `Name (2)`, `Name (3)`, …
+    pub fn clone_shared_playlist(
+        &self,
+        share: &PlaylistShare,
+        user_id: Id<UserMarker>,
+    ) -> Result<Playlist> {
+        let name = {
+            let mut conn = self.conn();
+            let tx = conn.transaction()?;
+            let now = chrono::Utc::now().timestamp_millis();
+
+            let (id, name) =
+                create_playlist_with_free_name(&tx, user_id, &share.playlist_name, now)?;
+            tx.execute(
+                "UPDATE playlists SET source_code = ?2, source_name = ?3, source_owner_id = ?4, imported_at = ?5
+                 WHERE id = ?1",
+                params![
+                    id,
+                    share.code,
+                    share.playlist_name,
+                    share.created_by.get(),
+                    now
+                ],
+            )?;
+            tx.execute(
+                "INSERT INTO playlist_tracks (playlist_id, encoded, title, author, uri, length_ms, is_stream, added_at)
+                 SELECT ?1, encoded, title, author, uri, length_ms, is_stream, ?2 FROM playlist_tracks
+                 WHERE playlist_id = ?3 ORDER BY id",
+                params![id, now, share.playlist_id],
+            )?;
+            tx.execute(
+                "UPDATE playlist_shares SET uses = uses + 1 WHERE code = ?1",
+                params![share.code],
+            )?;
+            tx.commit()?;
+            name
+        };
+
+        self.playlist(user_id, &name)?
+            .ok_or_else(|| anyhow!("The imported playlist disappeared."))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::fixtures::track;
+
+    fn shared_playlist(storage: &Storage, owner: Id<UserMarker>) -> i64 {
+        let id = storage
+            .create_playlist(owner, "Road Trip")
+            .unwrap()
+            .unwrap();
+        storage
+            .add_playlist_tracks(id, &[track("a"), track("b")])
+            .unwrap();
+        id
+    }
+
+    #[test]
+    fn test_share_codes_are_normalized() {
+        let code = generate_share_code();
+        assert_eq!(code.len(), SHARE_CODE_LEN);
+        assert_eq!(
+            normalize_share_code(&code.to_lowercase()),
+            Some(code.clone())
+        );
+        assert_eq!(
+            normalize_share_code("k7qm-3xdp"),
+            Some("K7QM3XDP".to_string())
+        );
+        // `0` and `O` are left out of the alphabet.
+        assert_eq!(normalize_share_code("K7QM3XD0"), None);
+        assert_eq!(normalize_share_code("K7QM"), None);
+    }
+
+    #[test]
+    fn test_clone_copies_tracks_and_records_provenance() {
+        let storage = Storage::open_in_memory().unwrap();
+        let (owner, other) = (Id::new(1), Id::new(2));
+        let id = shared_playlist(&storage, owner);
+        storage.create_playlist(other, "road trip").unwrap();
+
+        assert!(storage.create_playlist_share(other, id, None).is_err());
+        let code = storage.create_playlist_share(owner, id, None).unwrap();
+        let share = storage.playlist_share(&code).unwrap().unwrap();
+        let clone = storage.clone_shared_playlist(&share, other).unwrap();
+
+        assert_eq!(clone.name, "Road Trip (2)");
+        assert_eq!(clone.track_count, 2);
+        let source = clone.source.unwrap();
+        assert_eq!(
+            (source.code.as_str(), source.owner_id),
+            (code.as_str(), owner)
+        );
+        assert_eq!(source.name, "Road Trip");
+        assert_eq!(storage.playlist_share(&code).unwrap().unwrap().uses, 1);
+
+        // The clone is independent of the original.
+        storage.delete_playlist(owner, id).unwrap();
+        assert_eq!(storage.playlist_tracks(clone.id).unwrap().len(), 2);
+        assert_eq!(storage.playlist_share(&code).unwrap(), None);
+    }
+
+    #[test]
+    fn test_revoked_and_expired_codes_are_inactive() {
+        let storage = Storage::open_in_memory().unwrap();
+        let owner = Id::new(1);
+        let id = shared_playlist(&storage, owner);
+        let now = chrono::Utc::now().timestamp_millis();
+
+        let expiring = storage
+            .create_playlist_share(owner, id, Some(now + 60_000))
+            .unwrap();
+        let share = storage.playlist_share(&expiring).unwrap().unwrap();
+        assert!(share.is_active(now));
+        assert!(!share.is_active(now + 60_000));
+
+        let code = storage.create_playlist_share(owner, id, None).unwrap();
+        assert_eq!(storage.active_playlist_shares(owner).unwrap().len(), 2);
+        assert!(!storage.revoke_playlist_share(Id::new(2), &code).unwrap());
+        assert!(storage.revoke_playlist_share(owner, &code).unwrap());
+        assert!(
+            !storage
+                .playlist_share(&code)
+                .unwrap()
+                .unwrap()
+                .is_active(now)
+        );
+        assert_eq!(storage.active_playlist_shares(owner).unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,317 @@
+use anyhow::Result;
+use rusqlite::{Connection, OptionalExtension, Row, params};
+use twilight_model::id::{Id, marker::UserMarker};
+
+use super::Storage;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Playlist {
+    pub id: i64,
+    pub name: String,
+    pub track_count: usize,
+    /// Unix timestamp in milliseconds.
+    pub created_at: i64,
+    /// Set on playlists cloned from a share code.
+    pub source: Option<PlaylistSource>,
+}
+
+/// Where an imported playlist came from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlaylistSource {
+    pub code: String,
+    pub name: String,
+    pub owner_id: Id<UserMarker>,
+    /// Unix timestamp in milliseconds.
+    pub imported_at: i64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlaylistTrack {
+    pub id: i64,
+    /// The Lavalink-encoded track.
+    pub encoded: String,
+    pub title: String,
+    pub author: String,
+    pub uri: Option<String>,
+    pub length_ms: u64,
+    pub is_stream: bool,
+    /// Unix timestamp in milliseconds.
+    pub added_at: i64,
+}
+
+/// The fields of a saved track that the caller provides.
+pub struct NewPlaylistTrack<'a> {
+    pub encoded: &'a str,
+    pub title: &'a str,
+    pub author: &'a str,
+    pub uri: Option<&'a str>,
+    pub length_ms: u64,
+    pub is_stream: bool,
+}
+
+const PLAYLIST_COLUMNS: &str = "p.id, p.name, p.created_at,
+     (SELECT COUNT(*) FROM playlist_tracks t WHERE t.playlist_id = p.id),
+     p.source_code, p.source_name, p.source_owner_id, p.imported_at";
+
+fn playlist_from_row(row: &Row) -> rusqlite::Result<Playlist> {
+    let source = match row.get::<_, Option<String>>(4)? {
+        Some(code) => Some(PlaylistSource {
+            code,
+            name: row.get(5)?,
+            owner_id: Id::new(row.get(6)?),
+            imported_at: row.get(7)?,
+        }),
+        None => None,
+    };
+    Ok(Playlist {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        created_at: row.get(2)?,
+        track_count: row.get(3)?,
+        source,
+    })
+}
+
+pub(super) fn playlist_track_from_row(row: &Row) -> rusqlite::Result<PlaylistTrack> {
+    Ok(PlaylistTrack {
+        id: row.get(0)?,
+        encoded: row.get(1)?,
+        title: row.get(2)?,
+        author: row.get(3)?,
+        uri: row.get(4)?,
+        length_ms: row.get(5)?,
+        is_stream: row.get(6)?,
+        added_at: row.get(7)?,
+    })
+}
+
+/// Creates a playlist called `name`, or `name (2)`, `name (3)`, … if the user
+/// already has one by that name. Returns its id and the name it got.
+pub(super) fn create_playlist_with_free_name(
+    conn: &Connection,
+    user_id: Id<UserMarker>,
+    name: &str,
+    now: i64,
+) -> rusqlite::Result<(i64, String)> {
+    let mut candidate = name.to_string();
+    let mut suffix = 1;
+    loop {
+        let inserted = conn.execute(
+            "INSERT OR IGNORE INTO playlists (user_id, name, created_at) VALUES (?1, ?2, ?3)",
+            params![user_id.get(), candidate, now],
+        )?;
+        if inserted > 0 {
+            return Ok((conn.last_insert_rowid(), candidate));
+        }
+        suffix += 1;
+        candidate = format!("{} ({})", name, suffix);
+    }
+}
+
+impl Storage {
+    /// Returns `None` if the user already has a playlist with that name.
+    pub fn create_playlist(&self, user_id: Id<UserMarker>, name: &str) -> Result<Option<i64>> {
+        let conn = self.conn();
+        let inserted = conn.execute(
+            "INSERT OR IGNORE INTO playlists (user_id, name, created_at) VALUES (?1, ?2, ?3)",
+            params![user_id.get(), name, chrono::Utc::now().timestamp_millis()],
+        )?;
+        Ok((inserted > 0).then(|| conn.last_insert_rowid()))
+    }
+
+    /// The user's playlists, by name.
+    pub fn playlists(&self, user_id: Id<UserMarker>) -> Result<Vec<Playlist>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM playlists p WHERE p.user_id = ?1 ORDER BY p.name",
+            PLAYLIST_COLUMNS
+        ))?;
+        let playlists = stmt
+            .query_map(params![user_id.get()], playlist_from_row)?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(playlists)
+    }
+
+    /// Looks up one of the user's playlists by name, ignoring case.
+    pub fn playlist(&self, user_id: Id<UserMarker>, name: &str) -> Result<Option<Playlist>> {
+        let playlist = self
+            .conn()
+            .query_row(
+                &format!(
+                    "SELECT {} FROM playlists p WHERE p.user_id = ?1 AND p.name = ?2",
+                    PLAYLIST_COLUMNS
+                ),
+                params![user_id.get(), name],
+                playlist_from_row,
+            )
+            .optional()?;
+        Ok(playlist)
+    }
+
+    /// Returns `false` if the user already has a playlist called `new_name`.
+    pub fn rename_playlist(
+        &self,
+        user_id: Id<UserMarker>,
+        id: i64,
+        new_name: &str,
+    ) -> Result<bool> {
+        let renamed = self.conn().execute(
+            "UPDATE OR IGNORE playlists SET name = ?3 WHERE user_id = ?1 AND id = ?2",
+            params![user_id.get(), id, new_name],
+        )?;
+        Ok(renamed > 0)
+    }
+
+    /// Deletes the playlist and its tracks. Returns `false` if there was none.
+    pub fn delete_playlist(&self, user_id: Id<UserMarker>, id: i64) -> Result<bool> {
+        let removed = self.conn().execute(
+            "DELETE FROM playlists WHERE user_id = ?1 AND id = ?2",
+            params![user_id.get(), id],
+        )?;
+        Ok(removed > 0)
+    }
+
+    /// Appends tracks to a playlist in one transaction.
+    pub fn add_playlist_tracks(&self, playlist_id: i64, tracks: &[NewPlaylistTrack]) -> Result<()> {
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        {
+            let mut stmt = tx.prepare(
+                "INSERT INTO playlist_tracks (playlist_id, encoded, title, author, uri, length_ms, is_stream, added_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            )?;
+            let now = chrono::Utc::now().timestamp_millis();
+            for track in tracks {
+                stmt.execute(params![
+                    playlist_id,
+                    track.encoded,
+                    track.title,
+                    track.author,
+                    track.uri,
+                    track.length_ms,
+                    track.is_stream,
+                    now
+                ])?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// All tracks of a playlist, in the order they were added.
+    pub fn playlist_tracks(&self, playlist_id: i64) -> Result<Vec<PlaylistTrack>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare(
+            "SELECT id, encoded, title, author, uri, length_ms, is_stream, added_at FROM playlist_tracks
+             WHERE playlist_id = ?1 ORDER BY id",
+        )?;
+        let tracks = stmt
+            .query_map(params![playlist_id], playlist_track_from_row)?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(tracks)
+    }
+
+    /// Removes the track at zero-based `index` and returns it.
+    pub fn remove_playlist_track(
+        &self,
+        playlist_id: i64,
+        index: usize,
+    ) -> Result<Option<PlaylistTrack>> {
+        let conn = self.conn();
+        let track = conn
+            .query_row(
+                "SELECT id, encoded, title, author, uri, length_ms, is_stream, added_at FROM playlist_tracks
+                 WHERE playlist_id = ?1 ORDER BY id LIMIT 1 OFFSET ?2",
+                params![playlist_id, index],
+                playlist_track_from_row,
+            )
+            .optional()?;
+        if let Some(track) = &track {
+            conn.execute(
+                "DELETE FROM playlist_tracks WHERE id = ?1",
+                params![track.id],
+            )?;
+        }
+        Ok(track)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::fixtures::{STREAM_LENGTH_MS, stream, track};
+
+    #[test]
+    fn test_playlist_names_are_unique_per_user_ignoring_case() {
+        let storage = Storage::open_in_memory().unwrap();
+        let (alice, bob) = (Id::new(1), Id::new(2));
+
+        let id = storage
+            .create_playlist(alice, "Road Trip")
+            .unwrap()
+            .unwrap();
+        assert_eq!(storage.create_playlist(alice, "road trip").unwrap(), None);
+        assert!(storage.create_playlist(bob, "Road Trip").unwrap().is_some());
+
+        assert_eq!(
+            storage.playlist(alice, "ROAD TRIP").unwrap().unwrap().id,
+            id
+        );
+        storage.create_playlist(alice, "Chill").unwrap();
+        assert!(!storage.rename_playlist(alice, id, "chill").unwrap());
+        assert!(storage.rename_playlist(alice, id, "Summer").unwrap());
+        let names: Vec<_> = storage
+            .playlists(alice)
+            .unwrap()
+            .into_iter()
+            .map(|p| p.name)
+            .collect();
+        assert_eq!(names, ["Chill", "Summer"]);
+    }
+
+    #[test]
+    fn test_streams_keep_their_flag_and_length() {
+        let storage = Storage::open_in_memory().unwrap();
+        let id = storage
+            .create_playlist(Id::new(1), "radio")
+            .unwrap()
+            .unwrap();
+        storage
+            .add_playlist_tracks(id, &[track("a"), stream("live")])
+            .unwrap();
+
+        let tracks = storage.playlist_tracks(id).unwrap();
+        assert!(!tracks[0].is_stream);
+        assert!(tracks[1].is_stream);
+        assert_eq!(tracks[1].length_ms, STREAM_LENGTH_MS);
+    }
+
+    #[test]
+    fn test_tracks_keep_their_order_and_go_with_the_playlist() {
+        let storage = Storage::open_in_memory().unwrap();
+        let user = Id::new(1);
+        let id = storage.create_playlist(user, "mix").unwrap().unwrap();
+        storage
+            .add_playlist_tracks(id, &[track("a"), track("b"), track("c")])
+            .unwrap();
+        assert_eq!(
+            storage.playlist(user, "mix").unwrap().unwrap().track_count,
+            3
+        );
+
+        let removed = storage.remove_playlist_track(id, 1).unwrap().unwrap();
+        assert_eq!(removed.title, "b");
+        assert_eq!(storage.remove_playlist_track(id, 5).unwrap(), None);
+        let titles: Vec<_> = storage
+            .playlist_tracks(id)
+            .unwrap()
+            .into_iter()
+            .map(|t| t.title)
+            .collect();
+        assert_eq!(titles, ["a", "c"]);
+
+        assert!(!storage.delete_playlist(Id::new(2), id).unwrap());
+        assert!(storage.delete_playlist(user, id).unwrap());
+        assert!(storage.playlist_tracks(id).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,177 @@
+use anyhow::Result;
+use rusqlite::{OptionalExtension, params};
+use twilight_model::id::{
+    Id,
+    marker::{GuildMarker, UserMarker},
+};
+
+use super::Storage;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PodcastSubscription {
+    pub feed_url: String,
+    pub title: String,
+    pub subscribed_by: Id<UserMarker>,
+    /// Unix timestamp in milliseconds.
+    pub subscribed_at: i64,
+}
+
+impl Storage {
+    /// Subscribes the guild to a feed, refreshing the title if it already was.
+    pub fn subscribe_podcast(
+        &self,
+        guild_id: Id<GuildMarker>,
+        feed_url: &str,
+        title: &str,
+        user_id: Id<UserMarker>,
+    ) -> Result<()> {
+        self.conn().execute(
+            "INSERT INTO podcast_subscriptions (guild_id, feed_url, title, subscribed_by, subscribed_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)
+             ON CONFLICT (guild_id, feed_url) DO UPDATE SET title = excluded.title",
+            params![
+                guild_id.get(),
+                feed_url,
+                title,
+                user_id.get(),
+                chrono::Utc::now().timestamp_millis()
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Returns `false` if the guild wasn't subscribed to the feed.
+    pub fn unsubscribe_podcast(&self, guild_id: Id<GuildMarker>, feed_url: &str) -> Result<bool> {
+        let removed = self.conn().execute(
+            "DELETE FROM podcast_subscriptions WHERE guild_id = ?1 AND feed_url = ?2",
+            params![guild_id.get(), feed_url],
+        )?;
+        Ok(removed > 0)
+    }
+
+    pub fn podcast_subscriptions(
+        &self,
+        guild_id: Id<GuildMarker>,
+    ) -> Result<Vec<PodcastSubscription>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare(
+            "SELECT feed_url, title, subscribed_by, subscribed_at FROM podcast_subscriptions
+             WHERE guild_id = ?1 ORDER BY title COLLATE NOCASE",
+        )?;
+        let subscriptions = stmt
+            .query_map(params![guild_id.get()], |row| {
+                Ok(PodcastSubscription {
+                    feed_url: row.get(0)?,
+                    title: row.get(1)?,
+                    subscribed_by: Id::new(row.get(2)?),
+                    subscribed_at: row.get(3)?,
+                })
+            })?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(subscriptions)
+    }
+
+    /// Remembers how far into an episode the guild has listened.
+    pub fn save_episode_position(
+        &self,
+        guild_id: Id<GuildMarker>,
+        episode_id: &str,
+        position_ms: u64,
+    ) -> Result<()> {
+        self.conn().execute(
+            "INSERT INTO podcast_progress (guild_id, episode_id, position_ms, updated_at)
+             VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT (guild_id, episode_id) DO UPDATE
+             SET position_ms = excluded.position_ms, updated_at = excluded.updated_at",
+            params![
+                guild_id.get(),
+                episode_id,
+                position_ms,
+                chrono::Utc::now().timestamp_millis()
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn episode_position(
+        &self,
+        guild_id: Id<GuildMarker>,
+        episode_id: &str,
+    ) -> Result<Option<u64>> {
+        let position = self
+            .conn()
+            .query_row(
+                "SELECT position_ms FROM podcast_progress WHERE guild_id = ?1 AND episode_id = ?2",
+                params![guild_id.get(), episode_id],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(position)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_subscriptions_round_trip() {
+        let storage = Storage::open_in_memory().unwrap();
+        let guild = Id::new(1);
+        let user = Id::new(2);
+        storage
+            .subscribe_podcast(guild, "https://a.example/feed", "Zebra Talk", user)
+            .unwrap();
+        storage
+            .subscribe_podcast(guild, "https://b.example/feed", "apple hour", user)
+            .unwrap();
+        storage
+            .subscribe_podcast(
+                guild,
+                "https://a.example/feed",
+                "Zebra Talk (renamed)",
+                user,
+            )
+            .unwrap();
+
+        let titles: Vec<_> = storage
+            .podcast_subscriptions(guild)
+            .unwrap()
+            .into_iter()
+            .map(|sub| sub.title)
+            .collect();
+        assert_eq!(titles, ["apple hour", "Zebra Talk (renamed)"]);
+        assert!(
+            storage
+                .podcast_subscriptions(Id::new(3))
+                .unwrap()
+                .is_empty()
+        );
+
+        assert!(
+            storage
+                .unsubscribe_podcast(guild, "https://b.example/feed")
+                .unwrap()
+        );
+        assert!(
+            !storage
+                .unsubscribe_podcast(guild, "https://b.example/feed")
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_episode_position_is_overwritten() {
+        let storage = Storage::open_in_memory().unwrap();
+        let guild = Id::new(1);
+        assert_eq!(storage.episode_position(guild, "ep-1").unwrap(), None);
+        storage.save_episode_position(guild, "ep-1", 1_000).unwrap();
+        storage
+            .save_episode_position(guild, "ep-1", 65_000)
+            .unwrap();
+        assert_eq!(
+            storage.episode_position(guild, "ep-1").unwrap(),
+            Some(65_000)
+        );
+    }
+}
@@ -0,0 +1,177 @@
+use anyhow::Result;
+use rusqlite::{OptionalExtension, params};
+use twilight_model::id::{
+    Id,
+    marker::{GuildMarker, UserMarker},
+};
+
+use super::{Storage, TrackPlays};
+
+/// A user's year of listening in one guild.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Wrapped {
+    pub plays: u64,
+    pub listened_ms: u64,
+    pub top_tracks: Vec<TrackPlays>,
+    /// Artist and play count.
+    pub top_artists: Vec<(String, u64)>,
+    /// Who was in the channel for the most of the user's plays, and for how many.
+    pub companions: Vec<(Id<UserMarker>, u64)>,
+    pub first_play: Option<FirstPlay>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FirstPlay {
+    pub title: String,
+    pub author: String,
+    pub uri: Option<String>,
+    /// Unix timestamp in milliseconds.
+    pub started_at: i64,
+}
+
+/// The plays a user heard between `?3` and `?4`: those they were in the
+/// channel for, and those they requested. `?1` is the guild, `?2` the user.
+const HEARD: &str = "WITH heard AS (
+         SELECT * FROM plays
+         WHERE guild_id = ?1 AND started_at >= ?3 AND started_at < ?4
+           AND (requester_id = ?2
+                OR id IN (SELECT play_id FROM play_listeners WHERE user_id = ?2))
+     )";
+
+impl Storage {
+    /// Aggregates the plays the user heard in `[from, until)`, both Unix
+    /// timestamps in milliseconds, keeping the top `limit` of each ranking.
+    pub fn wrapped(
+        &self,
+        guild_id: Id<GuildMarker>,
+        user_id: Id<UserMarker>,
+        (from, until): (i64, i64),
+        limit: usize,
+    ) -> Result<Wrapped> {
+        let conn = self.conn();
+        let bounds = params![guild_id.get(), user_id.get(), from, until];
+        let ranked = params![guild_id.get(), user_id.get(), from, until, limit];
+
+        let (plays, listened_ms) = conn.query_row(
+            &format!(
+                "{} SELECT COUNT(*), COALESCE(SUM(listened_ms), 0) FROM heard",
+                HEARD
+            ),
+            bounds,
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
+        if plays == 0 {
+            return Ok(Wrapped::default());
+        }
+
+        let top_tracks = conn
+            .prepare(&format!(
+                "{} SELECT title, author, COUNT(*) AS n FROM heard GROUP BY title, author
+                 ORDER BY n DESC, MAX(id) DESC LIMIT ?5",
+                HEARD
+            ))?
+            .query_map(ranked, |row| {
+                Ok(TrackPlays {
+                    title: row.get(0)?,
+                    author: row.get(1)?,
+                    plays: row.get(2)?,
+                })
+            })?
+            .collect::<rusqlite::Result<_>>()?;
+        let top_artists = conn
+            .prepare(&format!(
+                "{} SELECT author, COUNT(*) AS n FROM heard GROUP BY author
+                 ORDER BY n DESC, MAX(id) DESC LIMIT ?5",
+                HEARD
+            ))?
+            .query_map(ranked, |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<rusqlite::Result<_>>()?;
+        let companions = conn
+            .prepare(&format!(
+                "{} SELECT l.user_id, COUNT(*) AS n FROM heard
+                 JOIN play_listeners l ON l.play_id = heard.id
+                 WHERE l.user_id != ?2
+                 GROUP BY l.user_id ORDER BY n DESC, MAX(heard.id) DESC LIMIT ?5",
+                HEARD
+            ))?
+            .query_map(ranked, |row| Ok((Id::new(row.get(0)?), row.get(1)?)))?
+            .collect::<rusqlite::Result<_>>()?;
+        let first_play = conn
+            .query_row(
+                &format!(
+                    "{} SELECT title, author, uri, started_at FROM heard ORDER BY started_at, id LIMIT 1",
+                    HEARD
+                ),
+                bounds,
+                |row| {
+                    Ok(FirstPlay {
+                        title: row.get(0)?,
+                        author: row.get(1)?,
+                        uri: row.get(2)?,
+                        started_at: row.get(3)?,
+                    })
+                },
+            )
+            .optional()?;
+
+        Ok(Wrapped {
+            plays,
+            listened_ms,
+            top_tracks,
+            top_artists,
+            companions,
+            first_play,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::{NewPlay, fixtures};
+
+    fn play<'a>(requester: u64, title: &'a str, listeners: &'a [Id<UserMarker>]) -> NewPlay<'a> {
+        NewPlay {
+            listeners,
+            ..fixtures::play(requester, title)
+        }
+    }
+
+    #[test]
+    fn test_wrapped_counts_plays_the_user_heard() {
+        let storage = Storage::open_in_memory().unwrap();
+        let guild = Id::new(10);
+        let (alice, bob, carol) = (Id::new(1), Id::new(2), Id::new(3));
+
+        storage
+            .log_play_start(guild, &play(1, "first", &[bob]))
+            .unwrap();
+        storage
+            .log_play_start(guild, &play(2, "second", &[alice, carol]))
+            .unwrap();
+        storage
+            .log_play_start(guild, &play(2, "second", &[alice, carol]))
+            .unwrap();
+        // Alice wasn't there for this one.
+        storage
+            .log_play_start(guild, &play(3, "third", &[bob]))
+            .unwrap();
+        storage
+            .log_play_start(Id::new(20), &play(1, "elsewhere", &[]))
+            .unwrap();
+
+        let now = chrono::Utc::now().timestamp_millis();
+        let wrapped = storage
+            .wrapped(guild, alice, (now - 60_000, now + 60_000), 5)
+            .unwrap();
+        assert_eq!(wrapped.plays, 3);
+        assert_eq!(wrapped.top_tracks[0].title, "second");
+        assert_eq!(wrapped.top_tracks[0].plays, 2);
+        assert_eq!(wrapped.top_artists, [("Some Band".to_string(), 3)]);
+        assert_eq!(wrapped.companions, [(carol, 2), (bob, 1)]);
+        assert_eq!(wrapped.first_play.unwrap().title, "first");
+
+        let last_year = storage.wrapped(guild, alice, (0, now - 60_000), 5).unwrap();
+        assert_eq!(last_year, Wrapped::default());
+    }
+}
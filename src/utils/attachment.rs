@@ -0,0 +1,122 @@
+use std::path::Path;
+
+use anyhow::{Result, anyhow};
+use lavalink_rs::model::track::TrackData;
+use twilight_model::channel::Attachment;
+
+/// Largest attachment `/play` accepts, in bytes.
+pub const MAX_ATTACHMENT_SIZE: u64 = 25 * 1024 * 1024;
+/// How many files of one message get queued.
+pub const MAX_ATTACHMENTS: usize = 10;
+const AUDIO_EXTENSIONS: &[&str] = &["mp3", "ogg", "oga", "opus", "flac", "wav", "m4a", "aac"];
+/// What Lavalink's HTTP source reports for files without tags.
+const UNKNOWN_TITLE: &str = "Unknown title";
+/// Where Discord serves attachments from. Their links expire after a day.
+const ATTACHMENT_HOSTS: &[&str] = &["cdn.discordapp.com", "media.discordapp.net"];
+/// The answer to saving an uploaded file anywhere.
+pub const UPLOAD_NOT_SAVED: &str = "Uploaded files can't be saved, as their links expire.";
+
+/// Whether a file is audio: both its content type and its extension have to
+/// say so.
+pub fn is_audio(filename: &str, content_type: Option<&str>) -> bool {
+    let audio_type = content_type.is_some_and(|content_type| {
+        let mime = content_type.split(';').next().unwrap_or_default().trim();
+        mime.starts_with("audio/") || mime == "application/ogg"
+    });
+    audio_type
+        && Path::new(filename)
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| AUDIO_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+}
+
+/// Whether `url` is a Discord attachment link, which stops working once it
+/// expires and so must not be saved.
+pub fn is_attachment_url(url: &str) -> bool {
+    let Some(rest) = url.strip_prefix("https://") else {
+        return false;
+    };
+    let (host, path) = rest.split_once('/').unwrap_or((rest, ""));
+    ATTACHMENT_HOSTS.contains(&host.to_lowercase().as_str())
+        && (path.starts_with("attachments/") || path.starts_with("ephemeral-attachments/"))
+}
+
+/// Whether the track plays from an uploaded file, see [`is_attachment_url`].
+pub fn is_upload(track: &TrackData) -> bool {
+    track.info.uri.as_deref().is_some_and(is_attachment_url)
+}
+
+/// Checks that an attachment is an audio file small enough to play.
+pub fn validate_audio_attachment(attachment: &Attachment) -> Result<()> {
+    if !is_audio(&attachment.filename, attachment.content_type.as_deref()) {
+        return Err(anyhow!("`{}` is not an audio file.", attachment.filename));
+    }
+    if attachment.size > MAX_ATTACHMENT_SIZE {
+        return Err(anyhow!(
+            "`{}` is larger than {} MiB.",
+            attachment.filename,
+            MAX_ATTACHMENT_SIZE / 1024 / 1024
+        ));
+    }
+    Ok(())
+}
+
+/// `my_song-final.mp3` becomes `my_song-final`.
+pub fn title_from_filename(filename: &str) -> String {
+    Path::new(filename)
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .filter(|stem| !stem.is_empty())
+        .unwrap_or(filename)
+        .to_string()
+}
+
+/// Untagged uploads would show up as "Unknown title", so name them after the file.
+pub fn apply_filename_title(track: &mut TrackData, filename: &str) {
+    if track.info.title.trim().is_empty() || track.info.title == UNKNOWN_TITLE {
+        track.info.title = title_from_filename(filename);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_audio_needs_type_and_extension() {
+        assert!(is_audio("song.mp3", Some("audio/mpeg")));
+        assert!(is_audio("Track 01.FLAC", Some("audio/flac")));
+        assert!(is_audio("voice.opus", Some("audio/ogg; codecs=opus")));
+        assert!(!is_audio("upload", Some("audio/mpeg")));
+        assert!(!is_audio("song.mp3", Some("image/png")));
+        assert!(!is_audio("voice.opus", Some("application/octet-stream")));
+        assert!(!is_audio("song.mp3", None));
+        assert!(!is_audio("notes.txt", None));
+    }
+
+    #[test]
+    fn test_attachment_urls() {
+        assert!(is_attachment_url(
+            "https://cdn.discordapp.com/attachments/1/2/song.mp3?ex=65&is=64&hm=ab"
+        ));
+        assert!(is_attachment_url(
+            "https://media.discordapp.net/attachments/1/2/song.mp3"
+        ));
+        assert!(!is_attachment_url(
+            "https://cdn.discordapp.com/avatars/1/a.png"
+        ));
+        assert!(!is_attachment_url(
+            "https://example.com/attachments/song.mp3"
+        ));
+        assert!(!is_attachment_url(
+            "https://www.youtube.com/watch?v=dQw4w9WgXcQ"
+        ));
+    }
+
+    #[test]
+    fn test_title_from_filename() {
+        assert_eq!(title_from_filename("my_song-final.mp3"), "my_song-final");
+        assert_eq!(title_from_filename("archive.tar.gz"), "archive.tar");
+        assert_eq!(title_from_filename("noext"), "noext");
+    }
+}
@@ -0,0 +1,129 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Result, anyhow};
+use twilight_model::id::{Id, marker::GuildMarker};
+
+/// Largest clip that can be uploaded, in bytes.
+pub const MAX_CLIP_SIZE: u64 = 2 * 1024 * 1024;
+pub const MAX_CLIP_NAME_LEN: usize = 32;
+const CLIP_EXTENSIONS: &[&str] = &["mp3", "ogg", "wav", "flac", "m4a"];
+
+/// Clips are stored as `<data dir>/clips/<guild id>/<name>.<ext>`.
+pub fn guild_clip_dir(data_dir: &Path, guild_id: Id<GuildMarker>) -> PathBuf {
+    data_dir.join("clips").join(guild_id.to_string())
+}
+
+/// Clip names end up in file names, so only allow a safe subset of characters.
+pub fn validate_clip_name(name: &str) -> Result<String> {
+    let name = name.trim().to_lowercase();
+    if name.is_empty() || name.len() > MAX_CLIP_NAME_LEN {
+        return Err(anyhow!(
+            "Clip names must be between 1 and {} characters long.",
+            MAX_CLIP_NAME_LEN
+        ));
+    }
+    if !name
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(anyhow!(
+            "Clip names may only contain letters, numbers, `-` and `_`."
+        ));
+    }
+    Ok(name)
+}
+
+/// The extension to store an uploaded clip under, if it's a supported format.
+pub fn clip_extension(filename: &str, content_type: Option<&str>) -> Option<&'static str> {
+    let from_name = Path::new(filename)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(str::to_lowercase);
+    if let Some(ext) = from_name.and_then(|ext| CLIP_EXTENSIONS.iter().find(|e| **e == ext)) {
+        return Some(ext);
+    }
+
+    match content_type?.split(';').next()?.trim() {
+        "audio/mpeg" => Some("mp3"),
+        "audio/ogg" => Some("ogg"),
+        "audio/wav" | "audio/x-wav" => Some("wav"),
+        "audio/flac" | "audio/x-flac" => Some("flac"),
+        "audio/mp4" | "audio/x-m4a" => Some("m4a"),
+        _ => None,
+    }
+}
+
+/// Finds the stored file for `name`, whatever its extension.
+pub async fn find_clip(data_dir: &Path, guild_id: Id<GuildMarker>, name: &str) -> Option<PathBuf> {
+    let dir = guild_clip_dir(data_dir, guild_id);
+    for ext in CLIP_EXTENSIONS {
+        let path = dir.join(format!("{}.{}", name, ext));
+        if tokio::fs::try_exists(&path).await.unwrap_or(false) {
+            return Some(path);
+        }
+    }
+    None
+}
+
+pub async fn list_clips(data_dir: &Path, guild_id: Id<GuildMarker>) -> Result<Vec<String>> {
+    let dir = guild_clip_dir(data_dir, guild_id);
+    let mut names = Vec::new();
+    let mut entries = match tokio::fs::read_dir(&dir).await {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(names),
+        Err(e) => return Err(e.into()),
+    };
+    while let Some(entry) = entries.next_entry().await? {
+        if let Some(stem) = entry.path().file_stem().and_then(|stem| stem.to_str()) {
+            names.push(stem.to_string());
+        }
+    }
+    names.sort();
+    Ok(names)
+}
+
+pub async fn save_clip(
+    data_dir: &Path,
+    guild_id: Id<GuildMarker>,
+    name: &str,
+    extension: &str,
+    bytes: &[u8],
+) -> Result<PathBuf> {
+    let dir = guild_clip_dir(data_dir, guild_id);
+    tokio::fs::create_dir_all(&dir).await?;
+    if let Some(existing) = find_clip(data_dir, guild_id, name).await {
+        tokio::fs::remove_file(existing).await?;
+    }
+    let path = dir.join(format!("{}.{}", name, extension));
+    tokio::fs::write(&path, bytes).await?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_clip_name_normalizes_case() {
+        assert_eq!(validate_clip_name(" AirHorn ").unwrap(), "airhorn");
+    }
+
+    #[test]
+    fn test_validate_clip_name_rejects_paths() {
+        assert!(validate_clip_name("../secret").is_err());
+        assert!(validate_clip_name("a/b").is_err());
+        assert!(validate_clip_name("").is_err());
+    }
+
+    #[test]
+    fn test_clip_extension_prefers_filename() {
+        assert_eq!(clip_extension("horn.OGG", Some("audio/mpeg")), Some("ogg"));
+    }
+
+    #[test]
+    fn test_clip_extension_falls_back_to_content_type() {
+        assert_eq!(clip_extension("horn", Some("audio/mpeg")), Some("mp3"));
+        assert_eq!(clip_extension("horn.txt", Some("text/plain")), None);
+        assert_eq!(clip_extension("horn", None), None);
+    }
+}
@@ -0,0 +1,50 @@
+//! Just enough CSV writing for exports: quoting per RFC 4180, and no fields
+//! a spreadsheet would run as a formula.
+
+/// One CSV line, ending in `\r\n`.
+pub fn csv_line<S: AsRef<str>>(fields: &[S]) -> String {
+    let mut line = fields
+        .iter()
+        .map(|field| escape(field.as_ref()))
+        .collect::<Vec<_>>()
+        .join(",");
+    line.push_str("\r\n");
+    line
+}
+
+fn escape(field: &str) -> String {
+    // A leading quote makes spreadsheets show the text instead of evaluating it.
+    let field = if field.starts_with(['=', '+', '-', '@']) {
+        format!("'{}", field)
+    } else {
+        field.to_string()
+    };
+    if field.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fields_are_quoted_only_when_needed() {
+        assert_eq!(csv_line(&["a", "b c"]), "a,b c\r\n");
+        assert_eq!(
+            csv_line(&["Hello, World", "12\" Mix", "two\nlines"]),
+            "\"Hello, World\",\"12\"\" Mix\",\"two\nlines\"\r\n"
+        );
+        assert_eq!(csv_line::<&str>(&[]), "\r\n");
+    }
+
+    #[test]
+    fn test_formula_fields_are_defused() {
+        assert_eq!(
+            csv_line(&["=HYPERLINK(\"x\")", "+1", "-ish", "@me", "a=b"]),
+            "\"'=HYPERLINK(\"\"x\"\")\",'+1,'-ish,'@me,a=b\r\n"
+        );
+    }
+}
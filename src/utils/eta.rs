@@ -0,0 +1,125 @@
+use std::collections::VecDeque;
+
+use lavalink_rs::model::player::{Filters, Player};
+use lavalink_rs::model::track::TrackData;
+use lavalink_rs::prelude::TrackInQueue;
+
+/// How fast tracks are being played back relative to real time, taking the
+/// timescale filter into account.
+pub fn playback_speed(filters: Option<&Filters>) -> f64 {
+    filters
+        .and_then(|filters| filters.timescale.as_ref())
+        .map_or(1.0, |timescale| {
+            timescale.speed.unwrap_or(1.0) * timescale.rate.unwrap_or(1.0)
+        })
+        .max(f64::EPSILON)
+}
+
+/// Milliseconds left of `track` at `position`, or `None` for livestreams.
+pub fn remaining_ms(track: &TrackData, position: u64) -> Option<u64> {
+    if track.info.is_stream {
+        None
+    } else {
+        Some(track.info.length.saturating_sub(position))
+    }
+}
+
+/// Milliseconds a queued track will play for, honouring its start and end time.
+pub fn queued_length_ms(track: &TrackInQueue) -> Option<u64> {
+    if track.track.info.is_stream {
+        return None;
+    }
+    let end = track
+        .end_time
+        .map_or(track.track.info.length, |end| {
+            u64::try_from(end.as_millis()).unwrap_or(u64::MAX)
+        })
+        .min(track.track.info.length);
+    let start = track.start_time.map_or(0, |start| {
+        u64::try_from(start.as_millis()).unwrap_or(u64::MAX)
+    });
+    Some(end.saturating_sub(start))
+}
+
+/// Offsets in milliseconds of real time from now at which each entry of
+/// `lengths` starts playing. Once an unknown length (a livestream) is hit,
+/// every later offset is unknown too.
+#[allow(
+    clippy::cast_precision_loss,
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss
+)]
+pub fn start_offsets(
+    remaining: Option<u64>,
+    lengths: impl IntoIterator<Item = Option<u64>>,
+    speed: f64,
+) -> Vec<Option<u64>> {
+    let mut elapsed = remaining;
+    lengths
+        .into_iter()
+        .map(|length| {
+            let offset = elapsed.map(|ms| (ms as f64 / speed) as u64);
+            elapsed = elapsed
+                .zip(length)
+                .map(|(elapsed, length)| elapsed + length);
+            offset
+        })
+        .collect()
+}
+
+/// Unix timestamps (in seconds) at which every queued track is expected to
+/// start, or `None` where that can't be known.
+pub fn queue_start_times(player: &Player, tracks: &VecDeque<TrackInQueue>) -> Vec<Option<i64>> {
+    let remaining = player
+        .track
+        .as_ref()
+        .map_or(Some(0), |track| remaining_ms(track, player.state.position));
+    let now = chrono::Utc::now().timestamp();
+
+    start_offsets(
+        remaining,
+        tracks.iter().map(queued_length_ms),
+        playback_speed(player.filters.as_ref()),
+    )
+    .into_iter()
+    .map(|offset| {
+        offset
+            .and_then(|ms| i64::try_from(ms / 1000).ok())
+            .map(|s| now + s)
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_start_offsets_accumulate() {
+        let offsets = start_offsets(Some(1_000), [Some(2_000), Some(3_000), Some(500)], 1.0);
+        assert_eq!(offsets, [Some(1_000), Some(3_000), Some(6_000)]);
+    }
+
+    #[test]
+    fn test_start_offsets_scale_with_speed() {
+        let offsets = start_offsets(Some(1_000), [Some(3_000), Some(1_000)], 2.0);
+        assert_eq!(offsets, [Some(500), Some(2_000)]);
+    }
+
+    #[test]
+    fn test_start_offsets_unknown_after_stream() {
+        let offsets = start_offsets(Some(0), [Some(1_000), None, Some(1_000)], 1.0);
+        assert_eq!(offsets, [Some(0), Some(1_000), None]);
+    }
+
+    #[test]
+    fn test_start_offsets_unknown_while_streaming() {
+        let offsets = start_offsets(None, [Some(1_000), Some(1_000)], 1.0);
+        assert_eq!(offsets, [None, None]);
+    }
+
+    #[test]
+    fn test_start_offsets_empty_queue() {
+        assert!(start_offsets(Some(1_000), [], 1.0).is_empty());
+    }
+}
@@ -0,0 +1,95 @@
+/// Lowercases `text` and splits it into alphanumeric words.
+pub fn normalize(text: &str) -> Vec<String> {
+    text.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_owned)
+        .collect()
+}
+
+/// Levenshtein similarity between two strings in the range `0.0..=1.0`.
+#[allow(clippy::cast_precision_loss)]
+pub fn similarity(a: &str, b: &str) -> f64 {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let longest = a.len().max(b.len());
+    if longest == 0 {
+        return 1.0;
+    }
+
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+    for (i, ca) in a.iter().enumerate() {
+        current[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let cost = usize::from(ca != cb);
+            current[j + 1] = (previous[j] + cost)
+                .min(previous[j + 1] + 1)
+                .min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    1.0 - previous[b.len()] as f64 / longest as f64
+}
+
+/// Whether every word of `query` loosely appears in `haystack`, either as part
+/// of a word or as a close misspelling of one.
+pub fn matches(query: &str, haystack: &str) -> bool {
+    let query_words = normalize(query);
+    if query_words.is_empty() {
+        return false;
+    }
+    let haystack_words = normalize(haystack);
+
+    query_words.iter().all(|query_word| {
+        haystack_words
+            .iter()
+            .any(|word| word.contains(query_word.as_str()) || similarity(query_word, word) >= 0.75)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_strips_punctuation() {
+        assert_eq!(
+            normalize("Don't Stop - Me Now!"),
+            ["don", "t", "stop", "me", "now"]
+        );
+    }
+
+    #[test]
+    fn test_similarity_bounds() {
+        assert_eq!(similarity("queen", "queen"), 1.0);
+        assert_eq!(similarity("", ""), 1.0);
+        assert_eq!(similarity("abc", "xyz"), 0.0);
+    }
+
+    #[test]
+    fn test_matches_is_case_insensitive() {
+        assert!(matches("BOHEMIAN", "Queen - Bohemian Rhapsody"));
+    }
+
+    #[test]
+    fn test_matches_partial_words() {
+        assert!(matches("bohem rhap", "Bohemian Rhapsody"));
+    }
+
+    #[test]
+    fn test_matches_typos() {
+        assert!(matches("rhapsodie", "Bohemian Rhapsody"));
+    }
+
+    #[test]
+    fn test_matches_requires_every_word() {
+        assert!(!matches("bohemian polka", "Bohemian Rhapsody"));
+    }
+
+    #[test]
+    fn test_empty_query_matches_nothing() {
+        assert!(!matches("  ", "Bohemian Rhapsody"));
+    }
+}
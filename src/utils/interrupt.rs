@@ -0,0 +1,33 @@
+use std::time::Duration;
+
+use lavalink_rs::model::track::TrackData;
+use lavalink_rs::player_context::PlayerContext;
+use lavalink_rs::prelude::TrackInQueue;
+
+use crate::utils::track::{RESUMED_FLAG, flag_track};
+
+/// Plays `track` right away and resumes whatever it interrupted once it ends.
+///
+/// The interrupted track is put back at the front of the queue carrying its
+/// position, volume and filters, so the player's normal advance on track end
+/// picks it up exactly where it stopped and the rest of the queue keeps its
+/// order. It is flagged as resumed so it isn't logged as a second play.
+pub async fn interrupt_with(player: &PlayerContext, track: &TrackData) -> anyhow::Result<()> {
+    let player_data = player.get_player().await?;
+
+    if let Some(mut current) = player_data.track {
+        flag_track(&mut current, RESUMED_FLAG);
+        let start_time =
+            (!current.info.is_stream).then(|| Duration::from_millis(player_data.state.position));
+        player.get_queue().push_to_front(TrackInQueue {
+            start_time,
+            volume: Some(player_data.volume),
+            filters: player_data.filters,
+            ..TrackInQueue::from(current)
+        })?;
+    }
+
+    player.play_now(track).await?;
+
+    Ok(())
+}
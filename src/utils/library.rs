@@ -0,0 +1,241 @@
+use std::fs::File;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, Utc};
+use symphonia::core::formats::FormatOptions;
+use symphonia::core::io::MediaSourceStream;
+use symphonia::core::meta::{MetadataOptions, StandardTagKey, Tag};
+use symphonia::core::probe::Hint;
+
+use crate::state::State;
+use crate::utils::fuzzy;
+
+/// File types the scanner reads; Lavalink's local source plays all of them.
+const LIBRARY_EXTENSIONS: &[&str] = &["mp3", "flac", "ogg", "wav", "m4a"];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LibraryTrack {
+    pub path: PathBuf,
+    /// From the tags, or the file name if the file has none.
+    pub title: String,
+    pub artist: Option<String>,
+    pub album: Option<String>,
+    pub duration_ms: Option<u64>,
+}
+
+impl LibraryTrack {
+    pub fn artist_or_unknown(&self) -> &str {
+        self.artist.as_deref().unwrap_or("Unknown artist")
+    }
+}
+
+/// Every track found under the library directory, ordered by path.
+#[derive(Debug, Default)]
+pub struct LibraryIndex {
+    pub tracks: Vec<LibraryTrack>,
+    pub scanned_at: Option<DateTime<Utc>>,
+}
+
+impl LibraryIndex {
+    /// Tracks whose title, artist or album loosely match `query`.
+    pub fn search(&self, query: &str) -> Vec<&LibraryTrack> {
+        self.tracks
+            .iter()
+            .filter(|track| {
+                fuzzy::matches(
+                    query,
+                    &format!(
+                        "{} {} {}",
+                        track.title,
+                        track.artist.as_deref().unwrap_or_default(),
+                        track.album.as_deref().unwrap_or_default()
+                    ),
+                )
+            })
+            .collect()
+    }
+
+    /// The tracks of the first album whose name matches `query`.
+    pub fn album(&self, query: &str) -> Vec<&LibraryTrack> {
+        self.group_by(query, |track| track.album.as_deref())
+    }
+
+    /// The tracks of the first artist whose name matches `query`.
+    pub fn artist(&self, query: &str) -> Vec<&LibraryTrack> {
+        self.group_by(query, |track| track.artist.as_deref())
+    }
+
+    fn group_by(
+        &self,
+        query: &str,
+        key: impl Fn(&LibraryTrack) -> Option<&str>,
+    ) -> Vec<&LibraryTrack> {
+        let Some(name) = self
+            .tracks
+            .iter()
+            .filter_map(&key)
+            .find(|name| name.eq_ignore_ascii_case(query.trim()))
+            .or_else(|| {
+                self.tracks
+                    .iter()
+                    .filter_map(&key)
+                    .find(|name| fuzzy::matches(query, name))
+            })
+        else {
+            return Vec::new();
+        };
+        self.tracks
+            .iter()
+            .filter(|track| key(track) == Some(name))
+            .collect()
+    }
+}
+
+/// Walks `dir` and reads the tags of every audio file in it. Files that
+/// can't be read are logged and skipped. This blocks, so run it off the runtime.
+pub fn scan_library(dir: &Path) -> Result<LibraryIndex> {
+    let mut paths = Vec::new();
+    collect_audio_files(dir, &mut paths)?;
+    paths.sort();
+
+    let tracks = paths
+        .into_iter()
+        .filter_map(|path| match read_track(&path) {
+            Ok(track) => Some(track),
+            Err(e) => {
+                tracing::warn!("Skipping {} in library: {}", path.display(), e);
+                None
+            }
+        })
+        .collect();
+
+    Ok(LibraryIndex {
+        tracks,
+        scanned_at: Some(Utc::now()),
+    })
+}
+
+/// Rescans the configured library directory and swaps in the new index.
+/// Returns the number of tracks found.
+pub async fn rescan_library(state: &State) -> Result<usize> {
+    let dir = state
+        .config
+        .library_dir
+        .clone()
+        .ok_or_else(|| anyhow!("No music library is configured."))?;
+    let index = tokio::task::spawn_blocking(move || scan_library(&dir)).await??;
+    let count = index.tracks.len();
+    *state.library.lock().await = index;
+    Ok(count)
+}
+
+fn collect_audio_files(dir: &Path, paths: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_audio_files(&path, paths)?;
+        } else if path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| LIBRARY_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+        {
+            paths.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Reads a file's tags and duration.
+pub fn read_track(path: &Path) -> Result<LibraryTrack> {
+    let source = MediaSourceStream::new(Box::new(File::open(path)?), Default::default());
+    let mut hint = Hint::new();
+    if let Some(ext) = path.extension().and_then(|ext| ext.to_str()) {
+        hint.with_extension(ext);
+    }
+    let mut probed = symphonia::default::get_probe().format(
+        &hint,
+        source,
+        &FormatOptions::default(),
+        &MetadataOptions::default(),
+    )?;
+
+    // ID3 tags sit in front of the container, everything else inside it.
+    let mut tags: Vec<Tag> = Vec::new();
+    if let Some(revision) = probed.metadata.get().as_ref().and_then(|m| m.current()) {
+        tags.extend(revision.tags().iter().cloned());
+    }
+    if let Some(revision) = probed.format.metadata().current() {
+        tags.extend(revision.tags().iter().cloned());
+    }
+    let tag = |wanted: StandardTagKey| {
+        tags.iter()
+            .find(|tag| tag.std_key == Some(wanted))
+            // RIFF INFO values keep their NUL terminator.
+            .map(|tag| {
+                tag.value
+                    .to_string()
+                    .trim_matches(|c: char| c.is_whitespace() || c == '\0')
+                    .to_string()
+            })
+            .filter(|value| !value.is_empty())
+    };
+
+    let duration_ms = probed.format.default_track().and_then(|track| {
+        let params = &track.codec_params;
+        Some(params.n_frames? * 1000 / u64::from(params.sample_rate?))
+    });
+
+    Ok(LibraryTrack {
+        title: tag(StandardTagKey::TrackTitle).unwrap_or_else(|| {
+            path.file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default()
+        }),
+        artist: tag(StandardTagKey::Artist),
+        album: tag(StandardTagKey::Album),
+        duration_ms,
+        path: path.to_path_buf(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture_index() -> LibraryIndex {
+        scan_library(&Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/library")).unwrap()
+    }
+
+    #[test]
+    fn test_scan_reads_tags_and_skips_other_files() {
+        let index = fixture_index();
+        let titles: Vec<_> = index.tracks.iter().map(|t| t.title.as_str()).collect();
+        assert_eq!(titles, ["Harbour Lights", "Tail Lights", "untagged_demo"]);
+        assert!(index.scanned_at.is_some());
+
+        let first = &index.tracks[0];
+        assert_eq!(first.artist.as_deref(), Some("Night Drive"));
+        assert_eq!(first.album.as_deref(), Some("Neon Coast"));
+        assert_eq!(first.duration_ms, Some(500));
+    }
+
+    #[test]
+    fn test_untagged_file_falls_back_to_file_name() {
+        let index = fixture_index();
+        let untagged = &index.tracks[2];
+        assert_eq!(untagged.artist, None);
+        assert_eq!(untagged.artist_or_unknown(), "Unknown artist");
+        assert_eq!(untagged.duration_ms, Some(1000));
+    }
+
+    #[test]
+    fn test_search_album_and_artist() {
+        let index = fixture_index();
+        assert_eq!(index.search("tail").len(), 1);
+        assert_eq!(index.search("night drive").len(), 2);
+        assert_eq!(index.album("neon coast").len(), 2);
+        assert_eq!(index.artist("night").len(), 2);
+        assert!(index.album("nothing like it").is_empty());
+    }
+}
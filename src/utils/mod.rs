@@ -1 +1,19 @@
+pub mod attachment;
+pub mod clips;
+pub mod csv;
+pub mod eta;
+pub mod fuzzy;
+pub mod interrupt;
+pub mod library;
 pub mod lyrics;
+pub mod permissions;
+pub mod playlist_file;
+pub mod podcast;
+pub mod queue_file;
+pub mod queue_history;
+pub mod quiz;
+pub mod radio;
+pub mod track;
+pub mod track_preview;
+pub mod vote;
+pub mod voice;
@@ -0,0 +1,20 @@
+use twilight_cache_inmemory::InMemoryCache;
+use twilight_model::guild::Permissions;
+use twilight_model::id::{
+    Id,
+    marker::{GuildMarker, UserMarker},
+};
+
+/// Whether the member runs the guild: its owner, or anyone with Manage Guild.
+/// `permissions` are the ones Discord sends with interactions; prefix
+/// commands have none, so only the owner passes there.
+pub fn is_guild_manager(
+    cache: &InMemoryCache,
+    guild_id: Id<GuildMarker>,
+    user_id: Id<UserMarker>,
+    permissions: Option<Permissions>,
+) -> bool {
+    let is_owner = cache.guild(guild_id).map(|guild| guild.owner_id()) == Some(user_id);
+    is_owner
+        || permissions.is_some_and(|permissions| permissions.contains(Permissions::MANAGE_GUILD))
+}
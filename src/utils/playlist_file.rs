@@ -0,0 +1,358 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use anyhow::{Result, anyhow};
+use roxmltree::Document;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlaylistFormat {
+    M3u,
+    Pls,
+    Xspf,
+}
+
+impl PlaylistFormat {
+    /// Goes by the file extension, then by the first bytes of the file.
+    pub fn detect(filename: &str, contents: &str) -> Option<Self> {
+        let extension = Path::new(filename)
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(str::to_lowercase);
+        match extension.as_deref() {
+            Some("m3u" | "m3u8") => return Some(Self::M3u),
+            Some("pls") => return Some(Self::Pls),
+            Some("xspf") => return Some(Self::Xspf),
+            _ => {}
+        }
+
+        let start = contents.trim_start_matches('\u{feff}').trim_start();
+        if start.starts_with("#EXTM3U") {
+            Some(Self::M3u)
+        } else if start.to_lowercase().starts_with("[playlist]") {
+            Some(Self::Pls)
+        } else if start.starts_with("<?xml") || start.starts_with("<playlist") {
+            Some(Self::Xspf)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PlaylistEntry {
+    /// A URL or a path on the machine the playlist was exported from.
+    pub location: Option<String>,
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    pub duration_secs: Option<u64>,
+}
+
+/// How an entry should be looked up.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EntryQuery {
+    Url(String),
+    Search(String),
+}
+
+impl PlaylistEntry {
+    /// URLs are loaded directly. Anything else becomes an "artist - title"
+    /// search, falling back to the file name of a local path.
+    pub fn query(&self) -> Option<EntryQuery> {
+        if let Some(location) = &self.location
+            && (location.starts_with("http://") || location.starts_with("https://"))
+        {
+            return Some(EntryQuery::Url(location.clone()));
+        }
+        match (&self.artist, &self.title) {
+            (Some(artist), Some(title)) => {
+                return Some(EntryQuery::Search(format!("{} - {}", artist, title)));
+            }
+            (None, Some(title)) => return Some(EntryQuery::Search(title.clone())),
+            _ => {}
+        }
+        self.location
+            .as_deref()
+            .and_then(file_stem)
+            .map(EntryQuery::Search)
+    }
+
+    /// How the entry is named in the import summary.
+    pub fn label(&self) -> String {
+        match (&self.artist, &self.title, &self.location) {
+            (Some(artist), Some(title), _) => format!("{} - {}", artist, title),
+            (None, Some(title), _) => title.clone(),
+            (_, None, Some(location)) => location.clone(),
+            _ => "untitled entry".to_string(),
+        }
+    }
+}
+
+pub fn parse_playlist(format: PlaylistFormat, contents: &str) -> Result<Vec<PlaylistEntry>> {
+    let contents = contents.trim_start_matches('\u{feff}');
+    match format {
+        PlaylistFormat::M3u => Ok(parse_m3u(contents)),
+        PlaylistFormat::Pls => Ok(parse_pls(contents)),
+        PlaylistFormat::Xspf => parse_xspf(contents),
+    }
+}
+
+/// Plain and extended M3U. `#EXTINF:<seconds>,<artist> - <title>` describes
+/// the location on the next line; other `#` lines are comments.
+pub fn parse_m3u(contents: &str) -> Vec<PlaylistEntry> {
+    let mut entries = Vec::new();
+    let mut pending = PlaylistEntry::default();
+
+    for line in contents.lines().map(str::trim) {
+        if line.is_empty() {
+            continue;
+        }
+        if let Some(info) = line.strip_prefix("#EXTINF:") {
+            let (duration, name) = info.split_once(',').unwrap_or((info, ""));
+            // Attributes such as `tvg-id="…"` may follow the duration.
+            pending.duration_secs = duration
+                .split_whitespace()
+                .next()
+                .and_then(|secs| secs.parse::<i64>().ok())
+                .and_then(|secs| u64::try_from(secs).ok());
+            let (artist, title) = split_artist_title(name);
+            pending.artist = artist;
+            pending.title = title;
+        } else if !line.starts_with('#') {
+            pending.location = Some(line.to_string());
+            entries.push(std::mem::take(&mut pending));
+        }
+    }
+
+    entries
+}
+
+/// PLS files number their keys (`File1`, `Title1`, `Length1`); entries are
+/// returned in that order, whatever order the lines are in.
+pub fn parse_pls(contents: &str) -> Vec<PlaylistEntry> {
+    let mut entries: BTreeMap<u32, PlaylistEntry> = BTreeMap::new();
+
+    for line in contents.lines().map(str::trim) {
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let (key, value) = (key.trim().to_lowercase(), value.trim());
+        let split = key.find(|c: char| c.is_ascii_digit()).unwrap_or(key.len());
+        let (field, number) = key.split_at(split);
+        let Ok(number) = number.parse::<u32>() else {
+            continue;
+        };
+        let entry = entries.entry(number).or_default();
+        match field {
+            "file" => entry.location = non_empty(value),
+            "title" => {
+                let (artist, title) = split_artist_title(value);
+                entry.artist = artist;
+                entry.title = title;
+            }
+            "length" => {
+                entry.duration_secs = value
+                    .parse::<i64>()
+                    .ok()
+                    .and_then(|s| u64::try_from(s).ok());
+            }
+            _ => {}
+        }
+    }
+
+    entries
+        .into_values()
+        .filter(|entry| entry.location.is_some())
+        .collect()
+}
+
+pub fn parse_xspf(contents: &str) -> Result<Vec<PlaylistEntry>> {
+    let doc =
+        Document::parse(contents).map_err(|e| anyhow!("The XSPF file is not valid XML: {}", e))?;
+    let root = doc.root_element();
+    if root.tag_name().name() != "playlist" {
+        return Err(anyhow!(
+            "Expected an XSPF <playlist>, found <{}>.",
+            root.tag_name().name()
+        ));
+    }
+
+    let text = |node: roxmltree::Node, name: &str| {
+        node.children()
+            .find(|child| child.is_element() && child.tag_name().name() == name)
+            .and_then(|child| child.text())
+            .and_then(non_empty)
+    };
+
+    let entries = root
+        .descendants()
+        .filter(|node| node.is_element() && node.tag_name().name() == "track")
+        .map(|track| PlaylistEntry {
+            location: text(track, "location"),
+            title: text(track, "title"),
+            artist: text(track, "creator"),
+            duration_secs: text(track, "duration")
+                .and_then(|ms| ms.parse::<u64>().ok())
+                .map(|ms| ms / 1000),
+        })
+        .collect();
+    Ok(entries)
+}
+
+/// `Artist - Title`, or just a title when there's no separator.
+fn split_artist_title(name: &str) -> (Option<String>, Option<String>) {
+    match name.split_once(" - ") {
+        Some((artist, title)) => (non_empty(artist), non_empty(title)),
+        None => (None, non_empty(name)),
+    }
+}
+
+fn non_empty(value: &str) -> Option<String> {
+    let value = value.trim();
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+/// The file name without extension of a Windows, Unix or `file://` path.
+fn file_stem(location: &str) -> Option<String> {
+    let location = location.strip_prefix("file://").unwrap_or(location);
+    let name = location.rsplit(['/', '\\']).next()?;
+    let stem = name.rsplit_once('.').map_or(name, |(stem, _)| stem);
+    non_empty(&percent_decode(stem))
+}
+
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut idx = 0;
+    while idx < bytes.len() {
+        let hex = bytes
+            .get(idx + 1..idx + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[idx], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                idx += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                idx += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const M3U: &str = include_str!("../../tests/fixtures/playlists/road_trip.m3u8");
+    const PLS: &str = include_str!("../../tests/fixtures/playlists/road_trip.pls");
+    const XSPF: &str = include_str!("../../tests/fixtures/playlists/road_trip.xspf");
+
+    fn queries(entries: &[PlaylistEntry]) -> Vec<Option<EntryQuery>> {
+        entries.iter().map(PlaylistEntry::query).collect()
+    }
+
+    #[test]
+    fn test_detect_format() {
+        assert_eq!(
+            PlaylistFormat::detect("mix.M3U8", ""),
+            Some(PlaylistFormat::M3u)
+        );
+        assert_eq!(
+            PlaylistFormat::detect("mix.pls", ""),
+            Some(PlaylistFormat::Pls)
+        );
+        assert_eq!(
+            PlaylistFormat::detect("mix.xspf", ""),
+            Some(PlaylistFormat::Xspf)
+        );
+        assert_eq!(
+            PlaylistFormat::detect("mix.txt", M3U),
+            Some(PlaylistFormat::M3u)
+        );
+        assert_eq!(
+            PlaylistFormat::detect("mix", PLS),
+            Some(PlaylistFormat::Pls)
+        );
+        assert_eq!(
+            PlaylistFormat::detect("mix", XSPF),
+            Some(PlaylistFormat::Xspf)
+        );
+        assert_eq!(PlaylistFormat::detect("mix.txt", "hello"), None);
+    }
+
+    #[test]
+    fn test_parse_m3u() {
+        let entries = parse_playlist(PlaylistFormat::M3u, M3U).unwrap();
+        assert_eq!(entries.len(), 4);
+        assert_eq!(entries[0].artist.as_deref(), Some("Queen"));
+        assert_eq!(entries[0].title.as_deref(), Some("Bohemian Rhapsody"));
+        assert_eq!(entries[0].duration_secs, Some(354));
+        // `#EXTINF:-1` marks a stream of unknown length.
+        assert_eq!(entries[1].duration_secs, None);
+        assert_eq!(entries[2].title, None);
+        assert_eq!(
+            queries(&entries),
+            [
+                Some(EntryQuery::Search("Queen - Bohemian Rhapsody".to_string())),
+                Some(EntryQuery::Url(
+                    "https://stream.radioparadise.com/mp3-192".to_string()
+                )),
+                Some(EntryQuery::Search("Daft Punk - One More Time".to_string())),
+                Some(EntryQuery::Url(
+                    "https://www.youtube.com/watch?v=dQw4w9WgXcQ".to_string()
+                )),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_pls() {
+        let entries = parse_pls(PLS);
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].label(), "Rick Astley - Never Gonna Give You Up");
+        assert_eq!(entries[0].duration_secs, Some(213));
+        assert_eq!(entries[1].title.as_deref(), Some("SomaFM: Groove Salad"));
+        assert_eq!(
+            entries[2].query(),
+            Some(EntryQuery::Search("Dreams".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_parse_xspf() {
+        let entries = parse_xspf(XSPF).unwrap();
+        assert_eq!(entries.len(), 4);
+        assert_eq!(entries[0].duration_secs, Some(354));
+        assert_eq!(
+            queries(&entries),
+            [
+                Some(EntryQuery::Search("Queen - Bohemian Rhapsody".to_string())),
+                Some(EntryQuery::Url(
+                    "https://www.youtube.com/watch?v=dQw4w9WgXcQ".to_string()
+                )),
+                Some(EntryQuery::Search("Fleetwood Mac - Dreams".to_string())),
+                None,
+            ]
+        );
+        assert_eq!(entries[3].label(), "untitled entry");
+    }
+
+    #[test]
+    fn test_parse_xspf_rejects_other_xml() {
+        assert!(parse_xspf("<rss version=\"2.0\"/>").is_err());
+        assert!(parse_xspf("<playlist>").is_err());
+    }
+
+    #[test]
+    fn test_file_stem_decodes_paths() {
+        assert_eq!(
+            file_stem("file:///home/me/Music/Bohemian%20Rhapsody.mp3").as_deref(),
+            Some("Bohemian Rhapsody")
+        );
+        assert_eq!(file_stem("C:\\Music\\Song.mp3").as_deref(), Some("Song"));
+        assert_eq!(file_stem("/music/").as_deref(), None);
+    }
+}
@@ -0,0 +1,325 @@
+use std::time::{Duration, Instant};
+
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, Utc};
+use roxmltree::{Document, Node};
+
+use crate::utils::fuzzy;
+
+/// Positions earlier than this aren't worth resuming from.
+const MIN_RESUME_POSITION_MS: u64 = 30_000;
+/// An episode stopped this close to the end counts as finished.
+const FINISHED_MARGIN_MS: u64 = 60_000;
+/// How often the position of a playing episode is saved.
+const SAVE_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Feed {
+    pub title: String,
+    /// Newest first.
+    pub episodes: Vec<Episode>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Episode {
+    /// The item's guid, or its audio URL when the feed has none.
+    pub id: String,
+    pub title: String,
+    pub url: String,
+    pub published: Option<DateTime<Utc>>,
+    pub duration_secs: Option<u64>,
+}
+
+impl Episode {
+    /// `2024-05-07 · 1h 2m 3s`, leaving out whatever the feed didn't say.
+    pub fn details(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(published) = self.published {
+            parts.push(published.format("%Y-%m-%d").to_string());
+        }
+        if let Some(secs) = self.duration_secs {
+            parts.push(humantime::format_duration(Duration::from_secs(secs)).to_string());
+        }
+        if parts.is_empty() {
+            "date unknown".to_string()
+        } else {
+            parts.join(" · ")
+        }
+    }
+}
+
+/// Parses an RSS 2.0 or Atom feed. Entries without an audio enclosure are skipped.
+pub fn parse_feed(xml: &str) -> Result<Feed> {
+    let doc = Document::parse(xml).map_err(|e| anyhow!("The feed is not valid XML: {}", e))?;
+    let root = doc.root_element();
+
+    let mut feed = match root.tag_name().name() {
+        "rss" => {
+            let channel =
+                child(root, "channel").ok_or_else(|| anyhow!("The RSS feed has no <channel>."))?;
+            Feed {
+                title: child_text(channel, "title")
+                    .unwrap_or_else(|| "Untitled podcast".to_string()),
+                episodes: children(channel, "item")
+                    .filter_map(parse_rss_item)
+                    .collect(),
+            }
+        }
+        "feed" => Feed {
+            title: child_text(root, "title").unwrap_or_else(|| "Untitled podcast".to_string()),
+            episodes: children(root, "entry")
+                .filter_map(parse_atom_entry)
+                .collect(),
+        },
+        other => return Err(anyhow!("Expected an RSS or Atom feed, found <{}>.", other)),
+    };
+
+    // Undated episodes keep their feed order, after the dated ones.
+    feed.episodes.sort_by(|a, b| {
+        b.published
+            .is_some()
+            .cmp(&a.published.is_some())
+            .then(b.published.cmp(&a.published))
+    });
+    Ok(feed)
+}
+
+fn parse_rss_item(item: Node) -> Option<Episode> {
+    let url = child(item, "enclosure")?
+        .attribute("url")?
+        .trim()
+        .to_string();
+    Some(Episode {
+        id: child_text(item, "guid").unwrap_or_else(|| url.clone()),
+        title: child_text(item, "title").unwrap_or_else(|| "Untitled episode".to_string()),
+        published: child_text(item, "pubDate")
+            .and_then(|date| DateTime::parse_from_rfc2822(&date).ok())
+            .map(|date| date.with_timezone(&Utc)),
+        duration_secs: child_text(item, "duration").and_then(|d| parse_duration(&d)),
+        url,
+    })
+}
+
+fn parse_atom_entry(entry: Node) -> Option<Episode> {
+    let url = children(entry, "link")
+        .find(|link| link.attribute("rel") == Some("enclosure"))?
+        .attribute("href")?
+        .trim()
+        .to_string();
+    Some(Episode {
+        id: child_text(entry, "id").unwrap_or_else(|| url.clone()),
+        title: child_text(entry, "title").unwrap_or_else(|| "Untitled episode".to_string()),
+        published: child_text(entry, "published")
+            .or_else(|| child_text(entry, "updated"))
+            .and_then(|date| DateTime::parse_from_rfc3339(&date).ok())
+            .map(|date| date.with_timezone(&Utc)),
+        duration_secs: child_text(entry, "duration").and_then(|d| parse_duration(&d)),
+        url,
+    })
+}
+
+/// Child elements are matched on their local name, so `itunes:duration`
+/// is found as `duration`.
+fn children<'a, 'input>(
+    node: Node<'a, 'input>,
+    name: &'static str,
+) -> impl Iterator<Item = Node<'a, 'input>> {
+    node.children()
+        .filter(move |child| child.is_element() && child.tag_name().name() == name)
+}
+
+fn child<'a, 'input>(node: Node<'a, 'input>, name: &'static str) -> Option<Node<'a, 'input>> {
+    children(node, name).next()
+}
+
+fn child_text(node: Node, name: &'static str) -> Option<String> {
+    let text = child(node, name)?.text()?.trim();
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+/// Parses `itunes:duration`, which is either seconds or `[HH:]MM:SS`.
+pub fn parse_duration(value: &str) -> Option<u64> {
+    let value = value.trim();
+    if !value.contains(':') {
+        return value.split('.').next()?.parse().ok();
+    }
+    let parts = value
+        .split(':')
+        .map(|part| part.parse::<u64>().ok())
+        .collect::<Option<Vec<_>>>()?;
+    match parts.as_slice() {
+        [minutes, seconds] => Some(minutes * 60 + seconds),
+        [hours, minutes, seconds] => Some(hours * 3600 + minutes * 60 + seconds),
+        _ => None,
+    }
+}
+
+/// Picks an episode by 1-based number (1 is the newest) or by title.
+/// Without a selector the newest episode is used.
+pub fn select_episode<'a>(feed: &'a Feed, selector: Option<&str>) -> Option<&'a Episode> {
+    let Some(selector) = selector.map(str::trim).filter(|s| !s.is_empty()) else {
+        return feed.episodes.first();
+    };
+    if let Ok(number) = selector.parse::<usize>() {
+        return feed.episodes.get(number.checked_sub(1)?);
+    }
+    feed.episodes
+        .iter()
+        .find(|episode| fuzzy::matches(selector, &episode.title))
+}
+
+/// Where to pick an episode back up, given the saved position and the
+/// episode's length. Starts over if barely started or already finished.
+pub fn resume_position(saved_ms: u64, length_ms: Option<u64>) -> Option<u64> {
+    if saved_ms < MIN_RESUME_POSITION_MS {
+        return None;
+    }
+    if length_ms.is_some_and(|length| saved_ms + FINISHED_MARGIN_MS >= length) {
+        return None;
+    }
+    Some(saved_ms)
+}
+
+/// How far into the playing episode Lavalink last reported, and when that
+/// was last saved.
+#[derive(Debug, Default)]
+pub struct PodcastProgress {
+    position_ms: Option<u64>,
+    saved_at: Option<Instant>,
+}
+
+impl PodcastProgress {
+    /// Records a reported position. Returns true if it is time to save it.
+    pub fn report(&mut self, position_ms: u64, now: Instant) -> bool {
+        self.position_ms = Some(position_ms);
+        if self
+            .saved_at
+            .is_some_and(|saved_at| now.duration_since(saved_at) < SAVE_INTERVAL)
+        {
+            return false;
+        }
+        self.saved_at = Some(now);
+        true
+    }
+
+    /// The last reported position, if any was reported.
+    pub fn position_ms(&self) -> Option<u64> {
+        self.position_ms
+    }
+}
+
+pub async fn fetch_feed(client: &reqwest::Client, url: &str) -> Result<Feed> {
+    let xml = client
+        .get(url)
+        .send()
+        .await?
+        .error_for_status()
+        .map_err(|e| anyhow!("Could not fetch the feed: {}", e))?
+        .text()
+        .await?;
+    parse_feed(&xml)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RSS: &str = include_str!("../../tests/fixtures/podcast_rss.xml");
+    const ATOM: &str = include_str!("../../tests/fixtures/podcast_atom.xml");
+
+    #[test]
+    fn test_parse_rss_feed() {
+        let feed = parse_feed(RSS).unwrap();
+        assert_eq!(feed.title, "Late Night Synths");
+        assert_eq!(feed.episodes.len(), 3);
+
+        let newest = &feed.episodes[0];
+        assert_eq!(newest.id, "lns-0002");
+        assert_eq!(newest.title, "Episode 2: The Juno-106");
+        assert_eq!(newest.url, "https://cdn.example.com/lns/0002.mp3");
+        assert_eq!(newest.duration_secs, Some(3723));
+        assert_eq!(newest.details(), "2024-05-07 · 1h 2m 3s");
+
+        assert_eq!(
+            feed.episodes[1].title,
+            "Episode 1: Why the DX7 & FM synthesis matter"
+        );
+        assert_eq!(feed.episodes[1].duration_secs, Some(2843));
+    }
+
+    #[test]
+    fn test_rss_item_without_guid_or_date() {
+        let feed = parse_feed(RSS).unwrap();
+        let trailer = &feed.episodes[2];
+        assert_eq!(trailer.id, "https://cdn.example.com/lns/trailer.mp3");
+        assert_eq!(trailer.published, None);
+        assert_eq!(trailer.duration_secs, Some(270));
+    }
+
+    #[test]
+    fn test_parse_atom_feed() {
+        let feed = parse_feed(ATOM).unwrap();
+        assert_eq!(feed.title, "Field Recordings Weekly");
+        let titles: Vec<_> = feed.episodes.iter().map(|e| e.title.as_str()).collect();
+        assert_eq!(titles, ["Harbour at dawn", "Rain on a tin roof"]);
+        assert_eq!(
+            feed.episodes[0].url,
+            "https://field.example.com/audio/harbour.mp3"
+        );
+        assert_eq!(feed.episodes[0].duration_secs, Some(1500));
+        // `published` wins over `updated`, and offsets are normalised to UTC.
+        assert_eq!(feed.episodes[0].details(), "2024-06-02 · 25m");
+        assert_eq!(
+            feed.episodes[1].published.unwrap().to_rfc3339(),
+            "2024-05-20T07:00:00+00:00"
+        );
+    }
+
+    #[test]
+    fn test_parse_feed_rejects_other_documents() {
+        assert!(parse_feed("<html><body/></html>").is_err());
+        assert!(parse_feed("not xml").is_err());
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("3600"), Some(3600));
+        assert_eq!(parse_duration("59:59"), Some(3599));
+        assert_eq!(parse_duration("01:00:01"), Some(3601));
+        assert_eq!(parse_duration("12.5"), Some(12));
+        assert_eq!(parse_duration("soon"), None);
+        assert_eq!(parse_duration("1:2:3:4"), None);
+    }
+
+    #[test]
+    fn test_select_episode() {
+        let feed = parse_feed(RSS).unwrap();
+        assert_eq!(select_episode(&feed, None).unwrap().id, "lns-0002");
+        assert_eq!(select_episode(&feed, Some("2")).unwrap().id, "lns-0001");
+        assert_eq!(select_episode(&feed, Some("0")), None);
+        assert_eq!(select_episode(&feed, Some("juno")).unwrap().id, "lns-0002");
+        assert_eq!(
+            select_episode(&feed, Some("trailer")).unwrap().title,
+            "Trailer"
+        );
+    }
+
+    #[test]
+    fn test_resume_position() {
+        assert_eq!(resume_position(5_000, Some(3_600_000)), None);
+        assert_eq!(resume_position(600_000, Some(3_600_000)), Some(600_000));
+        assert_eq!(resume_position(3_580_000, Some(3_600_000)), None);
+        assert_eq!(resume_position(600_000, None), Some(600_000));
+    }
+
+    #[test]
+    fn test_podcast_progress_saves_every_interval() {
+        let start = Instant::now();
+        let mut progress = PodcastProgress::default();
+        assert_eq!(progress.position_ms(), None);
+        assert!(progress.report(1_000, start));
+        assert!(!progress.report(6_000, start + Duration::from_secs(5)));
+        assert_eq!(progress.position_ms(), Some(6_000));
+        assert!(progress.report(31_000, start + SAVE_INTERVAL));
+    }
+}
@@ -0,0 +1,204 @@
+use anyhow::{Result, anyhow};
+use lavalink_rs::model::track::TrackData;
+use serde::{Deserialize, Serialize};
+use twilight_model::id::{Id, marker::UserMarker};
+
+/// Bumped whenever the layout of [`QueueFile`] changes incompatibly.
+pub const QUEUE_FILE_VERSION: u32 = 1;
+/// Upper bound on how many tracks one `/queue import` restores.
+pub const MAX_QUEUE_FILE_TRACKS: usize = 1000;
+
+/// What `/queue export` uploads and `/queue import` reads back.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QueueFile {
+    pub version: u32,
+    /// Unix timestamp in milliseconds.
+    pub exported_at: i64,
+    #[serde(default)]
+    pub current: Option<ExportedCurrent>,
+    #[serde(default)]
+    pub tracks: Vec<ExportedTrack>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportedTrack {
+    /// The track as Lavalink encodes it.
+    pub encoded: String,
+    /// Only there to make the file readable; import goes by `encoded`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_data: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportedCurrent {
+    #[serde(flatten)]
+    pub track: ExportedTrack,
+    pub position_ms: u64,
+}
+
+impl ExportedTrack {
+    pub fn from_track(track: &TrackData) -> Self {
+        Self {
+            encoded: track.encoded.clone(),
+            title: Some(track.info.title.clone()),
+            user_data: track.user_data.clone(),
+        }
+    }
+}
+
+impl QueueFile {
+    pub fn to_json(&self) -> Result<Vec<u8>> {
+        Ok(serde_json::to_vec_pretty(self)?)
+    }
+
+    /// The current track, if any, followed by the queued ones.
+    pub fn len(&self) -> usize {
+        self.tracks.len() + usize::from(self.current.is_some())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Parses and validates an uploaded queue file.
+pub fn parse_queue_file(contents: &str) -> Result<QueueFile> {
+    let file: QueueFile = serde_json::from_str(contents.trim_start_matches('\u{feff}'))
+        .map_err(|e| anyhow!("That is not a queue export: {}", e))?;
+
+    if file.version == 0 {
+        return Err(anyhow!("The queue file has an invalid version."));
+    }
+    if file.version > QUEUE_FILE_VERSION {
+        return Err(anyhow!(
+            "The queue file is version {}, but only version {} is supported.",
+            file.version,
+            QUEUE_FILE_VERSION
+        ));
+    }
+    if file.is_empty() {
+        return Err(anyhow!("The queue file has no tracks."));
+    }
+    if file.tracks.len() > MAX_QUEUE_FILE_TRACKS {
+        return Err(anyhow!(
+            "The queue file has {} tracks; at most {} can be imported.",
+            file.tracks.len(),
+            MAX_QUEUE_FILE_TRACKS
+        ));
+    }
+
+    let tracks = file.current.iter().map(|c| &c.track).chain(&file.tracks);
+    for (idx, track) in tracks.enumerate() {
+        if track.encoded.trim().is_empty() {
+            return Err(anyhow!(
+                "Track {} in the queue file has no encoded data.",
+                idx + 1
+            ));
+        }
+        if track
+            .user_data
+            .as_ref()
+            .is_some_and(|data| !data.is_object())
+        {
+            return Err(anyhow!(
+                "Track {} in the queue file has malformed user data.",
+                idx + 1
+            ));
+        }
+    }
+
+    Ok(file)
+}
+
+/// The exported `user_data`, credited to `importer`. Whoever requested the
+/// tracks in the file may not even be in this server, so the import counts as
+/// the request.
+pub fn restored_user_data(
+    user_data: Option<serde_json::Value>,
+    importer: Id<UserMarker>,
+) -> serde_json::Value {
+    let mut user_data = user_data
+        .filter(serde_json::Value::is_object)
+        .unwrap_or_else(|| serde_json::json!({}));
+    user_data["requester_id"] = serde_json::json!(importer);
+    user_data["added_at"] = serde_json::json!(chrono::Utc::now().timestamp_millis());
+    user_data
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn track(encoded: &str) -> ExportedTrack {
+        ExportedTrack {
+            encoded: encoded.to_string(),
+            title: None,
+            user_data: Some(serde_json::json!({ "requester_id": "42" })),
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let file = QueueFile {
+            version: QUEUE_FILE_VERSION,
+            exported_at: 1_700_000_000_000,
+            current: Some(ExportedCurrent {
+                track: track("QAAA1"),
+                position_ms: 61_000,
+            }),
+            tracks: vec![track("QAAA2"), track("QAAA3")],
+        };
+        let json = String::from_utf8(file.to_json().unwrap()).unwrap();
+        assert!(json.contains("\"position_ms\": 61000"));
+        let parsed = parse_queue_file(&json).unwrap();
+        assert_eq!(parsed, file);
+        assert_eq!(parsed.len(), 3);
+    }
+
+    #[test]
+    fn test_rejects_bad_files() {
+        assert!(parse_queue_file("#EXTM3U").is_err());
+        assert!(
+            parse_queue_file(
+                r#"{"version": 2, "exported_at": 0, "tracks": [{"encoded": "QAAA"}]}"#
+            )
+            .is_err()
+        );
+        assert!(parse_queue_file(r#"{"version": 1, "exported_at": 0, "tracks": []}"#).is_err());
+        assert!(
+            parse_queue_file(r#"{"version": 1, "exported_at": 0, "tracks": [{"encoded": " "}]}"#)
+                .is_err()
+        );
+        assert!(
+            parse_queue_file(r#"{"version": 1, "exported_at": 0, "tracks": [{"encoded": "QAAA", "user_data": 5}]}"#)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_accepts_queue_without_current_track() {
+        let file = parse_queue_file(
+            r#"{"version": 1, "exported_at": 0, "tracks": [{"encoded": "QAAA"}]}"#,
+        )
+        .unwrap();
+        assert_eq!(file.current, None);
+        assert_eq!(file.tracks[0].user_data, None);
+    }
+
+    #[test]
+    fn test_restored_user_data_credits_the_importer() {
+        let importer = Id::new(7);
+        let kept = restored_user_data(
+            Some(serde_json::json!({ "requester_id": "42", "station_id": "x" })),
+            importer,
+        );
+        assert_eq!(kept["requester_id"], "7");
+        assert_eq!(kept["station_id"], "x");
+
+        let filled = restored_user_data(None, importer);
+        assert_eq!(filled["requester_id"], "7");
+        assert!(filled["added_at"].is_i64());
+    }
+}
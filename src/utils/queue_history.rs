@@ -0,0 +1,112 @@
+use std::collections::{HashMap, VecDeque};
+
+use lavalink_rs::model::track::TrackData;
+use lavalink_rs::prelude::TrackInQueue;
+use twilight_model::id::{Id, marker::GuildMarker};
+
+use crate::command_handler::StateExt;
+use crate::state::State;
+
+/// How many snapshots are kept per guild before the oldest one is dropped.
+pub const MAX_SNAPSHOTS: usize = 10;
+
+/// The player's queue as it was right before a mutating command ran.
+pub struct QueueSnapshot {
+    pub current: Option<TrackData>,
+    /// Position of `current` in milliseconds.
+    pub position: u64,
+    pub queue: VecDeque<TrackInQueue>,
+}
+
+#[derive(Default)]
+pub struct QueueHistory {
+    snapshots: HashMap<Id<GuildMarker>, VecDeque<QueueSnapshot>>,
+}
+
+impl QueueHistory {
+    pub fn push(&mut self, guild_id: Id<GuildMarker>, snapshot: QueueSnapshot) {
+        let stack = self.snapshots.entry(guild_id).or_default();
+        if stack.len() == MAX_SNAPSHOTS {
+            stack.pop_front();
+        }
+        stack.push_back(snapshot);
+    }
+
+    pub fn pop(&mut self, guild_id: Id<GuildMarker>) -> Option<QueueSnapshot> {
+        let stack = self.snapshots.get_mut(&guild_id)?;
+        let snapshot = stack.pop_back();
+        if stack.is_empty() {
+            self.snapshots.remove(&guild_id);
+        }
+        snapshot
+    }
+}
+
+/// Records the current track, its position and the pending queue so `/undo`
+/// can bring them back. Does nothing when there is no player or nothing queued.
+pub async fn snapshot_queue(state: &State, guild_id: Id<GuildMarker>) -> anyhow::Result<()> {
+    let Some(player) = state.lavalink().get_player_context(guild_id) else {
+        return Ok(());
+    };
+
+    let player_data = player.get_player().await?;
+    let queue = player.get_queue().get_queue().await?;
+
+    if player_data.track.is_none() && queue.is_empty() {
+        return Ok(());
+    }
+
+    state.queue_history.lock().await.push(
+        guild_id,
+        QueueSnapshot {
+            current: player_data.track,
+            position: player_data.state.position,
+            queue,
+        },
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(position: u64) -> QueueSnapshot {
+        QueueSnapshot {
+            current: None,
+            position,
+            queue: VecDeque::new(),
+        }
+    }
+
+    #[test]
+    fn test_pop_returns_the_latest_snapshot_first() {
+        let mut history = QueueHistory::default();
+        let guild = Id::new(1);
+        history.push(guild, snapshot(1));
+        history.push(guild, snapshot(2));
+        history.push(Id::new(2), snapshot(3));
+
+        assert_eq!(history.pop(guild).map(|s| s.position), Some(2));
+        assert_eq!(history.pop(guild).map(|s| s.position), Some(1));
+        assert!(history.pop(guild).is_none());
+        assert_eq!(history.pop(Id::new(2)).map(|s| s.position), Some(3));
+    }
+
+    #[test]
+    fn test_push_drops_the_oldest_snapshot_past_the_limit() {
+        let mut history = QueueHistory::default();
+        let guild = Id::new(1);
+        for position in 0..=MAX_SNAPSHOTS as u64 {
+            history.push(guild, snapshot(position));
+        }
+
+        let positions: Vec<_> = std::iter::from_fn(|| history.pop(guild))
+            .map(|s| s.position)
+            .collect();
+        assert_eq!(positions.len(), MAX_SNAPSHOTS);
+        assert_eq!(positions.first(), Some(&(MAX_SNAPSHOTS as u64)));
+        assert_eq!(positions.last(), Some(&1));
+    }
+}
@@ -0,0 +1,313 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use tokio::sync::Notify;
+use twilight_model::id::{
+    Id,
+    marker::{ChannelMarker, MessageMarker, UserMarker},
+};
+
+use crate::utils::fuzzy::{normalize, similarity};
+
+/// How long each excerpt plays, and so how long a round lasts at most.
+pub const EXCERPT_MS: u64 = 20_000;
+pub const TITLE_POINTS: u32 = 2;
+pub const ARTIST_POINTS: u32 = 1;
+/// How close a guess has to be to the answer, see [`similarity`].
+const GUESS_SIMILARITY: f64 = 0.8;
+
+/// The track being guessed in the current round.
+pub struct QuizRound {
+    pub title: String,
+    pub author: String,
+    pub title_by: Option<Id<UserMarker>>,
+    pub artist_by: Option<Id<UserMarker>>,
+}
+
+impl QuizRound {
+    pub fn new(title: String, author: String) -> Self {
+        Self {
+            title,
+            author,
+            title_by: None,
+            artist_by: None,
+        }
+    }
+
+    /// Both the title and the artist have been guessed.
+    pub fn is_solved(&self) -> bool {
+        self.title_by.is_some() && self.artist_by.is_some()
+    }
+}
+
+/// What a guess got right.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuizHit {
+    Title,
+    Artist,
+    Both,
+}
+
+impl QuizHit {
+    pub fn points(self) -> u32 {
+        match self {
+            QuizHit::Title => TITLE_POINTS,
+            QuizHit::Artist => ARTIST_POINTS,
+            QuizHit::Both => TITLE_POINTS + ARTIST_POINTS,
+        }
+    }
+}
+
+/// A quiz running in a guild. Every message in `channel_id` is a guess
+/// while it lasts.
+pub struct QuizGame {
+    pub channel_id: Id<ChannelMarker>,
+    pub rounds: usize,
+    /// One-based; zero before the first round starts.
+    pub round: usize,
+    pub current: Option<QuizRound>,
+    pub scores: HashMap<Id<UserMarker>, u32>,
+    /// The live scoreboard, once posted.
+    pub scoreboard_id: Option<Id<MessageMarker>>,
+    /// Wakes the game up when a round is solved or the quiz is stopped.
+    pub wake: Arc<Notify>,
+}
+
+impl QuizGame {
+    pub fn new(channel_id: Id<ChannelMarker>, rounds: usize) -> Self {
+        Self {
+            channel_id,
+            rounds,
+            round: 0,
+            current: None,
+            scores: HashMap::new(),
+            scoreboard_id: None,
+            wake: Arc::new(Notify::new()),
+        }
+    }
+
+    /// Checks a guess against the current round and awards its points.
+    /// Parts someone else already got are not worth anything.
+    pub fn guess(&mut self, user_id: Id<UserMarker>, guess: &str) -> Option<QuizHit> {
+        let round = self.current.as_mut()?;
+        let title = round.title_by.is_none() && is_title_guess(guess, &round.title, &round.author);
+        let artist = round.artist_by.is_none() && is_artist_guess(guess, &round.author);
+        let hit = match (title, artist) {
+            (true, true) => QuizHit::Both,
+            (true, false) => QuizHit::Title,
+            (false, true) => QuizHit::Artist,
+            (false, false) => return None,
+        };
+        if title {
+            round.title_by = Some(user_id);
+        }
+        if artist {
+            round.artist_by = Some(user_id);
+        }
+        *self.scores.entry(user_id).or_default() += hit.points();
+        Some(hit)
+    }
+
+    /// Players by points, highest first; ties by user id to keep it stable.
+    pub fn standings(&self) -> Vec<(Id<UserMarker>, u32)> {
+        let mut standings: Vec<_> = self
+            .scores
+            .iter()
+            .map(|(&user, &points)| (user, points))
+            .collect();
+        standings.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        standings
+    }
+}
+
+/// The title without the decorations nobody would type, such as
+/// `(feat. …)`, `[Remastered]` or ` - Radio Edit`. Titles uploaded as
+/// `Artist - Title` keep the part after the dash instead.
+pub fn clean_title(title: &str, author: &str) -> String {
+    let mut cleaned = String::with_capacity(title.len());
+    let mut depth = 0usize;
+    for c in title.chars() {
+        match c {
+            '(' | '[' => depth += 1,
+            ')' | ']' => depth = depth.saturating_sub(1),
+            _ if depth == 0 => cleaned.push(c),
+            _ => {}
+        }
+    }
+    let cleaned = match cleaned.split_once(" - ") {
+        Some((artist, title)) if names_author(artist, author) => title,
+        Some((title, _)) => title,
+        None => &cleaned,
+    };
+    let cleaned = cleaned.split(" feat").next().unwrap_or_default();
+    cleaned.trim().to_string()
+}
+
+/// Whether `text` names the track's author, also when the channel it came
+/// from adds to the name, as in `QueenVEVO` or `Queen Official`.
+fn names_author(text: &str, author: &str) -> bool {
+    let squash = |text: &str| normalize(text).concat();
+    let text = squash(text);
+    is_artist_guess(&text, author) || (!text.is_empty() && squash(author).starts_with(&text))
+}
+
+/// The artists credited on a track, e.g. `A, B & C feat. D` is four.
+pub fn artist_names(author: &str) -> Vec<String> {
+    author
+        .replace(" feat. ", ",")
+        .replace(" ft. ", ",")
+        .replace(" & ", ",")
+        .replace(" x ", ",")
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(str::to_owned)
+        .collect()
+}
+
+/// Where the excerpt of a track `length_ms` long starts: past the intro,
+/// with a whole excerpt left before the end. `seed` picks the spot.
+pub fn excerpt_start(length_ms: u64, seed: u64) -> u64 {
+    let earliest = length_ms / 5;
+    let latest = length_ms.saturating_sub(EXCERPT_MS);
+    if latest <= earliest {
+        return latest;
+    }
+    earliest + seed % (latest - earliest)
+}
+
+/// Whether `guess` is close enough to `answer`, ignoring case, punctuation
+/// and a leading "the".
+pub fn is_close_guess(guess: &str, answer: &str) -> bool {
+    let simplify = |text: &str| {
+        let mut words = normalize(text);
+        if words.len() > 1 && words[0] == "the" {
+            words.remove(0);
+        }
+        words.join(" ")
+    };
+    let (guess, answer) = (simplify(guess), simplify(answer));
+    !guess.is_empty() && !answer.is_empty() && similarity(&guess, &answer) >= GUESS_SIMILARITY
+}
+
+fn is_title_guess(guess: &str, title: &str, author: &str) -> bool {
+    is_close_guess(guess, title) || is_close_guess(guess, &clean_title(title, author))
+}
+
+fn is_artist_guess(guess: &str, author: &str) -> bool {
+    is_close_guess(guess, author)
+        || artist_names(author)
+            .iter()
+            .any(|name| is_close_guess(guess, name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn game() -> QuizGame {
+        let mut game = QuizGame::new(Id::new(1), 3);
+        game.current = Some(QuizRound::new(
+            "Bohemian Rhapsody (Remastered 2011)".to_string(),
+            "Queen".to_string(),
+        ));
+        game
+    }
+
+    #[test]
+    fn test_clean_title_drops_decorations() {
+        assert_eq!(
+            clean_title("Bohemian Rhapsody (Remastered 2011)", "Queen"),
+            "Bohemian Rhapsody"
+        );
+        assert_eq!(
+            clean_title("Get Lucky [feat. Pharrell]", "Daft Punk"),
+            "Get Lucky"
+        );
+        assert_eq!(
+            clean_title("Blue Monday - 2016 Remaster", "New Order"),
+            "Blue Monday"
+        );
+        assert_eq!(
+            clean_title("Stay feat. Justin Bieber", "The Kid LAROI"),
+            "Stay"
+        );
+    }
+
+    #[test]
+    fn test_clean_title_skips_a_leading_artist() {
+        assert_eq!(
+            clean_title(
+                "Queen - Bohemian Rhapsody (Official Video)",
+                "Queen Official"
+            ),
+            "Bohemian Rhapsody"
+        );
+        assert_eq!(
+            clean_title("Daft Punk - Get Lucky", "DaftPunkVEVO"),
+            "Get Lucky"
+        );
+    }
+
+    #[test]
+    fn test_artist_title_uploads_split_the_points() {
+        let mut game = QuizGame::new(Id::new(1), 3);
+        game.current = Some(QuizRound::new(
+            "Queen - Bohemian Rhapsody".to_string(),
+            "Queen".to_string(),
+        ));
+        let (alice, bob) = (Id::new(10), Id::new(11));
+
+        assert_eq!(game.guess(alice, "queen"), Some(QuizHit::Artist));
+        assert_eq!(game.guess(bob, "bohemian rhapsody"), Some(QuizHit::Title));
+    }
+
+    #[test]
+    fn test_artist_names_splits_credits() {
+        assert_eq!(
+            artist_names("Daft Punk & Pharrell Williams"),
+            ["Daft Punk", "Pharrell Williams"]
+        );
+        assert_eq!(artist_names("A, B feat. C"), ["A", "B", "C"]);
+    }
+
+    #[test]
+    fn test_excerpt_fits_in_the_track() {
+        for seed in [0, 7, u64::MAX] {
+            let start = excerpt_start(200_000, seed);
+            assert!((40_000..180_000).contains(&start));
+        }
+        assert_eq!(excerpt_start(25_000, 3), 5_000);
+        assert_eq!(excerpt_start(10_000, 3), 0);
+    }
+
+    #[test]
+    fn test_close_guesses_forgive_typos_and_case() {
+        assert!(is_close_guess("bohemian rapsody", "Bohemian Rhapsody"));
+        assert!(is_close_guess("beatles", "The Beatles"));
+        assert!(!is_close_guess("bohemian", "Bohemian Rhapsody"));
+        assert!(!is_close_guess("!!", "Queen"));
+    }
+
+    #[test]
+    fn test_guess_awards_each_part_once() {
+        let mut game = game();
+        let (alice, bob) = (Id::new(10), Id::new(11));
+
+        assert_eq!(game.guess(alice, "queen"), Some(QuizHit::Artist));
+        assert_eq!(game.guess(bob, "queen"), None);
+        assert_eq!(game.guess(bob, "bohemian rhapsody"), Some(QuizHit::Title));
+        assert!(game.current.as_ref().unwrap().is_solved());
+        assert_eq!(
+            game.standings(),
+            [(bob, TITLE_POINTS), (alice, ARTIST_POINTS)]
+        );
+    }
+
+    #[test]
+    fn test_guess_without_a_round_is_ignored() {
+        let mut game = QuizGame::new(Id::new(1), 3);
+        assert_eq!(game.guess(Id::new(10), "queen"), None);
+        assert!(game.standings().is_empty());
+    }
+}
@@ -0,0 +1,218 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use anyhow::{Result, anyhow};
+use serde::Deserialize;
+
+use crate::utils::fuzzy;
+
+/// How often the ICY metadata of a playing station is re-read.
+pub const ICY_REFRESH_INTERVAL: Duration = Duration::from_secs(30);
+/// Give up on a stream that hasn't sent its metadata block within this many bytes.
+const MAX_ICY_METAINT: usize = 256 * 1024;
+/// Give up on a stream that hasn't sent its metadata block within this long.
+const ICY_FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct Station {
+    pub id: String,
+    pub name: String,
+    pub url: String,
+    pub genre: String,
+    pub country: String,
+}
+
+/// The stations `/radio` offers, loaded from a JSON file next to the bot.
+#[derive(Deserialize, Debug, Default)]
+pub struct RadioDirectory {
+    pub stations: Vec<Station>,
+}
+
+impl RadioDirectory {
+    pub fn from_json(json: &str) -> Result<Self> {
+        let directory: Self = serde_json::from_str(json)
+            .map_err(|e| anyhow!("Failed to parse radio stations: {}", e))?;
+        Ok(directory)
+    }
+
+    /// Loads the directory, falling back to an empty one if the file is missing.
+    pub fn load(path: &Path) -> Result<Self> {
+        match std::fs::read_to_string(path) {
+            Ok(json) => Self::from_json(&json),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                tracing::warn!(
+                    "Radio station file {} not found, /radio will be empty",
+                    path.display()
+                );
+                Ok(Self::default())
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn find(&self, id: &str) -> Option<&Station> {
+        self.stations.iter().find(|station| station.id == id)
+    }
+
+    /// Stations whose name, genre or country loosely match `query`; every
+    /// station when the query is empty.
+    pub fn search(&self, query: &str) -> Vec<&Station> {
+        if query.trim().is_empty() {
+            return self.stations.iter().collect();
+        }
+        self.stations
+            .iter()
+            .filter(|station| {
+                station.id == query
+                    || fuzzy::matches(
+                        query,
+                        &format!("{} {} {}", station.name, station.genre, station.country),
+                    )
+            })
+            .collect()
+    }
+
+    /// Stations grouped by genre, in alphabetical order.
+    pub fn by_genre(&self) -> BTreeMap<&str, Vec<&Station>> {
+        let mut genres: BTreeMap<&str, Vec<&Station>> = BTreeMap::new();
+        for station in &self.stations {
+            genres
+                .entry(station.genre.as_str())
+                .or_default()
+                .push(station);
+        }
+        genres
+    }
+}
+
+/// A station that is currently playing in a guild.
+pub struct RadioSession {
+    pub station: Station,
+    pub last_title: Option<String>,
+    /// `None` until the first title lookup.
+    pub last_checked: Option<Instant>,
+}
+
+impl RadioSession {
+    /// Whether the stream's title is due to be looked up again.
+    pub fn needs_refresh(&self) -> bool {
+        self.last_checked
+            .is_none_or(|checked| checked.elapsed() >= ICY_REFRESH_INTERVAL)
+    }
+}
+
+/// Extracts the `StreamTitle` from an ICY metadata block such as
+/// `StreamTitle='Artist - Song';StreamUrl='';`.
+pub fn parse_stream_title(metadata: &str) -> Option<String> {
+    let start = metadata.find("StreamTitle='")? + "StreamTitle='".len();
+    let rest = &metadata[start..];
+    let end = rest.find("';").unwrap_or(rest.trim_end_matches('\0').len());
+    let title = rest[..end].trim().trim_end_matches('\'');
+    (!title.is_empty()).then(|| title.to_string())
+}
+
+/// Reads the current song title a Shoutcast/Icecast stream advertises. The
+/// connection is dropped as soon as the first metadata block is in.
+pub async fn fetch_stream_title(client: &reqwest::Client, url: &str) -> Result<Option<String>> {
+    let mut response = client
+        .get(url)
+        .header("Icy-MetaData", "1")
+        .timeout(ICY_FETCH_TIMEOUT)
+        .send()
+        .await?
+        .error_for_status()?;
+
+    let Some(metaint) = response
+        .headers()
+        .get("icy-metaint")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<usize>().ok())
+        .filter(|metaint| *metaint <= MAX_ICY_METAINT)
+    else {
+        return Ok(None);
+    };
+
+    let mut buffer = Vec::with_capacity(metaint + 1);
+    while buffer.len() <= metaint {
+        match response.chunk().await? {
+            Some(chunk) => buffer.extend_from_slice(&chunk),
+            None => return Ok(None),
+        }
+    }
+
+    let metadata_len = usize::from(buffer[metaint]) * 16;
+    while buffer.len() < metaint + 1 + metadata_len {
+        match response.chunk().await? {
+            Some(chunk) => buffer.extend_from_slice(&chunk),
+            None => return Ok(None),
+        }
+    }
+
+    let metadata = String::from_utf8_lossy(&buffer[metaint + 1..metaint + 1 + metadata_len]);
+    Ok(parse_stream_title(&metadata))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STATIONS: &str = r#"{
+        "stations": [
+            { "id": "groovesalad", "name": "Groove Salad", "url": "https://example.com/gs", "genre": "Ambient", "country": "US" },
+            { "id": "fip", "name": "FIP", "url": "https://example.com/fip", "genre": "Eclectic", "country": "FR" },
+            { "id": "dronezone", "name": "Drone Zone", "url": "https://example.com/dz", "genre": "Ambient", "country": "US" }
+        ]
+    }"#;
+
+    #[test]
+    fn test_parse_stream_title() {
+        assert_eq!(
+            parse_stream_title("StreamTitle='Boards of Canada - Roygbiv';StreamUrl='';"),
+            Some("Boards of Canada - Roygbiv".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_stream_title_with_padding() {
+        assert_eq!(
+            parse_stream_title("StreamTitle='Aphex Twin - Xtal';\0\0\0\0"),
+            Some("Aphex Twin - Xtal".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_stream_title_empty() {
+        assert_eq!(parse_stream_title("StreamTitle='';"), None);
+        assert_eq!(parse_stream_title(""), None);
+    }
+
+    #[test]
+    fn test_directory_groups_by_genre() {
+        let directory = RadioDirectory::from_json(STATIONS).unwrap();
+        let genres = directory.by_genre();
+        assert_eq!(
+            genres.keys().copied().collect::<Vec<_>>(),
+            ["Ambient", "Eclectic"]
+        );
+        assert_eq!(genres["Ambient"].len(), 2);
+    }
+
+    #[test]
+    fn test_directory_search() {
+        let directory = RadioDirectory::from_json(STATIONS).unwrap();
+        let ids: Vec<_> = directory
+            .search("ambient")
+            .iter()
+            .map(|s| s.id.as_str())
+            .collect();
+        assert_eq!(ids, ["groovesalad", "dronezone"]);
+        assert_eq!(directory.search("fr")[0].id, "fip");
+        assert_eq!(directory.search("").len(), 3);
+    }
+
+    #[test]
+    fn test_directory_rejects_invalid_json() {
+        assert!(RadioDirectory::from_json("{ \"stations\": [ { \"id\": 1 } ] }").is_err());
+    }
+}
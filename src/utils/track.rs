@@ -0,0 +1,149 @@
+use std::time::Duration;
+
+use lavalink_rs::model::track::TrackData;
+use twilight_model::id::{Id, marker::UserMarker};
+
+/// The user who queued the track, as recorded in `user_data` by `/play`.
+pub fn requester_id(track: &TrackData) -> Option<Id<UserMarker>> {
+    let value = track.user_data.as_ref()?.get("requester_id")?;
+    let id = match value {
+        serde_json::Value::String(s) => s.parse().ok()?,
+        serde_json::Value::Number(n) => n.as_u64()?,
+        _ => return None,
+    };
+    Id::new_checked(id)
+}
+
+/// Unix timestamp in milliseconds of when the track was queued.
+pub fn added_at(track: &TrackData) -> Option<i64> {
+    track.user_data.as_ref()?.get("added_at")?.as_i64()
+}
+
+/// The `/radio` station the track was tuned in from.
+pub fn station_name(track: &TrackData) -> Option<&str> {
+    track.user_data.as_ref()?.get("station_name")?.as_str()
+}
+
+/// The podcast episode the track was queued from by `/podcast play`.
+pub fn podcast_episode_id(track: &TrackData) -> Option<&str> {
+    track.user_data.as_ref()?.get("podcast_episode")?.as_str()
+}
+
+/// Set in `user_data` on `/quiz` excerpts.
+pub const QUIZ_FLAG: &str = "quiz";
+/// Set in `user_data` on soundboard clips.
+pub const CLIP_FLAG: &str = "clip";
+/// Set in `user_data` on tracks picked up where they left off: after a clip,
+/// or restored by `/undo`, a bookmark or a queue import.
+pub const RESUMED_FLAG: &str = "resumed";
+
+/// Sets `flag` in the track's `user_data`, keeping whatever else is there.
+pub fn flag_track(track: &mut TrackData, flag: &str) {
+    let mut user_data = track
+        .user_data
+        .take()
+        .filter(serde_json::Value::is_object)
+        .unwrap_or_else(|| serde_json::json!({}));
+    user_data[flag] = serde_json::json!(true);
+    track.user_data = Some(user_data);
+}
+
+fn has_flag(track: &TrackData, flag: &str) -> bool {
+    track
+        .user_data
+        .as_ref()
+        .and_then(|user_data| user_data.get(flag))
+        .is_some_and(|value| value.as_bool() == Some(true))
+}
+
+/// Whether the track plays over the queue rather than from it: quiz
+/// excerpts and soundboard clips.
+pub fn is_interruption(track: &TrackData) -> bool {
+    [QUIZ_FLAG, CLIP_FLAG]
+        .iter()
+        .any(|flag| has_flag(track, flag))
+}
+
+/// Whether the track starting is a new play for the play log. Quiz
+/// excerpts, soundboard clips and resumed tracks are not.
+pub fn is_new_play(track: &TrackData) -> bool {
+    ![QUIZ_FLAG, CLIP_FLAG, RESUMED_FLAG]
+        .iter()
+        .any(|flag| has_flag(track, flag))
+}
+
+/// A short key identifying a track that fits comfortably in a component custom id.
+/// Buttons outlive restarts and upgrades, so the hash must never change.
+pub fn track_key(track: &TrackData) -> String {
+    format!("{:016x}", fnv1a(track.encoded.as_bytes()))
+}
+
+/// 64-bit FNV-1a.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+    bytes.iter().fold(OFFSET_BASIS, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(PRIME)
+    })
+}
+
+/// Finds the entry a button made for queue `position` points at, given the
+/// [`track_key`] of every queued track. If the entry moved since, it is only
+/// found when no other entry has the same key, so duplicates can't be mixed up.
+pub fn locate_queue_entry(keys: &[String], position: usize, key: &str) -> Option<usize> {
+    if keys.get(position).is_some_and(|k| k == key) {
+        return Some(position);
+    }
+    let mut matching = keys.iter().enumerate().filter(|(_, k)| *k == key);
+    match (matching.next(), matching.next()) {
+        (Some((idx, _)), None) => Some(idx),
+        _ => None,
+    }
+}
+
+/// Badge shown next to livestreams wherever tracks are listed.
+pub const LIVE_BADGE: &str = "🔴 LIVE";
+
+/// The track's length for display, or [`LIVE_BADGE`] for livestreams.
+pub fn format_length(track: &TrackData) -> String {
+    format_length_ms(track.info.length, track.info.is_stream)
+}
+
+/// [`format_length`] for tracks known only by their saved length.
+pub fn format_length_ms(length_ms: u64, is_stream: bool) -> String {
+    if is_stream {
+        LIVE_BADGE.to_string()
+    } else {
+        humantime::format_duration(Duration::from_secs(length_ms / 1000)).to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys(keys: &[&str]) -> Vec<String> {
+        keys.iter().map(|k| k.to_string()).collect()
+    }
+
+    #[test]
+    fn test_locate_queue_entry_prefers_the_original_position() {
+        let queue = keys(&["a", "b", "a"]);
+        assert_eq!(locate_queue_entry(&queue, 2, "a"), Some(2));
+        assert_eq!(locate_queue_entry(&queue, 0, "a"), Some(0));
+    }
+
+    #[test]
+    fn test_locate_queue_entry_follows_moved_tracks_unless_ambiguous() {
+        assert_eq!(locate_queue_entry(&keys(&["c", "b"]), 0, "b"), Some(1));
+        assert_eq!(locate_queue_entry(&keys(&["c", "a", "a"]), 0, "a"), None);
+        assert_eq!(locate_queue_entry(&keys(&["c"]), 0, "a"), None);
+    }
+
+    #[test]
+    fn test_fnv1a_matches_the_reference_values() {
+        assert_eq!(fnv1a(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fnv1a(b"a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(fnv1a(b"foobar"), 0x8594_4171_f739_67e8);
+    }
+}
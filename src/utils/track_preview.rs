@@ -0,0 +1,85 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use lavalink_rs::model::track::TrackData;
+use twilight_model::id::{Id, marker::UserMarker};
+
+/// How long the buttons on a `/trackinfo` preview keep working.
+pub const PREVIEW_TTL: Duration = Duration::from_secs(15 * 60);
+
+/// Tracks resolved by `/trackinfo` that are waiting for the user to decide
+/// whether to queue them.
+pub struct TrackPreview {
+    pub tracks: Vec<TrackData>,
+    pub playlist_name: Option<String>,
+    pub requester: Id<UserMarker>,
+    pub created_at: Instant,
+}
+
+impl TrackPreview {
+    /// A short description of what the preview would queue.
+    pub fn describe(&self) -> String {
+        match (&self.playlist_name, self.tracks.first()) {
+            (Some(name), _) => format!("playlist [{}] ({} tracks)", name, self.tracks.len()),
+            (None, Some(track)) => format!("`{}`", track.info.title),
+            (None, None) => "nothing".to_string(),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct TrackPreviews {
+    previews: HashMap<String, TrackPreview>,
+    next_id: u64,
+}
+
+impl TrackPreviews {
+    /// Stores a preview and returns the key its buttons should refer to.
+    pub fn insert(&mut self, preview: TrackPreview) -> String {
+        self.previews
+            .retain(|_, preview| preview.created_at.elapsed() < PREVIEW_TTL);
+
+        // Every preview gets its own buttons, even for the same track.
+        self.next_id += 1;
+        let key = self.next_id.to_string();
+        self.previews.insert(key.clone(), preview);
+        key
+    }
+
+    pub fn get(&self, key: &str) -> Option<&TrackPreview> {
+        self.previews
+            .get(key)
+            .filter(|preview| preview.created_at.elapsed() < PREVIEW_TTL)
+    }
+
+    pub fn remove(&mut self, key: &str) -> Option<TrackPreview> {
+        self.previews
+            .remove(key)
+            .filter(|preview| preview.created_at.elapsed() < PREVIEW_TTL)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn preview() -> TrackPreview {
+        TrackPreview {
+            tracks: Vec::new(),
+            playlist_name: None,
+            requester: Id::new(1),
+            created_at: Instant::now(),
+        }
+    }
+
+    #[test]
+    fn test_insert_keeps_earlier_previews() {
+        let mut previews = TrackPreviews::default();
+        let first = previews.insert(preview());
+        let second = previews.insert(preview());
+        assert_ne!(first, second);
+        assert!(previews.get(&first).is_some());
+        assert!(previews.remove(&second).is_some());
+        assert!(previews.get(&second).is_none());
+    }
+}
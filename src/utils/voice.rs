@@ -0,0 +1,26 @@
+use twilight_cache_inmemory::InMemoryCache;
+use twilight_model::id::{
+    Id,
+    marker::{GuildMarker, UserMarker},
+};
+
+/// Everyone but the bot in the bot's voice channel, according to the
+/// voice-state cache.
+pub fn listeners(cache: &InMemoryCache, guild_id: Id<GuildMarker>) -> Vec<Id<UserMarker>> {
+    let Some(bot_id) = cache.current_user().map(|user| user.id) else {
+        return Vec::new();
+    };
+    let Some(channel_id) = cache
+        .voice_state(bot_id, guild_id)
+        .map(|voice_state| voice_state.channel_id())
+    else {
+        return Vec::new();
+    };
+    cache
+        .voice_channel_states(channel_id)
+        .into_iter()
+        .flatten()
+        .map(|voice_state| voice_state.user_id())
+        .filter(|&user_id| user_id != bot_id)
+        .collect()
+}
@@ -0,0 +1,138 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use twilight_model::guild::Permissions;
+use twilight_model::id::{
+    Id,
+    marker::{ChannelMarker, UserMarker},
+};
+
+pub const MIN_CANDIDATES: usize = 3;
+pub const MAX_CANDIDATES: usize = 5;
+/// The longest a poll stays open.
+const MAX_POLL: Duration = Duration::from_secs(60);
+/// Even on a nearly finished track, listeners get this long to vote.
+const MIN_POLL: Duration = Duration::from_secs(15);
+/// The poll closes this long before the track ends, so the winner is in
+/// place when the player moves on.
+const END_MARGIN: Duration = Duration::from_secs(5);
+
+/// A track that can be voted for.
+pub struct VoteCandidate {
+    /// The Lavalink-encoded track.
+    pub encoded: String,
+    pub title: String,
+    pub author: String,
+    /// Already in the queue, so winning moves it rather than adds it.
+    pub queued: bool,
+}
+
+/// An open `/vote-next` poll in a guild.
+pub struct VotePoll {
+    /// Tells this poll's buttons apart from those of older ones.
+    pub id: i64,
+    pub channel_id: Id<ChannelMarker>,
+    pub created_by: Id<UserMarker>,
+    /// What the creator may do, as a winner is queued in their name.
+    pub creator_permissions: Option<Permissions>,
+    pub candidates: Vec<VoteCandidate>,
+    /// Each voter's pick, by candidate index.
+    pub votes: HashMap<Id<UserMarker>, usize>,
+    /// The track playing when the poll opened has finished, so the poll
+    /// closes as soon as the next one starts.
+    pub track_ended: bool,
+}
+
+impl VotePoll {
+    /// Records or changes the user's vote. Returns `false` for an unknown
+    /// candidate.
+    pub fn vote(&mut self, user_id: Id<UserMarker>, candidate: usize) -> bool {
+        if candidate >= self.candidates.len() {
+            return false;
+        }
+        self.votes.insert(user_id, candidate);
+        true
+    }
+
+    /// Votes per candidate, in candidate order.
+    pub fn tallies(&self) -> Vec<usize> {
+        let mut tallies = vec![0; self.candidates.len()];
+        for &candidate in self.votes.values() {
+            tallies[candidate] += 1;
+        }
+        tallies
+    }
+
+    /// The candidate with the most votes; ties go to the one listed first.
+    /// `None` if nobody voted.
+    pub fn winner(&self) -> Option<usize> {
+        let tallies = self.tallies();
+        let most = tallies.iter().copied().max().filter(|&most| most > 0)?;
+        tallies.iter().position(|&votes| votes == most)
+    }
+}
+
+/// How long a poll stays open when the current track has `remaining` left.
+pub fn poll_duration(remaining: Option<Duration>) -> Duration {
+    match remaining {
+        Some(remaining) => remaining
+            .saturating_sub(END_MARGIN)
+            .clamp(MIN_POLL, MAX_POLL),
+        None => MAX_POLL,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn poll() -> VotePoll {
+        let candidate = |title: &str| VoteCandidate {
+            encoded: "QAAA".to_string(),
+            title: title.to_string(),
+            author: "Some Band".to_string(),
+            queued: true,
+        };
+        VotePoll {
+            id: 1,
+            channel_id: Id::new(1),
+            created_by: Id::new(1),
+            creator_permissions: None,
+            candidates: vec![candidate("a"), candidate("b"), candidate("c")],
+            votes: HashMap::new(),
+            track_ended: false,
+        }
+    }
+
+    #[test]
+    fn test_votes_can_change_and_count_once() {
+        let mut poll = poll();
+        let (alice, bob) = (Id::new(10), Id::new(11));
+        assert!(poll.vote(alice, 0));
+        assert!(poll.vote(alice, 2));
+        assert!(poll.vote(bob, 2));
+        assert!(!poll.vote(bob, 3));
+        assert_eq!(poll.tallies(), [0, 0, 2]);
+        assert_eq!(poll.winner(), Some(2));
+    }
+
+    #[test]
+    fn test_ties_go_to_the_first_candidate() {
+        let mut poll = poll();
+        assert_eq!(poll.winner(), None);
+        poll.vote(Id::new(10), 2);
+        poll.vote(Id::new(11), 1);
+        assert_eq!(poll.winner(), Some(1));
+    }
+
+    #[test]
+    fn test_poll_closes_before_the_track_ends() {
+        assert_eq!(poll_duration(None), MAX_POLL);
+        assert_eq!(poll_duration(Some(Duration::from_secs(600))), MAX_POLL);
+        assert_eq!(
+            poll_duration(Some(Duration::from_secs(35))),
+            Duration::from_secs(30)
+        );
+        assert_eq!(poll_duration(Some(Duration::from_secs(3))), MIN_POLL);
+    }
+}